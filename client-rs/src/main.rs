@@ -1,7 +1,6 @@
 use anchor_client::solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
-use borsh::BorshSerialize;
 use dominari::{solana_sdk::{signature::{Keypair, read_keypair_file}, instruction::Instruction}, dominari::*, universe::SerializedComponent};
-use dominari::{universe::Universe, world::World, dominari::Dominari};
+use dominari::{universe::Universe, world::{World, ComponentPermissions}, dominari::Dominari};
 use serde::Deserialize;
 use solana_client_wasm::{solana_sdk::{signer::Signer, transaction::Transaction}, WasmClient};
 use tokio::task::JoinHandle;
@@ -129,10 +128,10 @@ pub async fn register_system_for_component(client: &Client, instance:u64) {
     system_register_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
     client.rpc.send_and_confirm_transaction(&system_register_tx).await.unwrap();
 
-    // Register Components for given system registration
-    println!("Adding components to Dominari registration...", );
+    // Dominari runs the whole game, so it gets every permission on every component
+    println!("Granting Dominari permissions on all components...", );
     let mut add_comp_tx = Transaction::new_with_payer(
-        client.world.add_components_to_system_registration(ComponentSchema::new(&client.world.pubkey).get_all_component_keys(), client.dominari.get_system_signer(), instance, client.id01.pubkey()).await.as_slice(),
+        client.world.grant_component_permissions(ComponentSchema::new(&client.world.pubkey).get_all_component_keys(), ComponentPermissions::all(), client.dominari.get_system_signer(), instance, client.id01.pubkey()).await.as_slice(),
         Some(&client.id01.pubkey())
     );
     add_comp_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
@@ -169,142 +168,31 @@ pub async fn register_blueprints(client: &Client, dir: &String) {
         let mut components: BTreeMap<Pubkey, SerializedComponent> = BTreeMap::new();
         let reference = schemas.key_index.as_ref().unwrap();
 
-        if blueprint.mapmeta.is_some() {
-            components.insert(reference.metadata, SerializedComponent { 
-                max_size: ComponentMapMeta::get_max_size(), 
-                data:  blueprint.mapmeta.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.location.is_some() {
-            components.insert(reference.location, SerializedComponent { 
-                max_size: ComponentLocation::get_max_size(), 
-                data:  blueprint.location.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.feature.is_some() {
-            components.insert(reference.feature, SerializedComponent { 
-                max_size: ComponentFeature::get_max_size(), 
-                data:  blueprint.feature.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.owner.is_some() {
-            components.insert(reference.owner, SerializedComponent { 
-                max_size: ComponentOwner::get_max_size(), 
-                data:  blueprint.owner.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.value.is_some() {
-            components.insert(reference.value, SerializedComponent { 
-                max_size: ComponentValue::get_max_size(), 
-                data:  blueprint.value.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.occupant.is_some() {
-            components.insert(reference.occupant, SerializedComponent { 
-                max_size: ComponentOccupant::get_max_size(), 
-                data:  blueprint.occupant.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.player_stats.is_some() {
-            components.insert(reference.player_stats, SerializedComponent { 
-                max_size: ComponentPlayerStats::get_max_size(), 
-                data:  blueprint.player_stats.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.last_used.is_some() {
-            components.insert(reference.last_used, SerializedComponent { 
-                max_size: ComponentLastUsed::get_max_size(), 
-                data:  blueprint.last_used.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.feature_rank.is_some() {
-            components.insert(reference.feature_rank, SerializedComponent { 
-                max_size: ComponentFeatureRank::get_max_size(), 
-                data:  blueprint.feature_rank.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.range.is_some() {
-            components.insert(reference.range, SerializedComponent { 
-                max_size: ComponentRange::get_max_size(), 
-                data:  blueprint.range.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.drop_table.is_some() {
-            components.insert(reference.drop_table, SerializedComponent { 
-                max_size: ComponentDropTable::get_max_size(), 
-                data:  blueprint.drop_table.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.uses.is_some() {
-            components.insert(reference.uses, SerializedComponent { 
-                max_size: ComponentUses::get_max_size(), 
-                data:  blueprint.uses.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.healing_power.is_some() {
-            components.insert(reference.healing_power, SerializedComponent { 
-                max_size: ComponentHealingPower::get_max_size(), 
-                data:  blueprint.healing_power.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.health.is_some() {
-            components.insert(reference.health, SerializedComponent { 
-                max_size: ComponentHealth::get_max_size(), 
-                data:  blueprint.health.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.damage.is_some() {
-            components.insert(reference.damage, SerializedComponent { 
-                max_size: ComponentDamage::get_max_size(), 
-                data:  blueprint.damage.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.troop_class.is_some() {
-            components.insert(reference.troop_class, SerializedComponent { 
-                max_size: ComponentTroopClass::get_max_size(), 
-                data:  blueprint.troop_class.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.active.is_some() {
-            components.insert(reference.active, SerializedComponent { 
-                max_size: ComponentActive::get_max_size(), 
-                data:  blueprint.active.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.cost.is_some() {
-            components.insert(reference.cost, SerializedComponent { 
-                max_size: ComponentCost::get_max_size(), 
-                data:  blueprint.cost.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
-
-        if blueprint.offchain_metadata.is_some() {
-            components.insert(reference.offchain_metadata, SerializedComponent { 
-                max_size: ComponentOffchainMetadata::get_max_size(), 
-                data:  blueprint.offchain_metadata.as_ref().unwrap().try_to_vec().unwrap()
-            });
-        }
+        insert_blueprint_component(&mut components, reference, &blueprint.metadata);
+        insert_blueprint_component(&mut components, reference, &blueprint.mapmeta);
+        insert_blueprint_component(&mut components, reference, &blueprint.location);
+        insert_blueprint_component(&mut components, reference, &blueprint.feature);
+        insert_blueprint_component(&mut components, reference, &blueprint.owner);
+        insert_blueprint_component(&mut components, reference, &blueprint.value);
+        insert_blueprint_component(&mut components, reference, &blueprint.occupant);
+        insert_blueprint_component(&mut components, reference, &blueprint.player_stats);
+        insert_blueprint_component(&mut components, reference, &blueprint.last_used);
+        insert_blueprint_component(&mut components, reference, &blueprint.feature_rank);
+        insert_blueprint_component(&mut components, reference, &blueprint.range);
+        insert_blueprint_component(&mut components, reference, &blueprint.drop_table);
+        insert_blueprint_component(&mut components, reference, &blueprint.uses);
+        insert_blueprint_component(&mut components, reference, &blueprint.healing_power);
+        insert_blueprint_component(&mut components, reference, &blueprint.health);
+        insert_blueprint_component(&mut components, reference, &blueprint.damage);
+        insert_blueprint_component(&mut components, reference, &blueprint.troop_class);
+        insert_blueprint_component(&mut components, reference, &blueprint.active);
+        insert_blueprint_component(&mut components, reference, &blueprint.cost);
+        insert_blueprint_component(&mut components, reference, &blueprint.offchain_metadata);
+        insert_blueprint_component(&mut components, reference, &blueprint.vision);
 
         // Register Blueprint Tx
         let mut register_blueprint_tx = Transaction::new_with_payer(
-            client.dominari.register_blueprint(client.id01.pubkey(), name.to_string(), components).await.as_slice(),
+            client.dominari.register_blueprint(client.id01.pubkey(), name.to_string(), components, blueprint.is_mod).await.as_slice(),
             Some(&client.id01.pubkey())
         ); 
         register_blueprint_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
@@ -318,6 +206,12 @@ pub async fn register_blueprints(client: &Client, dir: &String) {
     
 }
 
+pub fn insert_blueprint_component<T: Component>(components: &mut BTreeMap<Pubkey, SerializedComponent>, reference: &RelevantComponentKeys, component: &Option<T>) {
+    if component.is_some() {
+        components.insert(T::get_component_key(reference), component.as_ref().unwrap().to_serialized_component());
+    }
+}
+
 pub async fn setup_game(client: &mut Client, path: &String, instance: u64) {
     let mut config:Game = toml::from_str(fs::read_to_string(path.as_str()).unwrap().as_str()).unwrap();
     // Transform the transformation.starting_cards (Strings) into config.config.starting_cards (Pubkeys)
@@ -340,7 +234,9 @@ pub async fn setup_game(client: &mut Client, path: &String, instance: u64) {
     // After the Game & World are Instanced, we need to Register Dominari for all Components
     register_system_for_component(&client, instance).await;
 
-    // Create Map & Tiles & Features
+    // Create Map & Tiles
+    let map_features = config.map.features.clone();
+    let chunked = config.map.mapmeta.chunked;
     map(client, instance, config.map).await;
 
     println!("Creating players...");
@@ -358,43 +254,61 @@ pub async fn setup_game(client: &mut Client, path: &String, instance: u64) {
     let player_ids = client.dominari.get_gamestate(instance).index.as_ref().unwrap().players.clone();
     println!("Players Created: {:?}", player_ids);
 
-    println!("Switching game from Lobby to Play phase...");
+    println!("Switching game from Lobby to Build to Play phase...");
     let mut start_game_tx = Transaction::new_with_payer(
-        &client.dominari.change_game_state(client.id01.pubkey(), instance, player_ids.get(0).unwrap().clone(), PlayPhase::Play),
+        &[
+            client.dominari.set_play_phase(client.id01.pubkey(), instance, PlayPhase::Build),
+            client.dominari.set_play_phase(client.id01.pubkey(), instance, PlayPhase::Play),
+        ].concat(),
         Some(&client.id01.pubkey())
     );
     start_game_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
     client.rpc.send_and_confirm_transaction(&start_game_tx).await.unwrap();
     //send_tx_skip_preflight(start_game_tx);
     println!("Game Started!");
+
+    // Features can only be built once the game is in Play
+    if chunked {
+        if !map_features.is_empty() {
+            println!("Features aren't supported on chunked maps yet, skipping {} features", map_features.len());
+        }
+    } else {
+        features(client, instance, map_features).await;
+    }
 }
 
 pub async fn map(client: &mut Client, instance:u64, map: MapConfig) {
     // Initialize the map
     let max_x = map.mapmeta.max_x;
     let max_y = map.mapmeta.max_y;
-    println!("Initalizing {max_x} by {max_y} map...");
+    println!("Initalizing {max_x} by {max_y} {:?} map...", map.mapmeta.topology);
     let mut init_map_tx = Transaction::new_with_payer(
-        client.dominari.init_map(client.id01.pubkey(), instance, max_x, max_y).as_slice(),
+        client.dominari.init_map(client.id01.pubkey(), instance, max_x, max_y, map.mapmeta.topology.clone(), map.mapmeta.chunked).as_slice(),
         Some(&client.id01.pubkey())
     );
     init_map_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
     client.rpc.send_and_confirm_transaction(&init_map_tx).await.unwrap();
     //send_tx_skip_preflight(init_map_tx);
 
+    if map.mapmeta.chunked {
+        return regions(client, instance, map).await;
+    }
+
     println!("Initializing tiles...");
     // Initalize the Tiles
     let mut tile_txs:Vec<JoinHandle<()>> = vec![];
-    for row in 0..map.mapmeta.max_x {
-        for col in 0..map.mapmeta.max_y {
-            let mut init_tile_tx = Transaction::new_with_payer(
-                client.dominari.init_tile(client.id01.pubkey(), instance, row, col, map.cost_per_tile).as_slice(),
-                Some(&client.id01.pubkey())
-            );
-            init_tile_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
-            //send_tx_skip_preflight(init_tile_tx);
-            tile_txs.push(send_tx_async(client.rpc.clone(), init_tile_tx.clone()));
-        }
+    for location in get_map_locations(&map.mapmeta) {
+        // Tiles not listed in the map's terrain are left without terrain (plain, no modifiers)
+        let terrain = map.terrain.iter().flatten()
+            .find(|t| t.x == location.x && t.y == location.y)
+            .map(|t| ComponentTerrain::from_type(t.terrain.clone()));
+        let mut init_tile_tx = Transaction::new_with_payer(
+            client.dominari.init_tile(client.id01.pubkey(), instance, location.x, location.y, map.cost_per_tile, terrain).as_slice(),
+            Some(&client.id01.pubkey())
+        );
+        init_tile_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
+        //send_tx_skip_preflight(init_tile_tx);
+        tile_txs.push(send_tx_async(client.rpc.clone(), init_tile_tx.clone()));
     }
     for tile_tx in tile_txs {
         tile_tx.await.unwrap();
@@ -402,15 +316,21 @@ pub async fn map(client: &mut Client, instance:u64, map: MapConfig) {
 
     // Reloads the state after map and tiles are initalized
     client.dominari.build_gamestate(instance).await;
+}
 
+pub async fn features(client: &mut Client, instance:u64, features: Vec<Feature>) {
     // Init the Features
     println!("Initializing features...");
-    for feature in map.features {
-        let tile = client.dominari.get_gamestate(instance).get_tile(feature.x, feature.y).unwrap();
+    for feature in features {
+        // Each Feature bumps the Instance Index counter its id comes from
+        client.dominari.get_mut_gamestate(instance).update_instance_index().await;
+        let state = client.dominari.get_gamestate(instance);
+        let tile = state.get_tile(feature.x, feature.y).unwrap();
+        let feature_id = state.index.as_ref().unwrap().next_feature_id();
         //println!("Tile ({},{}) is {}", feature.x, feature.y, tile.0);
 
         let blueprint = Dominari::get_blueprint_key(&feature.feature);
-        let mut feature_tx = Transaction::new_with_payer(&client.dominari.init_feature(client.id01.pubkey(), instance, tile.0, blueprint).as_slice(), Some(&client.id01.pubkey()));
+        let mut feature_tx = Transaction::new_with_payer(&client.dominari.init_feature(client.id01.pubkey(), instance, tile.0, blueprint, feature_id).as_slice(), Some(&client.id01.pubkey()));
         feature_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
         
         //let rpc:RpcClient = RpcClient::new(RPC_URL); rpc.send_transaction_with_config(&feature_tx, RpcSendTransactionConfig {skip_preflight: true, .. Default::default()}).unwrap();
@@ -419,4 +339,35 @@ pub async fn map(client: &mut Client, instance:u64, map: MapConfig) {
         let sig = client.rpc.send_and_confirm_transaction(&feature_tx).await.unwrap();
         println!("Feature {} created at ({},{}): {}", feature.feature, feature.x, feature.y, sig);
     }   
-}
\ No newline at end of file
+}
+
+/**
+ * Chunked maps get one Region per REGION_SIZE x REGION_SIZE block of tiles instead of a Tile entity each.
+ */
+pub async fn regions(client: &mut Client, instance:u64, map: MapConfig) {
+    // Picks up the new Map entity
+    client.dominari.build_gamestate(instance).await;
+    let map_id = client.dominari.get_gamestate(instance).index.as_ref().unwrap().map;
+
+    println!("Initializing regions...");
+    let mut region_txs:Vec<JoinHandle<()>> = vec![];
+    for (region_x, region_y) in get_map_regions(&map.mapmeta) {
+        let mut terrain: Vec<Option<TerrainType>> = vec![None; REGION_SIZE as usize * REGION_SIZE as usize];
+        for t in map.terrain.iter().flatten() {
+            if t.x / REGION_SIZE == region_x && t.y / REGION_SIZE == region_y {
+                terrain[(t.y % REGION_SIZE) as usize * REGION_SIZE as usize + (t.x % REGION_SIZE) as usize] = Some(t.terrain.clone());
+            }
+        }
+        let mut init_region_tx = Transaction::new_with_payer(
+            client.dominari.init_region(client.id01.pubkey(), instance, map_id, region_x, region_y, terrain).as_slice(),
+            Some(&client.id01.pubkey())
+        );
+        init_region_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
+        region_txs.push(send_tx_async(client.rpc.clone(), init_region_tx.clone()));
+    }
+    for region_tx in region_txs {
+        region_tx.await.unwrap();
+    }
+
+    client.dominari.build_gamestate(instance).await;
+}
@@ -2,6 +2,8 @@ use std::rc::Rc;
 use std::sync::mpsc::channel;
 use anchor_client::{solana_sdk::{commitment_config::CommitmentConfig, compute_budget::ComputeBudgetInstruction}, Cluster, EventContext};
 use dominari::gamestate::GameState;
+use dominari::visibility::Visibility;
+use dominari::pathfinding::{find_move_path, find_region_move_path};
 use prettytable::{Table, Cell};
 use std::sync::Arc;
 use futures::lock::Mutex;
@@ -54,7 +56,7 @@ pub async fn game_repl(client: &mut Client, instance: u64) {
                 let mut dom = nus_client.lock().await;
                 dom.get_mut_gamestate(instance).update_entity(x.tile).await;
                 dom.get_mut_gamestate(instance).update_entity(x.unit).await;
-                let player = dom.get_mut_gamestate(instance).get_entity_owner(&x.unit).unwrap().player.unwrap();
+                let player = dom.get_mut_gamestate(instance).get::<ComponentOwner>(x.unit).unwrap().player.unwrap();
                 dom.get_mut_gamestate(instance).update_entity(player).await;
                 dom.get_mut_gamestate(instance).update_instance_index().await;
             }
@@ -100,8 +102,15 @@ pub async fn game_repl(client: &mut Client, instance: u64) {
                 dom.get_mut_gamestate(instance).update_entity(x.attacker).await;
                 dom.get_mut_gamestate(instance).update_entity(x.defender).await;
                 dom.get_mut_gamestate(instance).update_entity(x.defending_tile).await;
-                dom.get_mut_gamestate(instance).update_instance_index().await;
+                // Kills credit score, kills and card drops to the attacker's player
+                if let Some(player) = dom.get_mut_gamestate(instance).get::<ComponentOwner>(x.attacker).unwrap().player {
+                    dom.get_mut_gamestate(instance).update_entity(player).await;
+                }
+                let index = dom.get_mut_gamestate(instance).update_instance_index().await;
                 println!("Damage dealt: {}", x.damage);
+                if index.winner.is_some() {
+                    println!("Game over! Player {} wins", index.winner.unwrap());
+                }
             }
         }
     });
@@ -114,6 +123,117 @@ pub async fn game_repl(client: &mut Client, instance: u64) {
         let args:Vec<String> = input.split_whitespace().map(|s| s.to_string()).collect();
         match args.get(0).unwrap().as_str() {
             // Use Features
+            "use" => {
+                // use <x> <y>
+                let x:u16 = args.get(1).unwrap().parse().unwrap();
+                let y:u16 = args.get(2).unwrap().parse().unwrap();
+                use_feature(
+                    client,
+                    dominari.lock().await.state.get(&instance).unwrap(),
+                    x,
+                    y
+                ).await;
+            }
+            "upgrade" => {
+                // upgrade <x> <y>
+                let x:u16 = args.get(1).unwrap().parse().unwrap();
+                let y:u16 = args.get(2).unwrap().parse().unwrap();
+                upgrade_feature(
+                    client,
+                    dominari.lock().await.state.get(&instance).unwrap(),
+                    x,
+                    y
+                ).await;
+            }
+            "buy" => {
+                // buy <player_id> <x> <y>
+                let player_id:u64 = args.get(1).unwrap().parse().unwrap();
+                let x:u16 = args.get(2).unwrap().parse().unwrap();
+                let y:u16 = args.get(3).unwrap().parse().unwrap();
+                buy_tile(
+                    client,
+                    dominari.lock().await.state.get(&instance).unwrap(),
+                    player_id,
+                    x,
+                    y
+                ).await;
+            }
+            "build" => {
+                // build <x> <y> <feature name>
+                let x:u16 = args.get(1).unwrap().parse().unwrap();
+                let y:u16 = args.get(2).unwrap().parse().unwrap();
+                let feature = args.get(3).unwrap();
+                // Feature ids come off the Instance Index, so it has to be current
+                dominari.lock().await.get_mut_gamestate(instance).update_instance_index().await;
+                build_feature(
+                    client,
+                    dominari.lock().await.state.get(&instance).unwrap(),
+                    x,
+                    y,
+                    feature
+                ).await;
+            }
+            "reclaim" => {
+                // reclaim
+                let mut dom = dominari.lock().await;
+                let reclaimed = reclaim_entities(client, dom.state.get(&instance).unwrap()).await;
+                dom.get_mut_gamestate(instance).remove_entities(&reclaimed);
+                dom.get_mut_gamestate(instance).update_instance_index().await;
+                println!("Reclaimed {} entities", reclaimed.len());
+            }
+            "end" => {
+                // end <player_id>
+                let player_id:u64 = args.get(1).unwrap().parse().unwrap();
+                end_turn(client, instance, player_id).await;
+                let index = dominari.lock().await.get_mut_gamestate(instance).update_instance_index().await;
+                println!("Turn {}: player {}", index.turn_number, index.active_player);
+            }
+            "pause" | "resume" => {
+                // pause <player_id> | resume <player_id>
+                let player_id:u64 = args.get(1).unwrap().parse().unwrap();
+                let game_state = if args[0] == "pause" { PlayPhase::Paused } else { PlayPhase::Play };
+                change_game_state(client, instance, player_id, game_state).await;
+                let index = dominari.lock().await.get_mut_gamestate(instance).update_instance_index().await;
+                println!("Phase: {:?} ({} votes pending)", index.play_phase, index.phase_votes.len());
+            }
+            "claim" => {
+                // claim (only for wallets that joined through join_game)
+                let mut claim_tx = Transaction::new_with_payer(
+                    client.dominari.claim_prize(client.id01.pubkey(), instance).as_slice(),
+                    Some(&client.id01.pubkey())
+                );
+                claim_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
+                client.rpc.send_and_confirm_transaction(&claim_tx).await.unwrap();
+                println!("Prize claimed!");
+            }
+            "tokenize" => {
+                // tokenize <x> <y> mints the token for the unit on the tile
+                let x:u16 = args.get(1).unwrap().parse().unwrap();
+                let y:u16 = args.get(2).unwrap().parse().unwrap();
+                let unit_id = dominari.lock().await.get_gamestate(instance).get_tile_contents(x, y).unwrap().1.unwrap();
+                let mut tokenize_tx = Transaction::new_with_payer(
+                    client.dominari.tokenize_entity(client.id01.pubkey(), instance, unit_id).as_slice(),
+                    Some(&client.id01.pubkey())
+                );
+                tokenize_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
+                client.rpc.send_and_confirm_transaction(&tokenize_tx).await.unwrap();
+                println!("Unit {unit_id} tokenized!");
+            }
+            "take" => {
+                // take <player_id> <x> <y> claims the tokenized unit on the tile for player_id
+                let player_id:u64 = args.get(1).unwrap().parse().unwrap();
+                let x:u16 = args.get(2).unwrap().parse().unwrap();
+                let y:u16 = args.get(3).unwrap().parse().unwrap();
+                let unit_id = dominari.lock().await.get_gamestate(instance).get_tile_contents(x, y).unwrap().1.unwrap();
+                let mut take_tx = Transaction::new_with_payer(
+                    client.dominari.claim_entity(client.id01.pubkey(), instance, unit_id, player_id).as_slice(),
+                    Some(&client.id01.pubkey())
+                );
+                take_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
+                client.rpc.send_and_confirm_transaction(&take_tx).await.unwrap();
+                dominari.lock().await.get_mut_gamestate(instance).update_entity(unit_id).await;
+                println!("Unit {unit_id} now belongs to player {player_id}");
+            }
             "time" => {
                 println!("Slot: {}", client.rpc.get_slot().await.unwrap());
             }
@@ -122,8 +242,8 @@ pub async fn game_repl(client: &mut Client, instance: u64) {
             }
             "tile" => {
                 // tile <x> <y>
-                let x:u8 = args.get(1).unwrap().parse().unwrap();
-                let y:u8 = args.get(2).unwrap().parse().unwrap();
+                let x:u16 = args.get(1).unwrap().parse().unwrap();
+                let y:u16 = args.get(2).unwrap().parse().unwrap();
                 tile_info(
                     dominari.lock().await.state.get(&instance).unwrap(),
                     x,
@@ -132,10 +252,10 @@ pub async fn game_repl(client: &mut Client, instance: u64) {
             }
             "attack" => {
                 // attack <from x> <from y> <to x> <to y>
-                let from_x:u8 = args.get(1).unwrap().parse().unwrap();
-                let from_y:u8 = args.get(2).unwrap().parse().unwrap();
-                let to_x:u8 = args.get(3).unwrap().parse().unwrap();
-                let to_y:u8 = args.get(4).unwrap().parse().unwrap();
+                let from_x:u16 = args.get(1).unwrap().parse().unwrap();
+                let from_y:u16 = args.get(2).unwrap().parse().unwrap();
+                let to_x:u16 = args.get(3).unwrap().parse().unwrap();
+                let to_y:u16 = args.get(4).unwrap().parse().unwrap();
                 attack_tile(
                     client,
                     dominari.lock().await.state.get(&instance).unwrap(),
@@ -147,10 +267,10 @@ pub async fn game_repl(client: &mut Client, instance: u64) {
             }
             "move" => {
                 // move <from x> <from y> <to x> <to y>
-                let from_x:u8 = args.get(1).unwrap().parse().unwrap();
-                let from_y:u8 = args.get(2).unwrap().parse().unwrap();
-                let to_x:u8 = args.get(3).unwrap().parse().unwrap();
-                let to_y:u8 = args.get(4).unwrap().parse().unwrap();
+                let from_x:u16 = args.get(1).unwrap().parse().unwrap();
+                let from_y:u16 = args.get(2).unwrap().parse().unwrap();
+                let to_x:u16 = args.get(3).unwrap().parse().unwrap();
+                let to_y:u16 = args.get(4).unwrap().parse().unwrap();
                 move_unit(
                     client,
                     dominari.lock().await.state.get(&instance).unwrap(),
@@ -163,8 +283,8 @@ pub async fn game_repl(client: &mut Client, instance: u64) {
             "spawn" => {
                 // spawn <player_id> <x> <y> <troop/mod name>
                 let player_id:u64 = args.get(1).unwrap().parse().unwrap();
-                let x:u8 = args.get(2).unwrap().parse().unwrap();
-                let y:u8 = args.get(3).unwrap().parse().unwrap();
+                let x:u16 = args.get(2).unwrap().parse().unwrap();
+                let y:u16 = args.get(3).unwrap().parse().unwrap();
                 let card = args.get(4).unwrap();
                 spawn(
                     client,
@@ -181,7 +301,21 @@ pub async fn game_repl(client: &mut Client, instance: u64) {
             }
             // Print Map
             "map" => {
-                print_map(dominari.lock().await.state.get(&instance).unwrap()).printstd();
+                // map [player_id] to only show what that player can see
+                let dom = dominari.lock().await;
+                let state = dom.state.get(&instance).unwrap();
+                let (view, visibility) = match args.get(1) {
+                    Some(player_id) => {
+                        let player_id:u64 = player_id.parse().unwrap();
+                        (state.view_for(player_id), Some(state.get_visibility(player_id)))
+                    },
+                    None => (state.clone(), None)
+                };
+                let mapmeta = view.get::<ComponentMapMeta>(view.index.as_ref().unwrap().map).unwrap();
+                match mapmeta.topology {
+                    MapTopology::Square => print_map(&view, visibility.as_ref()).printstd(),
+                    MapTopology::Hex => println!("{}", print_hex_map(&view, visibility.as_ref())),
+                }
             }
             "exit" => {break;},
             &_ => {}
@@ -189,9 +323,9 @@ pub async fn game_repl(client: &mut Client, instance: u64) {
     }
 }
 
-pub fn print_map(state: &GameState) -> Table {
+pub fn print_map(state: &GameState, visibility: Option<&Visibility>) -> Table {
     let index = state.index.as_ref().unwrap();
-    let mapmeta = state.get_entity_mapmeta(&index.map).unwrap();
+    let mapmeta = state.get::<ComponentMapMeta>(index.map).unwrap();
 
     let mut table = Table::new();
     
@@ -206,22 +340,26 @@ pub fn print_map(state: &GameState) -> Table {
         for x in 0..mapmeta.max_x {
             let mut tile_info:String = String::from("");
             
-            let tile = state.get_tile(x, y).unwrap();
-            let feature = state.get_feature_on_tile(tile.0);
-            let occupant  = state.get_unit_on_tile(tile.0);
+            // Fogged tiles are blanked out
+            if visibility.is_some() && !visibility.unwrap().can_see(x, y) {
+                row.add_cell(Cell::new("~"));
+                continue;
+            }
+
+            let (feature, occupant) = state.get_tile_contents(x, y).unwrap();
             // Show feature name
-            if feature.0.is_some() {
-                let metadata = state.get_entity_metadata(&feature.0.unwrap()).unwrap();
+            if feature.is_some() {
+                let metadata = state.get::<ComponentMetadata>(feature.unwrap()).unwrap();
                 tile_info += format!("\n{}", metadata.name).as_str();
             }
             // Show unit name
-            if occupant.0.is_some() {
-                let metadata = state.get_entity_metadata(&occupant.0.unwrap()).unwrap();
+            if occupant.is_some() {
+                let metadata = state.get::<ComponentMetadata>(occupant.unwrap()).unwrap();
                 tile_info += format!("\n{}", metadata.name).as_str();
 
                 // Print Unit Owner underneath
-                let owner = state.get_entity_owner(&occupant.0.unwrap()).unwrap();
-                let player = state.get_entity_player_stats(&owner.player.unwrap()).unwrap();
+                let owner = state.get::<ComponentOwner>(occupant.unwrap()).unwrap();
+                let player = state.get::<ComponentPlayerStats>(owner.player.unwrap()).unwrap();
                 tile_info += format!("\n{}", player.name).as_str();
             }
             row.add_cell(Cell::new(tile_info.as_str()));
@@ -231,13 +369,56 @@ pub fn print_map(state: &GameState) -> Table {
     table
 }
 
+/**
+ * Hex maps are drawn as offset rows of [cells], each row shifted half a cell further right than the one above.
+ * Cells show the first letters of the feature and unit on the tile, or its q,r coordinates when empty.
+ */
+pub fn print_hex_map(state: &GameState, visibility: Option<&Visibility>) -> String {
+    let index = state.index.as_ref().unwrap();
+    let mapmeta = state.get::<ComponentMapMeta>(index.map).unwrap();
+    const CELL_WIDTH: usize = 8;
+
+    let mut map = String::new();
+    for y in 0..mapmeta.max_y {
+        let mut line = " ".repeat(y as usize * CELL_WIDTH / 2);
+        for x in 0..mapmeta.max_x {
+            let (feature, occupant) = match state.get_tile_contents(x, y) {
+                Ok(contents) => contents,
+                Err(_) => {
+                    line += " ".repeat(CELL_WIDTH).as_str();
+                    continue;
+                }
+            };
+
+            let mut label = vec![];
+            if visibility.is_some() && !visibility.unwrap().can_see(x, y) {
+                label.push("~".to_string());
+            } else {
+                if let Some(feature_id) = feature {
+                    label.push(state.get::<ComponentMetadata>(feature_id).unwrap().name.chars().take(3).collect());
+                }
+                if let Some(unit_id) = occupant {
+                    label.push(state.get::<ComponentMetadata>(unit_id).unwrap().name.chars().take(3).collect());
+                }
+                if label.is_empty() {
+                    label.push(format!("{x},{y}"));
+                }
+            }
+            line += format!("[{:^width$}]", label.join("/"), width = CELL_WIDTH - 2).as_str();
+        }
+        map += line.trim_end();
+        map += "\n";
+    }
+    map
+}
+
 pub fn players_table(state: &GameState) -> Table {
     let index = state.index.as_ref().unwrap();
     let mut table = Table::new();
     table.add_row(row!["ID", "NAME", "SCORE", "KILLS", "CARDS"]);
 
     for player_id in index.players.iter() {
-        let player_stats = state.get_entity_player_stats(&player_id).unwrap();
+        let player_stats = state.get::<ComponentPlayerStats>(*player_id).unwrap();
         table.add_row(row![
             player_id.to_string(),
             player_stats.name,
@@ -254,57 +435,75 @@ pub fn players_table(state: &GameState) -> Table {
     table
 }
 
-pub fn tile_info(state: &GameState, x:u8, y:u8) {
+pub fn tile_info(state: &GameState, x: u16, y: u16) {
     let tile = state.get_tile(x,y).unwrap();
     let feature = state.get_feature_on_tile(tile.0);
     let occupant  = state.get_unit_on_tile(tile.0);
 
     println!("\n Tile: ({x},{y}) ID {}", tile.0);
+    if let Ok(terrain) = state.get::<ComponentTerrain>(tile.0) {
+        println!("\t{:?}", terrain);
+    }
     if feature.0.is_some() {
         println!("\tFeature:");
         // Feature should print various components based on type of Feature TODO
-        let metadata = state.get_entity_metadata(&feature.0.unwrap()).unwrap();
+        let metadata = state.get::<ComponentMetadata>(feature.0.unwrap()).unwrap();
         println!("\t{}", metadata.name);
     }
     // Show unit name
     if occupant.0.is_some() {
         println!("\tUnit:");
-        let metadata = state.get_entity_metadata(&occupant.0.unwrap()).unwrap();
+        let metadata = state.get::<ComponentMetadata>(occupant.0.unwrap()).unwrap();
         println!("\t{}", metadata.name);
 
         // Print Unit Owner underneath
-        let owner = state.get_entity_owner(&occupant.0.unwrap()).unwrap();
-        let player = state.get_entity_player_stats(&owner.player.unwrap()).unwrap();
+        let owner = state.get::<ComponentOwner>(occupant.0.unwrap()).unwrap();
+        let player = state.get::<ComponentPlayerStats>(owner.player.unwrap()).unwrap();
         println!("\t{}", player.name);
 
         // Health
-        println!("\t{:?}", state.get_entity_health(&occupant.0.unwrap()).unwrap());
+        println!("\t{:?}", state.get::<ComponentHealth>(occupant.0.unwrap()).unwrap());
         // Damage
-        println!("\t{:?}", state.get_entity_damage(&occupant.0.unwrap()).unwrap());
+        println!("\t{:?}", state.get::<ComponentDamage>(occupant.0.unwrap()).unwrap());
         // Class
-        println!("\t{:?}", state.get_entity_troop_class(&occupant.0.unwrap()).unwrap());
+        println!("\t{:?}", state.get::<ComponentTroopClass>(occupant.0.unwrap()).unwrap());
         // Range
-        println!("\t{:?}", state.get_entity_range(&occupant.0.unwrap()).unwrap());
+        println!("\t{:?}", state.get::<ComponentRange>(occupant.0.unwrap()).unwrap());
         // Value
-        println!("\t{:?}", state.get_entity_value(&occupant.0.unwrap()).unwrap());
+        println!("\t{:?}", state.get::<ComponentValue>(occupant.0.unwrap()).unwrap());
         // Last Used
-        println!("\t{:?}", state.get_entity_last_used(&occupant.0.unwrap()).unwrap());
+        println!("\t{:?}", state.get::<ComponentLastUsed>(occupant.0.unwrap()).unwrap());
         // Active
-        println!("\t{:?}", state.get_entity_active(&occupant.0.unwrap()).unwrap());
+        println!("\t{:?}", state.get::<ComponentActive>(occupant.0.unwrap()).unwrap());
 
 
     }
 }
 
-pub async fn move_unit(client: &Client, state: &GameState, from_x: u8, from_y: u8, to_x: u8, to_y: u8) {
+pub async fn move_unit(client: &Client, state: &GameState, from_x: u16, from_y: u16, to_x: u16, to_y: u16) {
+    if state.get::<ComponentMapMeta>(state.index.as_ref().unwrap().map).unwrap().chunked {
+        return move_unit_chunked(client, state, from_x, from_y, to_x, to_y).await;
+    }
     let from_tile = state.get_tile(from_x, from_y).unwrap();
     let to_tile = state.get_tile(to_x, to_y).unwrap();
+    let path = match find_move_path(state, from_tile.0, to_tile.0) {
+        Ok(path) => path,
+        Err(e) => {
+            println!("Can't move there: {}", e);
+            return;
+        }
+    };
+    // Only move if nothing changed since the state was loaded
+    let unit_id = state.get::<ComponentOccupant>(from_tile.0).unwrap().occupant_id.unwrap();
+    let expected_versions = state.get_expected_versions(&vec![from_tile.0, to_tile.0, unit_id]).unwrap();
     let mut move_unit_tx = Transaction::new_with_payer(
         client.dominari.move_unit(
             client.id01.pubkey(),
             state.instance,
             from_tile.0,
-            to_tile.0
+            to_tile.0,
+            path,
+            expected_versions
         ).as_slice(),
         Some(&client.id01.pubkey())
     );
@@ -313,20 +512,84 @@ pub async fn move_unit(client: &Client, state: &GameState, from_x: u8, from_y: u
     send_tx_skip_preflight(move_unit_tx);
 }
 
+pub async fn move_unit_chunked(client: &Client, state: &GameState, from_x: u16, from_y: u16, to_x: u16, to_y: u16) {
+    let unit_id = match state.get_tile_contents(from_x, from_y).unwrap().1 {
+        Some(unit_id) => unit_id,
+        None => {
+            println!("No unit on tile!");
+            return;
+        }
+    };
+    let path = match find_region_move_path(state, unit_id, &ComponentLocation { x: to_x, y: to_y }) {
+        Ok(path) => path,
+        Err(e) => {
+            println!("Can't move there: {}", e);
+            return;
+        }
+    };
+    let mut pinned = vec![unit_id, get_region_id_for(&ComponentLocation { x: from_x, y: from_y }), get_region_id_for(&ComponentLocation { x: to_x, y: to_y })];
+    pinned.dedup();
+    let expected_versions = state.get_expected_versions(&pinned).unwrap();
+    let mut move_unit_tx = Transaction::new_with_payer(
+        client.dominari.move_unit_chunked(
+            client.id01.pubkey(),
+            state.instance,
+            unit_id,
+            path,
+            expected_versions
+        ).as_slice(),
+        Some(&client.id01.pubkey())
+    );
+    move_unit_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
+    send_tx_skip_preflight(move_unit_tx);
+}
+
+/**
+ * Entity actions pass in for the tile at x,y: the tile itself, or the Region holding it on chunked maps.
+ */
+pub fn get_tile_account_id(state: &GameState, x: u16, y: u16) -> u64 {
+    if state.get::<ComponentMapMeta>(state.index.as_ref().unwrap().map).unwrap().chunked {
+        state.get_region_tile(x, y).unwrap().0
+    } else {
+        state.get_tile(x, y).unwrap().0
+    }
+}
+
 // Doesn't support attacking Features yet.
-pub async fn attack_tile(client: &Client, state: &GameState, from_x: u8, from_y: u8, to_x: u8, to_y: u8) {
-    let from_tile = state.get_tile(from_x, from_y).unwrap();
-    let to_tile = state.get_tile(to_x, to_y).unwrap();
-    
-    let attacker = state.get_entity_occupant(&from_tile.0).unwrap().occupant_id.unwrap();
-    let defender = state.get_entity_occupant(&to_tile.0).unwrap().occupant_id.unwrap();
+pub async fn attack_tile(client: &Client, state: &GameState, from_x: u16, from_y: u16, to_x: u16, to_y: u16) {
+    let to_tile = get_tile_account_id(state, to_x, to_y);
+
+    let attacker = state.get_tile_contents(from_x, from_y).unwrap().1.unwrap();
+    let defender = state.get_tile_contents(to_x, to_y).unwrap().1.unwrap();
+    let attacking_player = state.get::<ComponentOwner>(attacker).unwrap().player.unwrap_or(attacker);
+
+    // Commit-reveal games need a commitment landed in an earlier slot than the attack
+    let mut seed = None;
+    if state.index.as_ref().unwrap().config.randomness == RandomnessMode::CommitReveal {
+        let (new_seed, commitment) = client.dominari.new_randomness_seed(state.instance, defender);
+        let mut commit_tx = Transaction::new_with_payer(
+            client.dominari.commit_randomness(client.id01.pubkey(), state.instance, attacker, commitment).as_slice(),
+            Some(&client.id01.pubkey())
+        );
+        commit_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
+        client.rpc.send_and_confirm_transaction(&commit_tx).await.unwrap();
+        // Roll uses the hash of the slot after the commit, wait until it's been produced
+        let committed_by = client.rpc.get_slot().await.unwrap();
+        while client.rpc.get_slot().await.unwrap() <= committed_by + 1 {
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        }
+        seed = Some(new_seed);
+    }
 
     let mut atk_ix = client.dominari.attack_tile(
         client.id01.pubkey(),
         state.instance,
         attacker,
         defender,
-        to_tile.0
+        to_tile,
+        attacking_player,
+        seed,
+        state.get_expected_versions(&vec![attacker, defender, to_tile, attacking_player]).unwrap()
     );
     atk_ix.insert(0, ComputeBudgetInstruction::set_compute_unit_limit(400_000));
 
@@ -339,9 +602,127 @@ pub async fn attack_tile(client: &Client, state: &GameState, from_x: u8, from_y:
     //send_tx_skip_preflight(atk_tile_tx);
 }
 
-pub async fn spawn(client: &Client, state: &GameState, player_id:u64, x:u8, y:u8, card: &String) {
+pub async fn buy_tile(client: &Client, state: &GameState, player_id: u64, x: u16, y: u16) {
+    let tile = state.get_tile(x, y).unwrap();
+    let mut buy_tile_tx = Transaction::new_with_payer(
+        client.dominari.buy_tile(
+            client.id01.pubkey(),
+            state.instance,
+            player_id,
+            tile.0
+        ).as_slice(),
+        Some(&client.id01.pubkey())
+    );
+    buy_tile_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
+    client.rpc.send_and_confirm_transaction(&buy_tile_tx).await.unwrap();
+}
+
+pub async fn build_feature(client: &Client, state: &GameState, x: u16, y: u16, feature: &String) {
+    let tile = state.get_tile(x, y).unwrap();
+    let mut build_feature_tx = Transaction::new_with_payer(
+        client.dominari.init_feature(
+            client.id01.pubkey(),
+            state.instance,
+            tile.0,
+            state.blueprints.get_blueprint_by_name(feature).unwrap(),
+            state.index.as_ref().unwrap().next_feature_id()
+        ).as_slice(),
+        Some(&client.id01.pubkey())
+    );
+    build_feature_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
+    client.rpc.send_and_confirm_transaction(&build_feature_tx).await.unwrap();
+}
+
+// Closes everything the game state says is reclaimable, a few entities per tx
+pub async fn reclaim_entities(client: &Client, state: &GameState) -> Vec<u64> {
+    let reclaimable = state.get_reclaimable();
+    for chunk in reclaimable.chunks(8) {
+        let mut reclaim_tx = Transaction::new_with_payer(
+            client.dominari.reclaim_entities(
+                client.id01.pubkey(),
+                state.instance,
+                chunk.to_vec()
+            ).as_slice(),
+            Some(&client.id01.pubkey())
+        );
+        reclaim_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
+        client.rpc.send_and_confirm_transaction(&reclaim_tx).await.unwrap();
+    }
+    reclaimable.iter().map(|(id, _)| *id).collect()
+}
+
+pub async fn end_turn(client: &Client, instance: u64, player_id: u64) {
+    let mut end_turn_tx = Transaction::new_with_payer(
+        client.dominari.end_turn(
+            client.id01.pubkey(),
+            instance,
+            player_id
+        ).as_slice(),
+        Some(&client.id01.pubkey())
+    );
+    end_turn_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
+    client.rpc.send_and_confirm_transaction(&end_turn_tx).await.unwrap();
+}
+
+pub async fn change_game_state(client: &Client, instance: u64, player_id: u64, game_state: PlayPhase) {
+    let mut change_tx = Transaction::new_with_payer(
+        client.dominari.change_game_state(
+            client.id01.pubkey(),
+            instance,
+            player_id,
+            game_state
+        ).as_slice(),
+        Some(&client.id01.pubkey())
+    );
+    change_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
+    client.rpc.send_and_confirm_transaction(&change_tx).await.unwrap();
+}
+
+pub async fn use_feature(client: &Client, state: &GameState, x: u16, y: u16) {
+    let tile = get_tile_account_id(state, x, y);
+    let (feature, unit) = state.get_tile_contents(x, y).unwrap();
+    let (feature, unit) = (feature.unwrap(), unit.unwrap());
+    let feature_owner = state.get::<ComponentOwner>(feature).unwrap().owner.unwrap();
+
+    let mut use_feature_tx = Transaction::new_with_payer(
+        client.dominari.use_feature(
+            client.id01.pubkey(),
+            state.instance,
+            tile,
+            unit,
+            feature,
+            feature_owner
+        ).as_slice(),
+        Some(&client.id01.pubkey())
+    );
+    use_feature_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
+    client.rpc.send_and_confirm_transaction(&use_feature_tx).await.unwrap();
+}
+
+pub async fn upgrade_feature(client: &Client, state: &GameState, x: u16, y: u16) {
     let tile = state.get_tile(x, y).unwrap();
-    let occupant = state.get_entity_occupant(&tile.0).unwrap().occupant_id;
+    let feature = state.get_feature_on_tile(tile.0).0.unwrap();
+
+    let mut upgrade_feature_tx = Transaction::new_with_payer(
+        client.dominari.upgrade_feature(
+            client.id01.pubkey(),
+            state.instance,
+            feature
+        ).as_slice(),
+        Some(&client.id01.pubkey())
+    );
+    upgrade_feature_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
+    client.rpc.send_and_confirm_transaction(&upgrade_feature_tx).await.unwrap();
+}
+
+pub async fn spawn(client: &Client, state: &GameState, player_id:u64, x: u16, y: u16, card: &String) {
+    // On chunked maps the unit is spawned into the Region holding the tile
+    let (tile_id, location) = if state.get::<ComponentMapMeta>(state.index.as_ref().unwrap().map).unwrap().chunked {
+        (state.get_region_tile(x, y).unwrap().0, Some(ComponentLocation { x, y }))
+    } else {
+        (state.get_tile(x, y).unwrap().0, None)
+    };
+    let occupant = state.get_tile_contents(x, y).unwrap().1;
     // Check if Tile is EMPTY == Use SPAWN UNIT
     // If tile is OCCUPIED == USE MODIFY UNIT
 
@@ -352,8 +733,9 @@ pub async fn spawn(client: &Client, state: &GameState, player_id:u64, x:u8, y:u8
             client.id01.pubkey(),
             state.instance,
             player_id,
-            tile.0,
-            state.blueprints.get_blueprint_by_name(card).unwrap()
+            tile_id,
+            state.blueprints.get_blueprint_by_name(card).unwrap(),
+            location
         );
 
         spawn_ix.insert(0, compute_buget_ix); 
@@ -366,7 +748,18 @@ pub async fn spawn(client: &Client, state: &GameState, player_id:u64, x:u8, y:u8
         client.rpc.send_and_confirm_transaction(&spawn_unit_tx).await.unwrap();
         //send_tx_skip_preflight(spawn_unit_tx);
     } else {
-        // Check if unit belongs to the player_id
-        // If it does, play the modify_unit tx
+        // PLAY MOD on the unit; the program checks the unit belongs to player_id
+        let mut play_mod_tx = Transaction::new_with_payer(
+            client.dominari.play_mod(
+                client.id01.pubkey(),
+                state.instance,
+                player_id,
+                occupant.unwrap(),
+                state.blueprints.get_blueprint_by_name(card).unwrap()
+            ).as_slice(),
+            Some(&client.id01.pubkey())
+        );
+        play_mod_tx.sign(&[&client.id01], client.rpc.get_latest_blockhash().await.unwrap());
+        client.rpc.send_and_confirm_transaction(&play_mod_tx).await.unwrap();
     }
 }
\ No newline at end of file
@@ -17,16 +17,24 @@ pub struct Transformation {
 pub struct MapConfig {
     pub cost_per_tile: u64,
     pub mapmeta: ComponentMapMeta,
-    pub features: Vec<Feature>
+    pub features: Vec<Feature>,
+    pub terrain: Option<Vec<Terrain>>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct Feature {
-    pub x: u8,
-    pub y: u8,
+    pub x: u16,
+    pub y: u16,
     pub feature: String,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+pub struct Terrain {
+    pub x: u16,
+    pub y: u16,
+    pub terrain: TerrainType,
+}
+
 pub struct Client {
     pub id01: Keypair,
     pub rpc: WasmClient,
@@ -2,7 +2,7 @@ use anchor_lang::prelude::*;
 use ecs::state::SerializedComponent;
 use std::collections::BTreeMap;
 
-use crate::{state::*, component::MaxSize};
+use crate::{state::*, component::MaxSize, constant::FEATURE_ID_PREFIX, error::DominariError};
 
 
 #[account]
@@ -17,6 +17,7 @@ pub struct SystemConfig {
 #[account]
 pub struct Blueprint {
     pub name: String,
+    pub is_mod: bool, // Only mods can be played onto units with play_mod
     pub components: BTreeMap<Pubkey, SerializedComponent>
 }
 
@@ -34,7 +35,60 @@ pub struct InstanceIndex {
     pub features: Vec<u64>,
     pub units: Vec<u64>,
     pub players: Vec<u64>,
-    pub play_phase: PlayPhase
+    pub play_phase: PlayPhase,
+    // Only used in turn based games
+    pub turn_number: u64,
+    pub active_player: u64,
+    pub turn_start: u64, // Slot the current turn started in
+    pub standings: Vec<PlayerStanding>,
+    pub winner: Option<u64>,
+    pub phase_votes: Vec<u64>, // Players who voted to toggle Play/Paused since the last phase change
+    pub features_built: u64, // Feeds the entity id of the next Feature built with system_instance_feature
+}
+
+/**
+ * Player's commitment to a randomness seed for one of their units attacking one defender
+ * PDA'd by the unit entity, closed when the seed is revealed in an attack or, as a forfeited attack, once it expires
+ */
+#[account]
+pub struct RandomnessCommit {
+    pub commitment: [u8; 32],
+    pub committer: Pubkey,
+    pub slot: u64,
+    pub target_slot: u64, // Slot whose hash the roll uses, see randomness::get_target_slot
+}
+
+/**
+ * Per instance treasury that collects tile purchases and feature build costs
+ * PDA'd by the world instance, init with the Instance Index
+ */
+#[account]
+pub struct Treasury {
+    pub instance: u64,
+    pub collected: u64, // Total lamports paid in over the life of the instance
+    pub withdrawn: u64, // Total lamports taken out by the Instance Authority
+}
+
+/**
+ * Per instance prize pool that escrows entry fees from join_game
+ * PDA'd by the world instance, init with the Instance Index
+ */
+#[account]
+pub struct Vault {
+    pub instance: u64,
+    pub pool: u64,          // Total entry fees collected, less refunds to players that left
+    pub paid_out: u64,
+    pub entrants: Vec<u64>, // Players that joined through join_game and share the pool
+}
+
+/**
+ * One per wallet per instance, so the same wallet can't join twice
+ * PDA'd by the world instance and the player's wallet, closed on leave_game or claim_prize
+ */
+#[account]
+pub struct PlayerSeat {
+    pub player: u64,
+    pub entry_fee: u64,
 }
 
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
@@ -47,6 +101,27 @@ pub enum PlayPhase {
     Finished
 }
 
+impl InstanceIndex {
+    /**
+     * Mutating game actions can only happen while the game is in Play.
+     * Finished games are locked for good.
+     */
+    pub fn check_in_play(&self) -> Result<()> {
+        if self.play_phase == PlayPhase::Finished {
+            return err!(DominariError::GameFinished)
+        }
+        if self.play_phase != PlayPhase::Play {
+            return err!(DominariError::GamePaused)
+        }
+        Ok(())
+    }
+
+    /// Entity id the next Feature built in this instance gets
+    pub fn next_feature_id(&self) -> u64 {
+        FEATURE_ID_PREFIX | self.features_built
+    }
+}
+
 /**
  * DOES NOT INCLUDE GAME CONFIG SIZE
  * To fetch that, use the get_max_size() function on the config object
@@ -54,6 +129,6 @@ pub enum PlayPhase {
  */
 impl MaxSize for InstanceIndex {
     fn get_max_size() -> u64 {
-        return 8+4+4+4+4+2;
+        return 8+4+4+4+4+2+8+8+8+4+9+4+8;
     }
 }
\ No newline at end of file
@@ -1,11 +1,50 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use ecs::state::SerializedComponent;
 
 use crate::constant::*;
+use crate::state::RelevantComponentKeys;
+use crate::error::ComponentErrors;
 
 pub trait MaxSize {
     fn get_max_size() -> u64;
 }
 
+/**
+ * Schema hashes are stored next to every serialized component on chain.
+ * Any change to a component's fields MUST change its layout string so clients can detect stale data.
+ */
+pub trait SchemaHash {
+    fn get_schema_hash() -> u64;
+}
+
+pub fn compute_schema_hash(layout: &str) -> u64 {
+    let slice = &hash(layout.as_bytes()).to_bytes()[0..8];
+    return u64::from_be_bytes(slice.try_into().unwrap());
+}
+
+/**
+ * Ties a component struct to its schema URL (registered with the World) and its key in RelevantComponentKeys.
+ * Implemented by the relevant_components! list in state.rs, so adding a new component only needs a struct,
+ * its MaxSize/SchemaHash impls and a line in that list.
+ */
+pub trait Component: MaxSize + SchemaHash + AnchorSerialize + AnchorDeserialize {
+    const SCHEMA_URL: &'static str;
+    fn get_component_key(keys: &RelevantComponentKeys) -> Pubkey;
+
+    fn get_schema_url() -> &'static str {
+        Self::SCHEMA_URL
+    }
+
+    fn to_serialized_component(&self) -> SerializedComponent {
+        SerializedComponent {
+            max_size: Self::get_max_size(),
+            schema_hash: Self::get_schema_hash(),
+            data: self.try_to_vec().unwrap()
+        }
+    }
+}
+
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct ComponentMetadata{
@@ -21,7 +60,8 @@ pub enum EntityType {
     Unit,
     Feature,
     Tile,
-    Player
+    Player,
+    Region
 }
 
 impl MaxSize for ComponentMetadata {
@@ -30,29 +70,60 @@ impl MaxSize for ComponentMetadata {
     }
 }
 
+impl SchemaHash for ComponentMetadata {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("metadata{name:String,entity_type:EntityType,world_instance:Pubkey}")
+    }
+}
+
+/**
+ * Square maps fill max_x by max_y.
+ * Hex maps use axial coordinates (q = x, r = y) and are hexagon shaped, centred in max_x by max_y.
+ */
+#[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq)]
+pub enum MapTopology {
+    Square,
+    Hex,
+}
+
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct ComponentMapMeta{
-    pub max_x: u8,
-    pub max_y: u8,
+    pub max_x: u16,
+    pub max_y: u16,
+    pub topology: MapTopology,
+    pub chunked: bool, // Tiles are packed into Region entities instead of one Entity per tile
 }
 
 impl MaxSize for ComponentMapMeta {
     fn get_max_size() -> u64 {
-        return 1 + 1
+        return 2 + 2 + 1 + 1
+    }
+}
+
+impl SchemaHash for ComponentMapMeta {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("mapmeta{max_x:u16,max_y:u16,topology:MapTopology,chunked:bool}")
     }
 }
 
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct ComponentLocation {
-    pub x: u8,
-    pub y: u8
+    pub x: u16,
+    pub y: u16
 }
 
 impl MaxSize for ComponentLocation {
     fn get_max_size() -> u64 {
-        return 1 + 1
+        return 2 + 2
+    }
+}
+
+impl SchemaHash for ComponentLocation {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("location{x:u16,y:u16}")
     }
 }
 
@@ -68,6 +139,12 @@ impl MaxSize for ComponentFeature {
     }
 }
 
+impl SchemaHash for ComponentFeature {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("feature{feature_id:Option<u64>}")
+    }
+}
+
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct ComponentOwner{
@@ -81,6 +158,11 @@ impl MaxSize for ComponentOwner {
     }
 }
 
+impl SchemaHash for ComponentOwner {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("owner{owner:Option<Pubkey>,player:Option<u64>}")
+    }
+}
 
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
@@ -94,6 +176,12 @@ impl MaxSize for ComponentValue {
     }
 }
 
+impl SchemaHash for ComponentValue {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("value{value:u64}")
+    }
+}
+
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct ComponentOccupant{
@@ -106,6 +194,12 @@ impl MaxSize for ComponentOccupant {
     }
 }
 
+impl SchemaHash for ComponentOccupant {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("occupant{occupant_id:Option<u64>}")
+    }
+}
+
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct ComponentPlayerStats{
@@ -123,6 +217,12 @@ impl MaxSize for ComponentPlayerStats {
     }
 }
 
+impl SchemaHash for ComponentPlayerStats {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("player_stats{name:String,image:String,key:Pubkey,score:u64,kills:u64,cards:Vec<Pubkey>}")
+    }
+}
+
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct ComponentLastUsed{
@@ -136,6 +236,12 @@ impl MaxSize for ComponentLastUsed {
     }
 }
 
+impl SchemaHash for ComponentLastUsed {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("last_used{last_used:u64,recovery:u64}")
+    }
+}
+
 // Rank Names and Links restricted to 32 characters otherwise this would be a very expensive component to create
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
@@ -154,6 +260,12 @@ impl MaxSize for ComponentFeatureRank {
     }
 }
 
+impl SchemaHash for ComponentFeatureRank {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("feature_rank{rank:u8,max_rank:u8,cost_for_use_ladder:Vec<u64>,link_rank_ladder:Vec<String>,name_rank_ladder:Vec<String>,per_rank_stat_increase:u64}")
+    }
+}
+
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct ComponentRange{
@@ -167,6 +279,12 @@ impl MaxSize for ComponentRange {
     }
 }
 
+impl SchemaHash for ComponentRange {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("range{movement:u64,attack_range:u64}")
+    }
+}
+
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct ComponentDropTable{
@@ -179,6 +297,12 @@ impl MaxSize for ComponentDropTable {
     }
 }
 
+impl SchemaHash for ComponentDropTable {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("drop_table{drop_table:Vec<Pubkey>}")
+    }
+}
+
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct ComponentUses{
@@ -192,6 +316,12 @@ impl MaxSize for ComponentUses {
     }
 }
 
+impl SchemaHash for ComponentUses {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("uses{uses_left:u64,max_uses:u64}")
+    }
+}
+
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct ComponentHealingPower{
@@ -204,15 +334,28 @@ impl MaxSize for ComponentHealingPower {
     }
 }
 
+impl SchemaHash for ComponentHealingPower {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("healing_power{heals:u64}")
+    }
+}
+
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct ComponentHealth{
     pub health: u64,
+    pub max_health: u64, // Healing can't raise health past this
 }
 
 impl MaxSize for ComponentHealth {
     fn get_max_size() -> u64 {
-        return 8
+        return 8 + 8
+    }
+}
+
+impl SchemaHash for ComponentHealth {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("health{health:u64,max_health:u64}")
     }
 }
 
@@ -233,6 +376,12 @@ impl MaxSize for ComponentDamage {
     }
 }
 
+impl SchemaHash for ComponentDamage {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("damage{min_damage:u64,max_damage:u64,bonus_infantry:u32,bonus_armor:u32,bonus_aircraft:u32,bonus_feature:u32}")
+    }
+}
+
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct ComponentTroopClass{
@@ -245,6 +394,86 @@ impl MaxSize for ComponentTroopClass {
     }
 }
 
+impl SchemaHash for ComponentTroopClass {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("troop_class{class:TroopClass}")
+    }
+}
+
+#[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq)]
+pub enum TerrainType {
+    Plains,
+    Forest,
+    Mountain,
+    Water,
+}
+
+/**
+ * Tile terrain. Move costs are extra movement spent entering the tile (None means the class can't enter it),
+ * defense is subtracted from damage dealt to a unit of that class standing on the tile.
+ */
+#[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct ComponentTerrain{
+    pub terrain: TerrainType,
+    pub move_cost_infantry: Option<u64>,
+    pub move_cost_armor: Option<u64>,
+    pub move_cost_aircraft: Option<u64>,
+    pub defense_infantry: u64,
+    pub defense_armor: u64,
+    pub defense_aircraft: u64,
+}
+
+impl ComponentTerrain {
+    /// Default modifiers for each terrain type
+    pub fn from_type(terrain: TerrainType) -> Self {
+        let (move_cost, defense) = match terrain {
+            TerrainType::Plains => ([Some(0), Some(0), Some(0)], [0, 0, 0]),
+            TerrainType::Forest => ([Some(1), Some(2), Some(0)], [2, 1, 0]),
+            TerrainType::Mountain => ([Some(2), None, Some(0)], [4, 0, 0]),
+            TerrainType::Water => ([None, None, Some(0)], [0, 0, 0]),
+        };
+        ComponentTerrain {
+            terrain,
+            move_cost_infantry: move_cost[0],
+            move_cost_armor: move_cost[1],
+            move_cost_aircraft: move_cost[2],
+            defense_infantry: defense[0],
+            defense_armor: defense[1],
+            defense_aircraft: defense[2],
+        }
+    }
+
+    pub fn get_move_cost(&self, class: &TroopClass) -> Option<u64> {
+        match class {
+            TroopClass::Infantry => self.move_cost_infantry,
+            TroopClass::Armor => self.move_cost_armor,
+            TroopClass::Aircraft => self.move_cost_aircraft,
+        }
+    }
+
+    pub fn get_defense(&self, class: &TroopClass) -> u64 {
+        match class {
+            TroopClass::Infantry => self.defense_infantry,
+            TroopClass::Armor => self.defense_armor,
+            TroopClass::Aircraft => self.defense_aircraft,
+        }
+    }
+}
+
+impl MaxSize for ComponentTerrain {
+    fn get_max_size() -> u64 {
+        return 1 + (9*3) + (8*3)
+    }
+}
+
+impl SchemaHash for ComponentTerrain {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("terrain{terrain:TerrainType,move_cost_infantry:Option<u64>,move_cost_armor:Option<u64>,move_cost_aircraft:Option<u64>,defense_infantry:u64,defense_armor:u64,defense_aircraft:u64}")
+    }
+}
+
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub enum TroopClass {
@@ -265,6 +494,12 @@ impl MaxSize for ComponentActive {
     }
 }
 
+impl SchemaHash for ComponentActive {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("active{active:bool}")
+    }
+}
+
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct ComponentCost{
@@ -277,6 +512,12 @@ impl MaxSize for ComponentCost {
     }
 }
 
+impl SchemaHash for ComponentCost {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("cost{lamports:u64}")
+    }
+}
+
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct ComponentOffchainMetadata{
@@ -288,3 +529,152 @@ impl MaxSize for ComponentOffchainMetadata {
         return STRING_MAX_SIZE*2 //can be 2 times regular string for long url links
     }
 }
+
+impl SchemaHash for ComponentOffchainMetadata {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("offchain_metadata{link:String}")
+    }
+}
+
+#[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct ComponentVision{
+    pub range: u64, // How far (in tiles) the owner can see from this entity
+}
+
+impl MaxSize for ComponentVision {
+    fn get_max_size() -> u64 {
+        return 8
+    }
+}
+
+impl SchemaHash for ComponentVision {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("vision{range:u64}")
+    }
+}
+
+/**
+ * One tile inside a Region: the same data a tile Entity keeps in its Terrain, Feature and Occupant components
+ */
+#[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct RegionTile {
+    pub terrain: Option<TerrainType>,
+    pub feature_id: Option<u64>,
+    pub occupant_id: Option<u64>,
+}
+
+impl MaxSize for RegionTile {
+    fn get_max_size() -> u64 {
+        return 2 + 9 + 9
+    }
+}
+
+/**
+ * REGION_SIZE x REGION_SIZE tiles packed into one entity for chunked maps, stored row major from the origin.
+ * Cells that fall outside the map are None.
+ * Region entity ids are derived from the region's coordinates (see region::get_region_id), so they aren't tracked
+ * in the Instance Index.
+ */
+#[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct ComponentRegion{
+    pub origin_x: u16,
+    pub origin_y: u16,
+    pub tiles: Vec<Option<RegionTile>>,
+}
+
+impl ComponentRegion {
+    pub fn contains(&self, location: &ComponentLocation) -> bool {
+        location.x >= self.origin_x && (location.x as u32) < self.origin_x as u32 + REGION_SIZE as u32 &&
+        location.y >= self.origin_y && (location.y as u32) < self.origin_y as u32 + REGION_SIZE as u32
+    }
+
+    fn get_tile_index(&self, location: &ComponentLocation) -> Option<usize> {
+        if !self.contains(location) {
+            return None
+        }
+        Some((location.y - self.origin_y) as usize * REGION_SIZE as usize + (location.x - self.origin_x) as usize)
+    }
+
+    pub fn get_tile(&self, location: &ComponentLocation) -> Option<&RegionTile> {
+        self.tiles.get(self.get_tile_index(location)?)?.as_ref()
+    }
+
+    pub fn get_tile_mut(&mut self, location: &ComponentLocation) -> Option<&mut RegionTile> {
+        let idx = self.get_tile_index(location)?;
+        self.tiles.get_mut(idx)?.as_mut()
+    }
+
+    /**
+     * Every tile in the region that exists, with its map location.
+     */
+    pub fn iter_tiles(&self) -> impl Iterator<Item = (ComponentLocation, &RegionTile)> + '_ {
+        self.tiles.iter().enumerate().filter_map(move |(idx, tile)| {
+            let location = ComponentLocation {
+                x: self.origin_x.saturating_add((idx % REGION_SIZE as usize) as u16),
+                y: self.origin_y.saturating_add((idx / REGION_SIZE as usize) as u16),
+            };
+            Some((location, tile.as_ref()?))
+        })
+    }
+}
+
+impl MaxSize for ComponentRegion {
+    fn get_max_size() -> u64 {
+        return 2 + 2 + 4 + (REGION_SIZE as u64 * REGION_SIZE as u64 * (1 + RegionTile::get_max_size()))
+    }
+}
+
+impl SchemaHash for ComponentRegion {
+    fn get_schema_hash() -> u64 {
+        return compute_schema_hash("region{origin_x:u16,origin_y:u16,tiles:Vec<Option<RegionTile{terrain:Option<TerrainType>,feature_id:Option<u64>,occupant_id:Option<u64>}>>}")
+    }
+}
+
+/**
+ * Components that make up what a unit is and where it stands in the game, mods can't touch these.
+ */
+pub fn is_moddable(key: &Pubkey, reference: &RelevantComponentKeys) -> bool {
+    let protected = [
+        reference.owner,
+        reference.location,
+        reference.active,
+        reference.last_used,
+        reference.metadata,
+        reference.troop_class,
+    ];
+    !protected.contains(key)
+}
+
+/**
+ * Merges a mod's component data into the same component already on a unit.
+ * Damage, Health and Range stack additively, everything else is replaced by the mod's data.
+ */
+pub fn merge_mod_component(key: &Pubkey, reference: &RelevantComponentKeys, existing: &[u8], modifier: &[u8]) -> Result<Vec<u8>> {
+    if *key == reference.damage {
+        let mut damage = ComponentDamage::try_from_slice(existing).unwrap();
+        let bonus = ComponentDamage::try_from_slice(modifier).unwrap();
+        damage.min_damage = damage.min_damage.checked_add(bonus.min_damage).ok_or(ComponentErrors::StatOverflow)?;
+        damage.max_damage = damage.max_damage.checked_add(bonus.max_damage).ok_or(ComponentErrors::StatOverflow)?;
+        damage.bonus_infantry = damage.bonus_infantry.checked_add(bonus.bonus_infantry).ok_or(ComponentErrors::StatOverflow)?;
+        damage.bonus_armor = damage.bonus_armor.checked_add(bonus.bonus_armor).ok_or(ComponentErrors::StatOverflow)?;
+        damage.bonus_aircraft = damage.bonus_aircraft.checked_add(bonus.bonus_aircraft).ok_or(ComponentErrors::StatOverflow)?;
+        damage.bonus_feature = damage.bonus_feature.checked_add(bonus.bonus_feature).ok_or(ComponentErrors::StatOverflow)?;
+        return Ok(damage.try_to_vec().unwrap())
+    } else if *key == reference.health {
+        let mut health = ComponentHealth::try_from_slice(existing).unwrap();
+        let bonus = ComponentHealth::try_from_slice(modifier).unwrap();
+        health.health = health.health.checked_add(bonus.health).ok_or(ComponentErrors::StatOverflow)?;
+        health.max_health = health.max_health.checked_add(bonus.max_health).ok_or(ComponentErrors::StatOverflow)?;
+        return Ok(health.try_to_vec().unwrap())
+    } else if *key == reference.range {
+        let mut range = ComponentRange::try_from_slice(existing).unwrap();
+        let bonus = ComponentRange::try_from_slice(modifier).unwrap();
+        range.movement = range.movement.checked_add(bonus.movement).ok_or(ComponentErrors::StatOverflow)?;
+        range.attack_range = range.attack_range.checked_add(bonus.attack_range).ok_or(ComponentErrors::StatOverflow)?;
+        return Ok(range.try_to_vec().unwrap())
+    }
+    Ok(modifier.to_vec())
+}
\ No newline at end of file
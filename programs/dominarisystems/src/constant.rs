@@ -2,4 +2,6 @@ pub const STRING_MAX_SIZE: u64 = 128;
 pub const PLAYER_MAX_CARDS: u64 = 10;
 pub const FEATURE_MAX_RANK: u64 = 9;
 pub const FEATURE_MAX_STRING: u64 = 32;
-pub const DROP_TABLE_MAX_SIZE:u64 = 32;
\ No newline at end of file
+pub const DROP_TABLE_MAX_SIZE:u64 = 32;
+pub const REGION_SIZE: u16 = 16; // Region entities hold REGION_SIZE x REGION_SIZE tiles
+pub const FEATURE_ID_PREFIX: u64 = 0x4645_4154_0000_0000; // High bits tag Feature ids built from the Instance Index counter
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use anchor_spl::{token::{Token, TokenAccount}, associated_token::AssociatedToken};
 use std::collections::BTreeMap;
 use crate::{account::*, state::GameConfig};
 use crate::component::MaxSize;
@@ -7,11 +8,12 @@ use crate::state::*;
 
 use ecs::{
     state::SerializedComponent, 
-    account::{WorldInstance, Entity},
+    account::{WorldInstance, Entity, EntityNFT},
+    constant::SERIALIZED_COMPONENT_EXTRA_SPACE,
     program::Ecs
 };
 use dominariworld::{
-    program::Dominariworld, account::{WorldConfig, SystemRegistration}
+    program::Dominariworld, account::{WorldConfig, SystemRegistration, InstanceAuthority}
 };
 
 #[derive(Accounts)]
@@ -50,7 +52,7 @@ pub struct RegisterBlueprint <'info> {
             name.as_bytes().as_ref()
         ],
         bump,
-        space= 8 + STRING_MAX_SIZE as usize + compute_comp_arr_max_size(&components.values().cloned().collect())
+        space= 8 + STRING_MAX_SIZE as usize + 1 + compute_comp_arr_max_size(&components.values().cloned().collect())
     )]
     pub blueprint: Account<'info, Blueprint>,
 }
@@ -147,14 +149,775 @@ pub struct SystemInitTile<'info> {
 }
 
 
+#[derive(Accounts)]
+pub struct SystemInitRegion<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        constraint = system_signer.authority.key() == payer.key(), //Only System Auth can make new Maps
+        seeds=[b"System_Signer"],
+        bump,
+    )]
+    pub system_signer: Box<Account<'info, SystemConfig>>,
+
+    /// CHECK: Signing account for DM Worlds
+    #[account(
+        seeds = [
+            b"world_signer",
+        ],
+        bump,
+        seeds::program = world_instance.world.key()
+    )]
+    pub world_config: Account<'info, WorldConfig>,
+
+    pub world_program: Program<'info, Dominariworld>,
+    pub universe: Program<'info, Ecs>, 
+
+    pub system_registration: Box<Account<'info, SystemRegistration>>,
+    pub world_instance: Account<'info, WorldInstance>,    
+
+    #[account(
+        constraint = map.instance == world_instance.instance && map.entity_id == instance_index.map
+    )]
+    pub map: Box<Account<'info, Entity>>,
+
+    /// CHECK: Initalized through CPI
+    #[account(mut)]
+    pub region_entity: AccountInfo<'info>,
+
+    // Regions aren't tracked in the index, so no realloc
+    #[account(
+        seeds=[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_index: Box<Account<'info, InstanceIndex>>,
+}
+
 #[derive(Accounts)]
 pub struct SystemInstanceFeature<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
 
+    // Only the Tile owner can build Features, on an empty Tile from a feature Blueprint (checked in the instruction)
+    #[account(
+        seeds=[b"System_Signer"],
+        bump,
+    )]
+    pub system_signer: Box<Account<'info, SystemConfig>>,
+    /// CHECK: Signing account for DM Worlds
+    #[account(
+        seeds = [
+            b"world_signer",
+        ],
+        bump,
+        seeds::program = world_instance.world.key()
+    )]
+    pub world_config: Account<'info, WorldConfig>,
+    pub world_program: Program<'info, Dominariworld>,
+    pub universe: Program<'info, Ecs>, 
+    pub system_registration: Box<Account<'info, SystemRegistration>>,
+    pub world_instance: Account<'info, WorldInstance>,    
+
+    /// CHECK: Initalized through CPI
+    #[account(mut)]
+    pub feature_entity: AccountInfo<'info>,
+    pub blueprint: Box<Account<'info, Blueprint>>,
+    
+    #[account(
+        mut,
+        constraint = tile_entity.instance == world_instance.instance
+    )]
+    pub tile_entity: Box<Account<'info, Entity>>,
+
+    #[account(
+        mut,
+        seeds=[
+            b"Treasury",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
+        mut,
+        realloc = instance_index.to_account_info().data_len() + 8,
+        realloc::payer = payer,
+        realloc::zero = false,
+        seeds=[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_index: Box<Account<'info, InstanceIndex>>,
+}
+
+#[derive(Accounts)]
+pub struct SystemInitPlayer<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        constraint = system_signer.authority.key() == payer.key(), //Only System Auth can make new Features 
+        seeds=[b"System_Signer"],
+        bump,
+    )]
+    pub system_signer: Box<Account<'info, SystemConfig>>,
+
+    /// CHECK: Signing account for DM Worlds
+    
+    #[account(
+        seeds = [
+            b"world_signer",
+        ],
+        bump,
+        seeds::program = world_instance.world.key()
+    )]
+    pub world_config: Account<'info, WorldConfig>,
+    pub world_program: Program<'info, Dominariworld>,
+    pub universe: Program<'info, Ecs>, 
+
+    pub system_registration: Box<Account<'info, SystemRegistration>>,
+    pub world_instance: Account<'info, WorldInstance>,    
+
+    /// CHECK: Created via CPI
+    #[account(mut)]
+    pub player_entity: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        realloc = instance_index.to_account_info().data_len() + 8 + PlayerStanding::get_max_size() as usize + 8, // player id + standing + phase vote slot
+        realloc::payer = payer,
+        realloc::zero = false,
+        seeds=[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_index: Box<Account<'info, InstanceIndex>>,
+}
+
+#[derive(Accounts)]
+pub struct JoinGame<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        seeds=[b"System_Signer"],
+        bump,
+    )]
+    pub system_signer: Box<Account<'info, SystemConfig>>,
+
+    /// CHECK: Signing account for DM Worlds
+    #[account(
+        seeds = [
+            b"world_signer",
+        ],
+        bump,
+        seeds::program = world_instance.world.key()
+    )]
+    pub world_config: Account<'info, WorldConfig>,
+    pub world_program: Program<'info, Dominariworld>,
+    pub universe: Program<'info, Ecs>, 
+
+    pub system_registration: Box<Account<'info, SystemRegistration>>,
+    pub world_instance: Account<'info, WorldInstance>,    
+
+    /// CHECK: Created via CPI
+    #[account(mut)]
+    pub player_entity: AccountInfo<'info>,
+
+    // Init fails if this wallet already has a player in the instance
+    #[account(
+        init,
+        payer=payer,
+        seeds=[
+            b"Player_Seat",
+            world_instance.key().as_ref(),
+            payer.key().as_ref()
+        ],
+        bump,
+        space=8+8+8
+    )]
+    pub seat: Account<'info, PlayerSeat>,
+
+    #[account(
+        mut,
+        seeds=[
+            b"Vault",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        realloc = instance_index.to_account_info().data_len() + 8 + PlayerStanding::get_max_size() as usize + 8, // player id + standing + phase vote slot
+        realloc::payer = payer,
+        realloc::zero = false,
+        seeds=[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_index: Box<Account<'info, InstanceIndex>>,
+}
+
+#[derive(Accounts)]
+pub struct LeaveGame<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds=[b"System_Signer"],
+        bump,
+    )]
+    pub system_signer: Box<Account<'info, SystemConfig>>,
+    /// CHECK: Signing account for DM Worlds
+    #[account(
+        seeds = [
+            b"world_signer",
+        ],
+        bump,
+        seeds::program = world_instance.world.key()
+    )]
+    pub world_config: Account<'info, WorldConfig>,
+    pub world_program: Program<'info, Dominariworld>,
+    pub universe: Program<'info, Ecs>, 
+    pub system_registration: Box<Account<'info, SystemRegistration>>,
+    pub world_instance: Account<'info, WorldInstance>,
+
+    #[account(
+        mut,
+        constraint = player_entity.entity_id == seat.player,
+        constraint = player_entity.instance == world_instance.instance,
+        constraint = player_entity.world_signer == world_config.key()
+    )]
+    pub player_entity: Box<Account<'info, Entity>>,
+
+    #[account(
+        mut,
+        close = payer,
+        seeds=[
+            b"Player_Seat",
+            world_instance.key().as_ref(),
+            payer.key().as_ref()
+        ],
+        bump,
+    )]
+    pub seat: Account<'info, PlayerSeat>,
+
+    #[account(
+        mut,
+        seeds=[
+            b"Vault",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds=[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_index: Box<Account<'info, InstanceIndex>>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimPrize<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub world_instance: Account<'info, WorldInstance>,
+
+    #[account(
+        mut,
+        close = payer,
+        seeds=[
+            b"Player_Seat",
+            world_instance.key().as_ref(),
+            payer.key().as_ref()
+        ],
+        bump,
+    )]
+    pub seat: Account<'info, PlayerSeat>,
+
+    #[account(
+        mut,
+        seeds=[
+            b"Vault",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        seeds=[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_index: Box<Account<'info, InstanceIndex>>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTreasury<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub world_instance: Account<'info, WorldInstance>,
+
+    #[account(
+        mut,
+        seeds=[
+            b"Treasury",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    // Only the Instance Authority can withdraw (checked in the instruction)
+    #[account(
+        seeds=[
+            b"Instance_Authority",
+            world_instance.key().as_ref()
+        ],
+        bump,
+        seeds::program = world_instance.world.key()
+    )]
+    pub instance_authority: Box<Account<'info, InstanceAuthority>>,
+}
+
+#[derive(Accounts)]
+#[instruction(instance:u64, config: GameConfig)]
+pub struct CreateGameInstance<'info>{
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    #[account(
+        constraint = system_signer.authority.key() == payer.key(), //Only System Auth can make new Maps
+        seeds=[b"System_Signer"],
+        bump,
+    )]
+    pub system_signer: Box<Account<'info, SystemConfig>>,
+
+    /// CHECK: Signing account for DM Worlds
+    #[account(mut)]    
+    pub world_config: AccountInfo<'info>,
+
+    pub world_program: Program<'info, Dominariworld>,
+    pub universe: Program<'info, Ecs>, 
+
+    /// CHECK: Created via CPI in the Universe program
+    #[account(mut)]
+    pub world_instance: AccountInfo<'info>,
+
+    /// CHECK: Created via CPI in the World program
+    #[account(mut)]
+    pub instance_authority: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer=payer,
+        seeds=[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ],
+        bump,
+        space= 8 + InstanceIndex::get_max_size() as usize + config.get_max_size() as usize
+    )]
+    pub instance_index: Box<Account<'info, InstanceIndex>>,
+    #[account(
+        init,
+        payer=payer,
+        seeds=[
+            b"Treasury",
+            world_instance.key().as_ref()
+        ],
+        bump,
+        space=8+8+8+8
+    )]
+    pub treasury: Account<'info, Treasury>,
+    #[account(
+        init,
+        payer=payer,
+        seeds=[
+            b"Vault",
+            world_instance.key().as_ref()
+        ],
+        bump,
+        space=8+8+8+8+4+(8*config.max_players as usize)
+    )]
+    pub vault: Account<'info, Vault>,
+}
+
+#[derive(Accounts)]
+pub struct ChangeGameState<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds=[b"System_Signer"],
+        bump,
+    )]
+    pub system_signer: Box<Account<'info, SystemConfig>>,
+    /// CHECK: Signing account for DM Worlds
+    #[account(
+        seeds = [
+            b"world_signer",
+        ],
+        bump,
+        seeds::program = world_instance.world.key()
+    )]
+    pub world_config: Account<'info, WorldConfig>,
+    pub world_program: Program<'info, Dominariworld>,
+    pub universe: Program<'info, Ecs>, 
+    pub system_registration: Box<Account<'info, SystemRegistration>>,
+    pub world_instance: Account<'info, WorldInstance>,    
+
+    pub player: Box<Account<'info, Entity>>,
+    pub map: Box<Account<'info, Entity>>,
+
+    #[account(
+        mut,
+        seeds=[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_index: Box<Account<'info, InstanceIndex>>,
+}
+
+#[derive(Accounts)]
+pub struct SetPlayPhase<'info> {
+    pub payer: Signer<'info>,
+    pub world_instance: Account<'info, WorldInstance>,
+
+    #[account(
+        mut,
+        seeds=[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_index: Box<Account<'info, InstanceIndex>>,
+
+    // Only the Instance Authority can drive the game between phases (checked in the instruction)
+    #[account(
+        seeds=[
+            b"Instance_Authority",
+            world_instance.key().as_ref()
+        ],
+        bump,
+        seeds::program = world_instance.world.key()
+    )]
+    pub instance_authority: Box<Account<'info, InstanceAuthority>>,
+}
+
+#[derive(Accounts)]
+pub struct EndTurn<'info> {
+    pub payer: Signer<'info>,
+
+    #[account(
+        seeds=[b"System_Signer"],
+        bump,
+    )]
+    pub system_signer: Box<Account<'info, SystemConfig>>,
+    pub world_instance: Account<'info, WorldInstance>,
+
+    #[account(
+        constraint = player.instance == world_instance.instance
+    )]
+    pub player: Box<Account<'info, Entity>>,
+
+    #[account(
+        mut,
+        seeds=[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_index: Box<Account<'info, InstanceIndex>>,
+}
+
+#[derive(Accounts)]
+pub struct SpawnUnit<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds=[b"System_Signer"],
+        bump,
+    )]
+    pub system_signer: Box<Account<'info, SystemConfig>>,
+    /// CHECK: Signing account for DM Worlds
+    #[account(
+        seeds = [
+            b"world_signer",
+        ],
+        bump,
+        seeds::program = world_instance.world.key()
+    )]
+    pub world_config: Account<'info, WorldConfig>,
+    pub world_program: Program<'info, Dominariworld>,
+    pub universe: Program<'info, Ecs>, 
+    pub system_registration: Box<Account<'info, SystemRegistration>>,
+    pub world_instance: Account<'info, WorldInstance>,
+
+    #[account(mut)]
+    pub player: Box<Account<'info, Entity>>,
+    #[account(mut)]
+    pub tile: Box<Account<'info, Entity>>,
+    /// CHECK: Initalized through CPI
+    #[account(mut)]
+    pub unit: AccountInfo<'info>,
+    pub unit_blueprint: Account<'info, Blueprint>,
+
+    #[account(
+        mut,
+        realloc = instance_index.to_account_info().data_len() + 8,
+        realloc::payer = payer,
+        realloc::zero = false,
+        seeds=[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_index: Box<Account<'info, InstanceIndex>>,
+}
+
+#[derive(Accounts)]
+pub struct MoveUnit<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds=[b"System_Signer"],
+        bump,
+    )]
+    pub system_signer: Box<Account<'info, SystemConfig>>,
+    /// CHECK: Signing account for DM Worlds
+    #[account(
+        seeds = [
+            b"world_signer",
+        ],
+        bump,
+        seeds::program = world_instance.world.key()
+    )]
+    pub world_config: Account<'info, WorldConfig>,
+    pub world_program: Program<'info, Dominariworld>,
+    pub universe: Program<'info, Ecs>, 
+    pub system_registration: Box<Account<'info, SystemRegistration>>,
+    pub world_instance: Account<'info, WorldInstance>,
+
+    #[account(
+        mut,
+        constraint = from.instance == world_instance.instance
+    )]
+    pub from: Box<Account<'info, Entity>>,
+    #[account(
+        mut,
+        constraint = to.instance == world_instance.instance
+    )]
+    pub to: Box<Account<'info, Entity>>,
+    #[account(
+        mut,
+        constraint = unit.instance == world_instance.instance
+    )]
+    pub unit: Box<Account<'info, Entity>>,
+
+    #[account(
+        seeds=[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_index: Box<Account<'info, InstanceIndex>>,
+}
+
+#[derive(Accounts)]
+pub struct MoveUnitChunked<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds=[b"System_Signer"],
+        bump,
+    )]
+    pub system_signer: Box<Account<'info, SystemConfig>>,
+    /// CHECK: Signing account for DM Worlds
+    #[account(
+        seeds = [
+            b"world_signer",
+        ],
+        bump,
+        seeds::program = world_instance.world.key()
+    )]
+    pub world_config: Account<'info, WorldConfig>,
+    pub world_program: Program<'info, Dominariworld>,
+    pub universe: Program<'info, Ecs>, 
+    pub system_registration: Box<Account<'info, SystemRegistration>>,
+    pub world_instance: Account<'info, WorldInstance>,
+
+    #[account(
+        mut,
+        constraint = unit.instance == world_instance.instance
+    )]
+    pub unit: Box<Account<'info, Entity>>,
+
+    #[account(
+        seeds=[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_index: Box<Account<'info, InstanceIndex>>,
+}
+
+#[derive(Accounts)]
+pub struct AttackTile <'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds=[b"System_Signer"],
+        bump,
+    )]
+    pub system_signer: Box<Account<'info, SystemConfig>>,
+    /// CHECK: Signing account for DM Worlds
+    #[account(
+        seeds = [
+            b"world_signer",
+        ],
+        bump,
+        seeds::program = world_instance.world.key()
+    )]
+    pub world_config: Account<'info, WorldConfig>,
+    pub world_program: Program<'info, Dominariworld>,
+    pub universe: Program<'info, Ecs>, 
+    pub system_registration: Box<Account<'info, SystemRegistration>>,
+    pub world_instance: Account<'info, WorldInstance>,
+
+    #[account(
+        mut,
+        constraint = attacker.instance == world_instance.instance
+    )]
+    pub attacker: Box<Account<'info, Entity>>,
+    #[account(
+        mut,
+        constraint = defender.instance == world_instance.instance
+    )]
+    pub defender: Box<Account<'info, Entity>>,
+    #[account(
+        mut,
+        constraint = defending_tile.instance == world_instance.instance
+    )]
+    pub defending_tile: Box<Account<'info, Entity>>,
+    // Player entity that owns the attacker, credited with score/kills/drops on a kill
+    #[account(
+        mut,
+        constraint = attacking_player.instance == world_instance.instance
+    )]
+    pub attacking_player: Box<Account<'info, Entity>>,
+    
+    #[account(
+        mut,
+        seeds=[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_index: Box<Account<'info, InstanceIndex>>,
+
+    /// CHECK: Only read in CommitReveal mode, deserialized and closed in the instruction
+    #[account(
+        mut,
+        seeds=[
+            b"Randomness_Commit",
+            attacker.key().as_ref()
+        ],
+        bump,
+    )]
+    pub randomness_commit: AccountInfo<'info>,
+
+    /// CHECK: SlotHashes sysvar, too large to deserialize so it's read manually
+    #[account(
+        address = anchor_lang::solana_program::sysvar::slot_hashes::ID
+    )]
+    pub slot_hashes: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CommitRandomness<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    #[account(
+        seeds=[b"System_Signer"],
+        bump,
+    )]
+    pub system_signer: Box<Account<'info, SystemConfig>>,
+    pub world_instance: Account<'info, WorldInstance>,
+
+    #[account(
+        constraint = attacker.instance == world_instance.instance
+    )]
+    pub attacker: Box<Account<'info, Entity>>,
+
+    #[account(
+        init,
+        payer=payer,
+        seeds=[
+            b"Randomness_Commit",
+            attacker.key().as_ref()
+        ],
+        bump,
+        space=8+32+32+8+8
+    )]
+    pub randomness_commit: Account<'info, RandomnessCommit>,
+
+    #[account(
+        seeds=[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_index: Box<Account<'info, InstanceIndex>>,
+}
+
+#[derive(Accounts)]
+pub struct ClearExpiredCommitment<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
     #[account(
-        constraint = system_signer.authority.key() == payer.key(), //Only System Auth can make new Features 
         seeds=[b"System_Signer"],
         bump,
     )]
@@ -171,21 +934,34 @@ pub struct SystemInstanceFeature<'info> {
     pub world_program: Program<'info, Dominariworld>,
     pub universe: Program<'info, Ecs>, 
     pub system_registration: Box<Account<'info, SystemRegistration>>,
-    pub world_instance: Account<'info, WorldInstance>,    
+    pub world_instance: Account<'info, WorldInstance>,
 
-    /// CHECK: Initalized through CPI
-    #[account(mut)]
-    pub feature_entity: AccountInfo<'info>,
-    pub blueprint: Box<Account<'info, Blueprint>>,
-    
-    #[account(mut)]
-    pub tile_entity: Box<Account<'info, Entity>>,
+    // Expiring counts as the attacker's use, so its Last Used is stamped
+    #[account(
+        mut,
+        constraint = attacker.instance == world_instance.instance
+    )]
+    pub attacker: Box<Account<'info, Entity>>,
 
     #[account(
         mut,
-        realloc = instance_index.to_account_info().data_len() + 8,
-        realloc::payer = payer,
-        realloc::zero = false,
+        close=payer,
+        seeds=[
+            b"Randomness_Commit",
+            attacker.key().as_ref()
+        ],
+        bump,
+        constraint = randomness_commit.committer == payer.key()
+    )]
+    pub randomness_commit: Account<'info, RandomnessCommit>,
+
+    /// CHECK: SlotHashes sysvar, too large to deserialize so it's read manually
+    #[account(
+        address = anchor_lang::solana_program::sysvar::slot_hashes::ID
+    )]
+    pub slot_hashes: AccountInfo<'info>,
+
+    #[account(
         seeds=[
             b"Instance_Index",
             world_instance.key().as_ref()
@@ -195,20 +971,19 @@ pub struct SystemInstanceFeature<'info> {
     pub instance_index: Box<Account<'info, InstanceIndex>>,
 }
 
+
 #[derive(Accounts)]
-pub struct SystemInitPlayer<'info> {
+pub struct UseFeature<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
+
     #[account(
-        constraint = system_signer.authority.key() == payer.key(), //Only System Auth can make new Features 
         seeds=[b"System_Signer"],
         bump,
     )]
     pub system_signer: Box<Account<'info, SystemConfig>>,
-
     /// CHECK: Signing account for DM Worlds
-    
     #[account(
         seeds = [
             b"world_signer",
@@ -219,19 +994,28 @@ pub struct SystemInitPlayer<'info> {
     pub world_config: Account<'info, WorldConfig>,
     pub world_program: Program<'info, Dominariworld>,
     pub universe: Program<'info, Ecs>, 
-
     pub system_registration: Box<Account<'info, SystemRegistration>>,
-    pub world_instance: Account<'info, WorldInstance>,    
+    pub world_instance: Account<'info, WorldInstance>,
 
-    /// CHECK: Created via CPI
+    #[account(
+        constraint = tile.instance == world_instance.instance
+    )]
+    pub tile: Box<Account<'info, Entity>>,
+    #[account(
+        mut,
+        constraint = feature.instance == world_instance.instance
+    )]
+    pub feature: Box<Account<'info, Entity>>,
+    #[account(
+        mut,
+        constraint = unit.instance == world_instance.instance
+    )]
+    pub unit: Box<Account<'info, Entity>>,
+    /// CHECK: Checked against the Feature's Owner component, receives the cost of use
     #[account(mut)]
-    pub player_entity: AccountInfo<'info>,
+    pub feature_owner: AccountInfo<'info>,
 
     #[account(
-        mut,
-        realloc = instance_index.to_account_info().data_len() + 8,
-        realloc::payer = payer,
-        realloc::zero = false,
         seeds=[
             b"Instance_Index",
             world_instance.key().as_ref()
@@ -242,49 +1026,51 @@ pub struct SystemInitPlayer<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(instance:u64, config: GameConfig)]
-pub struct CreateGameInstance<'info>{
+pub struct UpgradeFeature<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
+
     #[account(
-        constraint = system_signer.authority.key() == payer.key(), //Only System Auth can make new Maps
         seeds=[b"System_Signer"],
         bump,
     )]
     pub system_signer: Box<Account<'info, SystemConfig>>,
-
     /// CHECK: Signing account for DM Worlds
-    #[account(mut)]    
-    pub world_config: AccountInfo<'info>,
-
+    #[account(
+        seeds = [
+            b"world_signer",
+        ],
+        bump,
+        seeds::program = world_instance.world.key()
+    )]
+    pub world_config: Account<'info, WorldConfig>,
     pub world_program: Program<'info, Dominariworld>,
     pub universe: Program<'info, Ecs>, 
+    pub system_registration: Box<Account<'info, SystemRegistration>>,
+    pub world_instance: Account<'info, WorldInstance>,
 
-    /// CHECK: Created via CPI in the Universe program
-    #[account(mut)]
-    pub world_instance: AccountInfo<'info>,
-
-    /// CHECK: Created via CPI in the World program
-    #[account(mut)]
-    pub instance_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        constraint = feature.instance == world_instance.instance
+    )]
+    pub feature: Box<Account<'info, Entity>>,
 
     #[account(
-        init,
-        payer=payer,
         seeds=[
             b"Instance_Index",
             world_instance.key().as_ref()
         ],
         bump,
-        space= 8 + InstanceIndex::get_max_size() as usize + config.get_max_size() as usize
     )]
     pub instance_index: Box<Account<'info, InstanceIndex>>,
 }
 
 #[derive(Accounts)]
-pub struct ChangeGameState<'info> {
+pub struct BuyTile<'info> {
+    #[account(mut)]
     pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
 
     #[account(
         seeds=[b"System_Signer"],
@@ -303,13 +1089,29 @@ pub struct ChangeGameState<'info> {
     pub world_program: Program<'info, Dominariworld>,
     pub universe: Program<'info, Ecs>, 
     pub system_registration: Box<Account<'info, SystemRegistration>>,
-    pub world_instance: Account<'info, WorldInstance>,    
+    pub world_instance: Account<'info, WorldInstance>,
 
+    #[account(
+        mut,
+        constraint = tile.instance == world_instance.instance
+    )]
+    pub tile: Box<Account<'info, Entity>>,
+    #[account(
+        constraint = player.instance == world_instance.instance
+    )]
     pub player: Box<Account<'info, Entity>>,
-    pub map: Box<Account<'info, Entity>>,
 
     #[account(
         mut,
+        seeds=[
+            b"Treasury",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub treasury: Account<'info, Treasury>,
+
+    #[account(
         seeds=[
             b"Instance_Index",
             world_instance.key().as_ref()
@@ -320,10 +1122,9 @@ pub struct ChangeGameState<'info> {
 }
 
 #[derive(Accounts)]
-pub struct SpawnUnit<'info> {
+pub struct ReclaimEntities<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
-    pub system_program: Program<'info, System>,
 
     #[account(
         seeds=[b"System_Signer"],
@@ -344,20 +1145,10 @@ pub struct SpawnUnit<'info> {
     pub system_registration: Box<Account<'info, SystemRegistration>>,
     pub world_instance: Account<'info, WorldInstance>,
 
-    #[account(mut)]
-    pub player: Box<Account<'info, Entity>>,
-    #[account(mut)]
-    pub tile: Box<Account<'info, Entity>>,
-    /// CHECK: Initalized through CPI
-    #[account(mut)]
-    pub unit: AccountInfo<'info>,
-    pub unit_blueprint: Account<'info, Blueprint>,
+    // Entities and their original payers are passed in as remaining accounts
 
     #[account(
         mut,
-        realloc = instance_index.to_account_info().data_len() + 8,
-        realloc::payer = payer,
-        realloc::zero = false,
         seeds=[
             b"Instance_Index",
             world_instance.key().as_ref()
@@ -368,10 +1159,13 @@ pub struct SpawnUnit<'info> {
 }
 
 #[derive(Accounts)]
-pub struct MoveUnit<'info> {
+pub struct TokenizeEntity<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
 
     #[account(
         seeds=[b"System_Signer"],
@@ -393,22 +1187,70 @@ pub struct MoveUnit<'info> {
     pub world_instance: Account<'info, WorldInstance>,
 
     #[account(
-        mut,
-        constraint = from.instance == world_instance.instance
+        constraint = entity.instance == world_instance.instance
     )]
-    pub from: Box<Account<'info, Entity>>,
+    pub entity: Box<Account<'info, Entity>>,
+
+    /// CHECK: Initalized through CPI
+    #[account(mut)]
+    pub entity_nft: AccountInfo<'info>,
+    /// CHECK: Initalized through CPI
+    #[account(mut)]
+    pub mint: AccountInfo<'info>,
+    /// CHECK: Initalized through CPI
+    #[account(mut)]
+    pub owner_ata: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimEntity<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
     #[account(
-        mut,
-        constraint = to.instance == world_instance.instance
+        seeds=[b"System_Signer"],
+        bump,
     )]
-    pub to: Box<Account<'info, Entity>>,
+    pub system_signer: Box<Account<'info, SystemConfig>>,
+    /// CHECK: Signing account for DM Worlds
+    #[account(
+        seeds = [
+            b"world_signer",
+        ],
+        bump,
+        seeds::program = world_instance.world.key()
+    )]
+    pub world_config: Account<'info, WorldConfig>,
+    pub world_program: Program<'info, Dominariworld>,
+    pub universe: Program<'info, Ecs>, 
+    pub system_registration: Box<Account<'info, SystemRegistration>>,
+    pub world_instance: Account<'info, WorldInstance>,
+
     #[account(
         mut,
-        constraint = unit.instance == world_instance.instance
+        constraint = entity.instance == world_instance.instance
     )]
-    pub unit: Box<Account<'info, Entity>>,
+    pub entity: Box<Account<'info, Entity>>,
+
+    // The player the entity is claimed for
+    #[account(
+        constraint = player.instance == world_instance.instance
+    )]
+    pub player: Box<Account<'info, Entity>>,
+
+    #[account(
+        seeds = [
+            b"Entity_NFT",
+            entity.key().as_ref()
+        ],
+        bump,
+        seeds::program = universe.key()
+    )]
+    pub entity_nft: Account<'info, EntityNFT>,
+    pub holder_ata: Account<'info, TokenAccount>,
 
     #[account(
+        mut,
         seeds=[
             b"Instance_Index",
             world_instance.key().as_ref()
@@ -419,7 +1261,7 @@ pub struct MoveUnit<'info> {
 }
 
 #[derive(Accounts)]
-pub struct AttackTile <'info> {
+pub struct PlayMod<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -445,20 +1287,16 @@ pub struct AttackTile <'info> {
 
     #[account(
         mut,
-        constraint = attacker.instance == world_instance.instance
-    )]
-    pub attacker: Box<Account<'info, Entity>>,
-    #[account(
-        mut,
-        constraint = defender.instance == world_instance.instance
+        constraint = player.instance == world_instance.instance
     )]
-    pub defender: Box<Account<'info, Entity>>,
+    pub player: Box<Account<'info, Entity>>,
     #[account(
         mut,
-        constraint = defending_tile.instance == world_instance.instance
+        constraint = unit.instance == world_instance.instance
     )]
-    pub defending_tile: Box<Account<'info, Entity>>,
-    
+    pub unit: Box<Account<'info, Entity>>,
+    pub mod_blueprint: Box<Account<'info, Blueprint>>,
+
     #[account(
         seeds=[
             b"Instance_Index",
@@ -469,12 +1307,11 @@ pub struct AttackTile <'info> {
     pub instance_index: Box<Account<'info, InstanceIndex>>,
 }
 
-
 /********************************************UTIL Fns */
 pub fn compute_comp_arr_max_size(components: &Vec<SerializedComponent>) -> usize {
     let mut max_size:usize = 0;
     for comp in components {
-        max_size += comp.max_size as usize + 44; // 44 is the size of the additional fields in Serialized Comp (pubkey, max_size, and empty BTreeMap)
+        max_size += comp.max_size as usize + SERIALIZED_COMPONENT_EXTRA_SPACE as usize;
     }
     return max_size;
 }
\ No newline at end of file
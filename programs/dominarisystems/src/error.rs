@@ -16,6 +16,63 @@ pub enum DominariError {
 
     #[msg("Game Paused")]
     GamePaused,
+
+    #[msg("Game can't move between those phases!")]
+    InvalidPhaseTransition,
+
+    #[msg("Player already voted for this phase change!")]
+    AlreadyVoted,
+
+    #[msg("Players can only join or leave before the game starts!")]
+    GameAlreadyStarted,
+
+    #[msg("Prizes can only be claimed once the game is finished!")]
+    GameNotFinished,
+
+    #[msg("Treasury doesn't hold that much!")]
+    InsufficientTreasury,
+
+    #[msg("Map topology doesn't match the game's grid metric!")]
+    InvalidMapTopology,
+
+    #[msg("Game isn't using that randomness mode!")]
+    InvalidRandomnessMode,
+
+    #[msg("Attack needs a randomness commitment and seed in this game!")]
+    MissingCommitment,
+
+    #[msg("Revealed seed doesn't match the commitment!")]
+    InvalidCommitment,
+
+    #[msg("Seed can't be revealed until the commitment's target slot has passed!")]
+    CommitmentNotReady,
+
+    #[msg("Commitment's target slot has left SlotHashes, clear it and commit again!")]
+    CommitmentExpired,
+
+    #[msg("Commitment can still be revealed!")]
+    CommitmentNotExpired,
+
+    #[msg("Invalid SlotHashes sysvar!")]
+    InvalidSlotHashes,
+
+    #[msg("Only inactive entities or entities in a finished game can be reclaimed!")]
+    EntityNotReclaimable,
+
+    #[msg("It's not your turn!")]
+    NotYourTurn,
+
+    #[msg("Game isn't turn based!")]
+    NotTurnBased,
+
+    #[msg("Game is finished!")]
+    GameFinished,
+
+    #[msg("Entity has changed since the expected version!")]
+    StaleEntity,
+
+    #[msg("Expected version given for an entity the instruction doesn't use!")]
+    UnknownVersionedEntity,
 }
 
 #[error_code]
@@ -35,6 +92,21 @@ pub enum ComponentErrors {
     #[msg("Player doesn't have that card")]
     InvalidCard,
 
+    #[msg("Only mod blueprints can be played onto units")]
+    NotAMod,
+
+    #[msg("Only feature blueprints can be built onto tiles")]
+    NotAFeature,
+
+    #[msg("Tile already has a Feature")]
+    TileHasFeature,
+
+    #[msg("Mods can't change a unit's identity or state")]
+    ProtectedComponent,
+
+    #[msg("Stat overflowed")]
+    StatOverflow,
+
     #[msg("Invalid Unit")]
     InvalidUnit,
 
@@ -55,4 +127,34 @@ pub enum ComponentErrors {
 
     #[msg("Invalid Location")]
     InvalidLocation,
+
+    #[msg("Invalid Feature")]
+    InvalidFeature,
+
+    #[msg("Feature can't be used")]
+    FeatureNotUsable,
+
+    #[msg("Feature has no uses left")]
+    NoUsesLeft,
+
+    #[msg("Unit is already at full health")]
+    NothingToHeal,
+
+    #[msg("Feature is recovering from last use")]
+    FeatureRecovering,
+
+    #[msg("Feature is already at max rank")]
+    MaxRankReached,
+
+    #[msg("Tile is already owned by a player")]
+    TileAlreadyOwned,
+
+    #[msg("Unit can't move onto that terrain")]
+    ImpassableTerrain,
+
+    #[msg("Only units and features can be tokenized")]
+    NotTokenizable,
+
+    #[msg("Payer doesn't hold the entity's token")]
+    NotTokenHolder,
 }
@@ -1,5 +1,9 @@
 use anchor_lang::prelude::*;
 
+use crate::account::PlayPhase;
+use crate::component::ComponentLocation;
+use crate::state::VictoryCondition;
+
 #[event]
 pub struct NewWorldInstance {
     pub world_instance: Pubkey,
@@ -36,6 +40,14 @@ pub struct TroopMovement {
     pub unit: u64
 }
 
+#[event]
+pub struct RegionTroopMovement {
+    pub instance: u64,
+    pub from: ComponentLocation,
+    pub to: ComponentLocation,
+    pub unit: u64
+}
+
 #[event]
 pub struct TileAttacked {
     pub instance:u64,
@@ -43,4 +55,125 @@ pub struct TileAttacked {
     pub defender: u64,
     pub defending_tile: u64,
     pub damage: u64
-}
\ No newline at end of file
+}
+
+#[event]
+pub struct UnitKilled {
+    pub instance: u64,
+    pub attacker: u64,
+    pub defender: u64,
+    pub player: u64,        // Player credited with the kill
+    pub score: u64,         // Score gained from the defender's value
+    pub kills: u64,         // Player's total kills after this one
+    pub card: Option<Pubkey> // Blueprint dropped into the player's hand, if any
+}
+
+#[event]
+pub struct FeatureUsed {
+    pub instance: u64,
+    pub feature: u64,
+    pub unit: u64,
+    pub healed: u64,
+    pub cost: u64
+}
+
+#[event]
+pub struct FeatureUpgraded {
+    pub instance: u64,
+    pub feature: u64,
+    pub rank: u8
+}
+
+#[event]
+pub struct TileBought {
+    pub instance: u64,
+    pub tile: u64,
+    pub player: u64,
+    pub cost: u64
+}
+
+#[event]
+pub struct EntitiesReclaimed {
+    pub instance: u64,
+    pub entities: Vec<u64>
+}
+
+#[event]
+pub struct ModPlayed {
+    pub instance: u64,
+    pub player: u64,
+    pub unit: u64,
+    pub blueprint: Pubkey
+}
+
+#[event]
+pub struct TurnEnded {
+    pub instance: u64,
+    pub turn_number: u64,   // New turn number
+    pub ended_player: u64,
+    pub active_player: u64,
+    pub skipped: bool       // Ended by someone else after the turn timed out
+}
+
+#[event]
+pub struct GameFinished {
+    pub instance: u64,
+    pub winner: u64,
+    pub condition: VictoryCondition
+}
+
+#[event]
+pub struct PlayPhaseChanged {
+    pub instance: u64,
+    pub from: PlayPhase,
+    pub to: PlayPhase,
+    pub changed_by: Pubkey
+}
+
+#[event]
+pub struct PhaseVoteCast {
+    pub instance: u64,
+    pub player: u64,
+    pub to: PlayPhase,
+    pub votes: u16,
+    pub quorum: u16
+}
+
+#[event]
+pub struct PlayerJoined {
+    pub instance: u64,
+    pub player: u64,
+    pub wallet: Pubkey,
+    pub entry_fee: u64
+}
+
+#[event]
+pub struct PlayerLeft {
+    pub instance: u64,
+    pub player: u64,
+    pub wallet: Pubkey,
+    pub refund: u64
+}
+
+#[event]
+pub struct PrizeClaimed {
+    pub instance: u64,
+    pub player: u64,
+    pub wallet: Pubkey,
+    pub amount: u64
+}
+
+#[event]
+pub struct TreasuryWithdrawn {
+    pub instance: u64,
+    pub wallet: Pubkey,
+    pub amount: u64
+}
+
+#[event]
+pub struct EntityOwnerClaimed {
+    pub instance: u64,
+    pub entity: u64,
+    pub owner: Pubkey,
+    pub player: u64
+}
@@ -0,0 +1,158 @@
+use anchor_lang::prelude::*;
+
+use crate::component::{ComponentLocation, ComponentMapMeta, MapTopology};
+
+/**
+ * How distance is measured on the map. Integer only so it's cheap and exact on chain.
+ * Manhattan: 4 neighbours, diagonals cost 2
+ * Chebyshev: 8 neighbours, diagonals cost 1
+ * Hex: 6 neighbours, locations are axial (q = x, r = y) coordinates
+ */
+#[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq)]
+pub enum GridMetric {
+    Manhattan,
+    Chebyshev,
+    Hex,
+}
+
+pub fn get_distance(metric: &GridMetric, from: &ComponentLocation, to: &ComponentLocation) -> u64 {
+    let dx = to.x as i64 - from.x as i64;
+    let dy = to.y as i64 - from.y as i64;
+    match metric {
+        GridMetric::Manhattan => (dx.abs() + dy.abs()) as u64,
+        GridMetric::Chebyshev => dx.abs().max(dy.abs()) as u64,
+        GridMetric::Hex => ((dx.abs() + dy.abs() + (dx + dy).abs()) / 2) as u64,
+    }
+}
+
+/// Tiles are adjacent if they're exactly one step apart under the metric
+pub fn is_adjacent(metric: &GridMetric, from: &ComponentLocation, to: &ComponentLocation) -> bool {
+    get_distance(metric, from, to) == 1
+}
+
+/// Hex maps need the hex metric and square maps can't use it
+pub fn is_metric_for_topology(metric: &GridMetric, topology: &MapTopology) -> bool {
+    (*metric == GridMetric::Hex) == (*topology == MapTopology::Hex)
+}
+
+/// Whether a location is inside the map's bounds and, for hex maps, its hexagon
+pub fn is_on_map(mapmeta: &ComponentMapMeta, location: &ComponentLocation) -> bool {
+    if location.x >= mapmeta.max_x || location.y >= mapmeta.max_y {
+        return false
+    }
+    if mapmeta.topology == MapTopology::Hex {
+        let center = ComponentLocation {
+            x: (mapmeta.max_x.max(1) - 1) / 2,
+            y: (mapmeta.max_y.max(1) - 1) / 2,
+        };
+        let radius = center.x.min(center.y) as u64;
+        return get_distance(&GridMetric::Hex, &center, location) <= radius
+    }
+    true
+}
+
+/**
+ * Every location on the map.
+ * Square maps are the full max_x by max_y grid, hex maps the largest hexagon that fits inside it.
+ */
+pub fn get_map_locations(mapmeta: &ComponentMapMeta) -> Vec<ComponentLocation> {
+    let mut locations = vec![];
+    for x in 0..mapmeta.max_x {
+        for y in 0..mapmeta.max_y {
+            let location = ComponentLocation { x, y };
+            if !is_on_map(mapmeta, &location) {
+                continue;
+            }
+            locations.push(location);
+        }
+    }
+    locations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loc(x: u16, y: u16) -> ComponentLocation {
+        ComponentLocation { x, y }
+    }
+
+    #[test]
+    fn manhattan_counts_diagonals_as_two() {
+        assert_eq!(get_distance(&GridMetric::Manhattan, &loc(0, 0), &loc(3, 4)), 7);
+        assert_eq!(get_distance(&GridMetric::Manhattan, &loc(3, 4), &loc(0, 0)), 7);
+        assert!(!is_adjacent(&GridMetric::Manhattan, &loc(1, 1), &loc(2, 2)));
+        assert!(is_adjacent(&GridMetric::Manhattan, &loc(1, 1), &loc(1, 2)));
+    }
+
+    #[test]
+    fn chebyshev_counts_diagonals_as_one() {
+        assert_eq!(get_distance(&GridMetric::Chebyshev, &loc(0, 0), &loc(3, 4)), 4);
+        assert!(is_adjacent(&GridMetric::Chebyshev, &loc(1, 1), &loc(2, 2)));
+        assert!(!is_adjacent(&GridMetric::Chebyshev, &loc(1, 1), &loc(1, 1)));
+    }
+
+    #[test]
+    fn hex_distance_uses_axial_coordinates() {
+        // The six axial neighbours of (2, 2)
+        for (x, y) in [(3, 2), (1, 2), (2, 3), (2, 1), (3, 1), (1, 3)] {
+            assert_eq!(get_distance(&GridMetric::Hex, &loc(2, 2), &loc(x, y)), 1);
+        }
+        // (1, 1) and (3, 3) sit along the axis hexes don't share an edge on
+        assert_eq!(get_distance(&GridMetric::Hex, &loc(2, 2), &loc(1, 1)), 2);
+        assert_eq!(get_distance(&GridMetric::Hex, &loc(2, 2), &loc(3, 3)), 2);
+        assert_eq!(get_distance(&GridMetric::Hex, &loc(0, 4), &loc(4, 0)), 4);
+        assert_eq!(get_distance(&GridMetric::Hex, &loc(0, 0), &loc(4, 4)), 8);
+    }
+
+    #[test]
+    fn distance_does_not_overflow_at_the_edges() {
+        let far = loc(u16::MAX, u16::MAX);
+        assert_eq!(get_distance(&GridMetric::Manhattan, &loc(0, 0), &far), 2 * u16::MAX as u64);
+        assert_eq!(get_distance(&GridMetric::Hex, &loc(0, 0), &far), 2 * u16::MAX as u64);
+    }
+
+    fn mapmeta(max_x: u16, max_y: u16, topology: MapTopology) -> ComponentMapMeta {
+        ComponentMapMeta { max_x, max_y, topology, chunked: false }
+    }
+
+    #[test]
+    fn hex_maps_are_the_hexagon_inside_the_bounds() {
+        let hex = mapmeta(5, 5, MapTopology::Hex);
+        assert!(is_on_map(&hex, &loc(2, 2)));
+        assert!(is_on_map(&hex, &loc(0, 2)));
+        assert!(is_on_map(&hex, &loc(4, 0)));
+        assert!(is_on_map(&hex, &loc(0, 4)));
+        // Corners along the long diagonal fall outside the hexagon
+        assert!(!is_on_map(&hex, &loc(0, 0)));
+        assert!(!is_on_map(&hex, &loc(4, 4)));
+        assert!(!is_on_map(&hex, &loc(5, 2)));
+        // Radius 2 hexagon
+        assert_eq!(get_map_locations(&hex).len(), 19);
+    }
+
+    #[test]
+    fn square_maps_fill_their_bounds() {
+        let square = mapmeta(5, 3, MapTopology::Square);
+        assert!(is_on_map(&square, &loc(0, 0)));
+        assert!(is_on_map(&square, &loc(4, 2)));
+        assert!(!is_on_map(&square, &loc(5, 0)));
+        assert!(!is_on_map(&square, &loc(0, 3)));
+        assert_eq!(get_map_locations(&square).len(), 15);
+    }
+
+    #[test]
+    fn empty_maps_have_no_locations() {
+        assert!(get_map_locations(&mapmeta(0, 0, MapTopology::Hex)).is_empty());
+        assert!(get_map_locations(&mapmeta(0, 0, MapTopology::Square)).is_empty());
+    }
+
+    #[test]
+    fn hex_metric_only_for_hex_maps() {
+        assert!(is_metric_for_topology(&GridMetric::Hex, &MapTopology::Hex));
+        assert!(!is_metric_for_topology(&GridMetric::Manhattan, &MapTopology::Hex));
+        assert!(!is_metric_for_topology(&GridMetric::Hex, &MapTopology::Square));
+        assert!(is_metric_for_topology(&GridMetric::Chebyshev, &MapTopology::Square));
+    }
+}
@@ -1,5 +1,4 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::hash::*;
 use std::collections::BTreeMap;
 
 pub mod account;
@@ -9,6 +8,14 @@ pub mod error;
 pub mod event;
 pub mod component;
 pub mod state;
+pub mod randomness;
+pub mod turn;
+pub mod victory;
+pub mod phase;
+pub mod movement;
+pub mod geometry;
+pub mod region;
+pub mod version;
 
 use account::*;
 use context::*;
@@ -17,8 +24,17 @@ use error::*;
 use event::*;
 use component::*;
 use state::*;
-
-use ecs::state::SerializedComponent;
+use randomness::*;
+use turn::*;
+use victory::*;
+use phase::*;
+use movement::*;
+use geometry::*;
+use region::*;
+use version::*;
+
+use ecs::state::{SerializedComponent, EntityModification, EntityVersion};
+use ecs::account::Entity;
 
 declare_id!("3YdayPtujByJ1g1DWEUh7vpg78gZL49FWyD5rDGyof9T");
 
@@ -35,14 +51,18 @@ pub mod dominarisystems {
         Ok(())
     }
 
-    pub fn register_blueprint(ctx:Context<RegisterBlueprint>, name:String, components: BTreeMap<Pubkey, SerializedComponent>) -> Result<()> {
+    pub fn register_blueprint(ctx:Context<RegisterBlueprint>, name:String, components: BTreeMap<Pubkey, SerializedComponent>, is_mod: bool) -> Result<()> {
         ctx.accounts.blueprint.name = name;
+        ctx.accounts.blueprint.is_mod = is_mod;
         ctx.accounts.blueprint.components = components;
         Ok(())
     }
 
-    pub fn system_initalize_map(ctx:Context<SystemInitMap>, entity_id:u64, max_x: u8, max_y: u8) -> Result<()> {
+    pub fn system_initalize_map(ctx:Context<SystemInitMap>, entity_id:u64, max_x: u16, max_y: u16, topology: MapTopology, chunked: bool) -> Result<()> {
         let reference = &ctx.accounts.system_signer.components;
+        if !is_metric_for_topology(&ctx.accounts.instance_index.config.grid_metric, &topology) {
+            return err!(DominariError::InvalidMapTopology)
+        }
         let system_signer_seeds:&[&[u8]] = &[
             b"System_Signer",
             &[*ctx.bumps.get("system_signer").unwrap()]
@@ -73,6 +93,7 @@ pub mod dominarisystems {
         }.try_to_vec().unwrap();
         components.insert(reference.metadata.key(), SerializedComponent { 
             max_size: ComponentMetadata::get_max_size(), 
+            schema_hash: ComponentMetadata::get_schema_hash(),
             data:  metadata_component
         });
 
@@ -80,9 +101,12 @@ pub mod dominarisystems {
         let mapmeta_component = ComponentMapMeta {
             max_x,
             max_y,
+            topology,
+            chunked,
         }.try_to_vec().unwrap();
         components.insert(reference.mapmeta.key(), SerializedComponent { 
             max_size: ComponentMapMeta::get_max_size(), 
+            schema_hash: ComponentMapMeta::get_schema_hash(),
             data: mapmeta_component 
         });
 
@@ -92,12 +116,12 @@ pub mod dominarisystems {
         Ok(())
     }
 
-    pub fn system_init_tile(ctx:Context<SystemInitTile>, entity_id:u64, x:u8, y:u8, cost:u64) -> Result<()> {
+    pub fn system_init_tile(ctx:Context<SystemInitTile>, entity_id:u64, x:u16, y:u16, cost:u64, terrain: Option<ComponentTerrain>) -> Result<()> {
         // Tile can only be instanced by Admin
         // So we can trust in the input
         let reference = &ctx.accounts.system_signer.components;
 
-        // Tile has Metadata, Location, Feature, Occupant, Owner and Cost components
+        // Tile has Metadata, Location, Feature, Occupant, Owner and Cost components, and optionally Terrain
         let mut components: BTreeMap<Pubkey, SerializedComponent> = BTreeMap::new();
         let metadata = ComponentMetadata {
             name: format!("Tile ({x}, {y})"),
@@ -106,6 +130,7 @@ pub mod dominarisystems {
         }.try_to_vec().unwrap();
         components.insert(reference.metadata.key(), SerializedComponent { 
             max_size: ComponentMetadata::get_max_size(),
+            schema_hash: ComponentMetadata::get_schema_hash(),
             data: metadata
         });
 
@@ -115,6 +140,7 @@ pub mod dominarisystems {
         }.try_to_vec().unwrap();
         components.insert(reference.location.key(), SerializedComponent { 
             max_size: ComponentLocation::get_max_size(),
+            schema_hash: ComponentLocation::get_schema_hash(),
             data: location
         });
 
@@ -123,6 +149,7 @@ pub mod dominarisystems {
         }.try_to_vec().unwrap();
         components.insert(reference.feature.key(), SerializedComponent { 
             max_size: ComponentFeature::get_max_size(),
+            schema_hash: ComponentFeature::get_schema_hash(),
             data: feature
         });
 
@@ -131,6 +158,7 @@ pub mod dominarisystems {
         }.try_to_vec().unwrap();
         components.insert(reference.occupant.key(), SerializedComponent { 
             max_size: ComponentOccupant::get_max_size(),
+            schema_hash: ComponentOccupant::get_schema_hash(),
             data: occupant
         });
 
@@ -140,6 +168,7 @@ pub mod dominarisystems {
         }.try_to_vec().unwrap();
         components.insert(reference.owner.key(), SerializedComponent { 
             max_size: ComponentOwner::get_max_size(),
+            schema_hash: ComponentOwner::get_schema_hash(),
             data: owner
         });
 
@@ -148,9 +177,14 @@ pub mod dominarisystems {
         }.try_to_vec().unwrap();
         components.insert(reference.cost.key(), SerializedComponent { 
             max_size: ComponentCost::get_max_size(),
+            schema_hash: ComponentCost::get_schema_hash(),
             data: cost_component
         });
 
+        if let Some(terrain) = terrain {
+            components.insert(reference.terrain.key(), terrain.to_serialized_component());
+        }
+
         let system_signer_seeds:&[&[u8]] = &[
             b"System_Signer",
             &[*ctx.bumps.get("system_signer").unwrap()]
@@ -176,18 +210,135 @@ pub mod dominarisystems {
         ctx.accounts.instance_index.tiles.push(entity_id);
         Ok(())
     }
+
+    /**
+     * Chunked maps store their tiles in Region entities instead of one Entity per tile.
+     * Terrain is given per cell, row major from the region's origin, or left empty for all plains.
+     */
+    pub fn system_init_region(ctx:Context<SystemInitRegion>, region_x:u16, region_y:u16, terrain: Vec<Option<TerrainType>>) -> Result<()> {
+        // Region can only be instanced by Admin
+        let reference = &ctx.accounts.system_signer.components;
+        let mapmeta_c = ctx.accounts.map.components.get(&reference.mapmeta).unwrap();
+        let mapmeta = ComponentMapMeta::try_from_slice(&mapmeta_c.data.as_slice()).unwrap();
+        let cell_count = REGION_SIZE as usize * REGION_SIZE as usize;
+        if !mapmeta.chunked || (terrain.len() != 0 && terrain.len() != cell_count) {
+            return err!(ComponentErrors::InvalidLocation)
+        }
+
+        let origin = ComponentLocation {
+            x: region_x.checked_mul(REGION_SIZE).ok_or(ComponentErrors::InvalidLocation)?,
+            y: region_y.checked_mul(REGION_SIZE).ok_or(ComponentErrors::InvalidLocation)?,
+        };
+        if origin.x >= mapmeta.max_x || origin.y >= mapmeta.max_y {
+            return err!(ComponentErrors::InvalidLocation)
+        }
+
+        let mut tiles: Vec<Option<RegionTile>> = Vec::with_capacity(cell_count);
+        for idx in 0..cell_count {
+            let location = ComponentLocation {
+                x: origin.x.saturating_add((idx % REGION_SIZE as usize) as u16),
+                y: origin.y.saturating_add((idx / REGION_SIZE as usize) as u16),
+            };
+            if !is_on_map(&mapmeta, &location) {
+                tiles.push(None);
+                continue;
+            }
+            tiles.push(Some(RegionTile {
+                terrain: terrain.get(idx).cloned().flatten(),
+                feature_id: None,
+                occupant_id: None,
+            }));
+        }
+
+        // Region has Metadata, Location (its origin) and Region components
+        let mut components: BTreeMap<Pubkey, SerializedComponent> = BTreeMap::new();
+        let metadata = ComponentMetadata {
+            name: format!("Region ({region_x}, {region_y})"),
+            entity_type: EntityType::Region,
+            world_instance: ctx.accounts.world_instance.key(),
+        }.try_to_vec().unwrap();
+        components.insert(reference.metadata.key(), SerializedComponent { 
+            max_size: ComponentMetadata::get_max_size(),
+            schema_hash: ComponentMetadata::get_schema_hash(),
+            data: metadata
+        });
+        components.insert(reference.location.key(), origin.to_serialized_component());
+
+        let region = ComponentRegion {
+            origin_x: origin.x,
+            origin_y: origin.y,
+            tiles,
+        };
+        components.insert(reference.region.key(), region.to_serialized_component());
+
+        let system_signer_seeds:&[&[u8]] = &[
+            b"System_Signer",
+            &[*ctx.bumps.get("system_signer").unwrap()]
+        ];
+        let signer_seeds = &[system_signer_seeds];
+
+        let mint_entity_ctx = CpiContext::new_with_signer(
+            ctx.accounts.world_program.to_account_info(),
+            dominariworld::cpi::accounts::MintEntity{
+                entity: ctx.accounts.region_entity.to_account_info(),
+                payer: ctx.accounts.payer.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                world_instance: ctx.accounts.world_instance.to_account_info(),
+                world_config: ctx.accounts.world_config.to_account_info(),
+                system: ctx.accounts.system_signer.to_account_info(),
+                system_registration: ctx.accounts.system_registration.to_account_info(),
+                universe: ctx.accounts.universe.to_account_info(),
+            },
+            signer_seeds
+        );
+
+        dominariworld::cpi::mint_entity(mint_entity_ctx, get_region_id(region_x, region_y), components)?;
+        Ok(())
+    }
     
-    pub fn system_instance_feature(ctx:Context<SystemInstanceFeature>, entity_id: u64) -> Result<()> {
+    /**
+     * Tile owner builds a Feature from a feature Blueprint onto an empty Tile.
+     * The Feature's entity id comes from the Instance Index so callers can't pick it.
+     */
+    pub fn system_instance_feature(ctx:Context<SystemInstanceFeature>) -> Result<()> {
+        ctx.accounts.instance_index.check_in_play()?;
+
         // Check to make sure tile can be modified by payer
         let reference = &ctx.accounts.system_signer.components;
         let tile_owner_component = ctx.accounts.tile_entity.components.get(&reference.owner).unwrap();
         let tile_owner:ComponentOwner = ComponentOwner::try_from_slice(&tile_owner_component.data.as_slice()).unwrap();
         
-        if tile_owner.owner.unwrap().key() != ctx.accounts.payer.key() {
+        if tile_owner.owner != Some(ctx.accounts.payer.key()) {
             return err!(ComponentErrors::InvalidOwner)
         }
 
-        // TODO: Check Blueprint 'cost' component and transfer that fee to build the Feature
+        // Only feature blueprints can be built, and only onto a Tile without one
+        if ctx.accounts.blueprint.is_mod || !ctx.accounts.blueprint.components.contains_key(&reference.feature_rank) {
+            return err!(ComponentErrors::NotAFeature)
+        }
+        let tile_feature_component = ctx.accounts.tile_entity.components.get(&reference.feature).unwrap();
+        let mut tile_feature:ComponentFeature = ComponentFeature::try_from_slice(&tile_feature_component.data.as_slice()).unwrap();
+        if tile_feature.feature_id.is_some() {
+            return err!(ComponentErrors::TileHasFeature)
+        }
+
+        let entity_id = ctx.accounts.instance_index.next_feature_id();
+        ctx.accounts.instance_index.features_built += 1;
+
+        // If the Blueprint has a cost, transfer that fee to the treasury to build the Feature
+        if let Some(cost_c) = ctx.accounts.blueprint.components.get(&reference.cost) {
+            let cost = ComponentCost::try_from_slice(&cost_c.data.as_slice()).unwrap();
+            if cost.lamports > 0 {
+                anchor_lang::system_program::transfer(CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: ctx.accounts.treasury.to_account_info(),
+                    }
+                ), cost.lamports)?;
+                ctx.accounts.treasury.collected += cost.lamports;
+            }
+        }
 
         // Create Feature entity
         let mut components: BTreeMap<Pubkey, SerializedComponent> = BTreeMap::new();
@@ -199,6 +350,7 @@ pub mod dominarisystems {
         }.try_to_vec().unwrap();
         components.insert(reference.metadata.key(), SerializedComponent { 
             max_size: ComponentMetadata::get_max_size(), 
+            schema_hash: ComponentMetadata::get_schema_hash(),
             data:  metadata_component
         });
         // Just copy the Tile Location component
@@ -207,10 +359,11 @@ pub mod dominarisystems {
         
         let owner = ComponentOwner {
             owner: tile_owner.owner,
-            player: None,
+            player: tile_owner.player,
         }.try_to_vec().unwrap();
         components.insert(reference.owner.key(), SerializedComponent { 
             max_size: ComponentOwner::get_max_size(),
+            schema_hash: ComponentOwner::get_schema_hash(),
             data: owner
         });
 
@@ -219,6 +372,7 @@ pub mod dominarisystems {
         }.try_to_vec().unwrap();
         components.insert(reference.active.key(), SerializedComponent { 
             max_size: ComponentActive::get_max_size(),
+            schema_hash: ComponentActive::get_schema_hash(),
             data: active
         });
 
@@ -251,10 +405,13 @@ pub mod dominarisystems {
 
         dominariworld::cpi::mint_entity(mint_entity_ctx, entity_id, components)?;
         ctx.accounts.instance_index.features.push(entity_id);
+        if tile_owner.player.is_some() {
+            if let Some(standing) = get_standing_mut(&mut ctx.accounts.instance_index, tile_owner.player.unwrap()) {
+                standing.features += 1;
+            }
+        }
 
         // Modify the Tile Entity with the new Feature
-        let tile_feature_component = ctx.accounts.tile_entity.components.get(&reference.feature).unwrap();
-        let mut tile_feature:ComponentFeature = ComponentFeature::try_from_slice(&tile_feature_component.data.as_slice()).unwrap();
         tile_feature.feature_id = Some(entity_id);
         let data = tile_feature.try_to_vec().unwrap();
 
@@ -271,7 +428,7 @@ pub mod dominarisystems {
             },
             signer_seeds
         );
-        dominariworld::cpi::req_modify_component(modify_tile_ctx, vec![reference.feature.key()], vec![data])?;
+        dominariworld::cpi::req_modify_component(modify_tile_ctx, vec![reference.feature.key()], vec![data], None)?;
         Ok(())
     }
 
@@ -292,11 +449,118 @@ pub mod dominarisystems {
         dominariworld::cpi::instance_world(instance_ctx, instance)?;
         // Set up Instance Index
         ctx.accounts.instance_index.config = config; 
+        ctx.accounts.treasury.instance = instance;
+        ctx.accounts.vault.instance = instance;
+        Ok(())
+    }
+
+    /**
+     * Instance Authority withdraws tile purchases and feature build costs out of the treasury.
+     * Only what was paid in can come out, so the treasury stays rent exempt.
+     */
+    pub fn withdraw_treasury(ctx:Context<WithdrawTreasury>, amount: u64) -> Result<()> {
+        ctx.accounts.instance_authority.check_authorized(&ctx.accounts.payer.key(), ctx.remaining_accounts)?;
+        let treasury = &ctx.accounts.treasury;
+        if amount > treasury.collected.saturating_sub(treasury.withdrawn) {
+            return err!(DominariError::InsufficientTreasury)
+        }
+
+        **ctx.accounts.treasury.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.payer.to_account_info().try_borrow_mut_lamports()? += amount;
+        ctx.accounts.treasury.withdrawn += amount;
+
+        emit!(TreasuryWithdrawn {
+            instance: ctx.accounts.world_instance.instance,
+            wallet: ctx.accounts.payer.key(),
+            amount
+        });
+        Ok(())
+    }
+
+    /**
+     * Player buys an unowned Tile, paying the Tile's cost into the instance treasury.
+     * Tiles start owned by the admin and only become player owned through this.
+     */
+    pub fn buy_tile(ctx:Context<BuyTile>) -> Result<()> {
+        let reference = &ctx.accounts.system_signer.components;
+        // Can't buy tiles in a paused or finished game
+        let play_phase = &ctx.accounts.instance_index.play_phase;
+        if *play_phase == PlayPhase::Finished {
+            return err!(DominariError::GameFinished)
+        }
+        if *play_phase == PlayPhase::Paused {
+            return err!(DominariError::GamePaused)
+        }
+
+        // Player must belong to the payer and be in this game
+        let player_stats_c = ctx.accounts.player.components.get(&reference.player_stats).unwrap();
+        let player_stats = ComponentPlayerStats::try_from_slice(&player_stats_c.data.as_slice()).unwrap();
+        if player_stats.key != ctx.accounts.payer.key() || !ctx.accounts.instance_index.players.contains(&ctx.accounts.player.entity_id) {
+            return err!(DominariError::InvalidPlayer)
+        }
+
+        // Tile can't already be owned by a player
+        let tile_owner_c = ctx.accounts.tile.components.get(&reference.owner).unwrap();
+        let mut tile_owner = ComponentOwner::try_from_slice(&tile_owner_c.data.as_slice()).unwrap();
+        if tile_owner.player.is_some() {
+            return err!(ComponentErrors::TileAlreadyOwned)
+        }
+
+        // Transfer the Tile's cost to the treasury
+        let tile_cost_c = ctx.accounts.tile.components.get(&reference.cost).unwrap();
+        let tile_cost = ComponentCost::try_from_slice(&tile_cost_c.data.as_slice()).unwrap();
+        if tile_cost.lamports > 0 {
+            anchor_lang::system_program::transfer(CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.treasury.to_account_info(),
+                }
+            ), tile_cost.lamports)?;
+            ctx.accounts.treasury.collected += tile_cost.lamports;
+        }
+
+        tile_owner.owner = Some(ctx.accounts.payer.key());
+        tile_owner.player = Some(ctx.accounts.player.entity_id);
+
+        let system_signer_seeds:&[&[u8]] = &[
+            b"System_Signer",
+            &[*ctx.bumps.get("system_signer").unwrap()]
+        ];
+        let signer_seeds = &[system_signer_seeds];
+
+        let modify_tile_ctx = CpiContext::new_with_signer(
+            ctx.accounts.world_program.to_account_info(),
+            dominariworld::cpi::accounts::ModifyComponent {
+                world_config: ctx.accounts.world_config.to_account_info(),
+                entity: ctx.accounts.tile.to_account_info(),
+                system: ctx.accounts.system_signer.to_account_info(),
+                system_registration: ctx.accounts.system_registration.to_account_info(),
+                universe: ctx.accounts.universe.to_account_info(),
+            },
+            signer_seeds
+        );
+        dominariworld::cpi::req_modify_component(modify_tile_ctx, vec![reference.owner.key()], vec![tile_owner.try_to_vec().unwrap()], None)?;
+
+        emit!(TileBought {
+            instance: ctx.accounts.world_instance.instance,
+            tile: ctx.accounts.tile.entity_id,
+            player: ctx.accounts.player.entity_id,
+            cost: tile_cost.lamports
+        });
+
         Ok(())
     }
 
+    /**
+     * System authority seats a player directly, without a seat or entry fee.
+     * Used for hot seat games where one wallet plays every side; everyone else uses join_game.
+     */
     pub fn system_init_player(ctx:Context<SystemInitPlayer>, entity_id: u64, name:String, image: String ) -> Result <()> {
         let reference = &ctx.accounts.system_signer.components;
+        if ctx.accounts.instance_index.play_phase == PlayPhase::Finished {
+            return err!(DominariError::GameFinished)
+        }
         // Optional: Fail if too many players already in the instance
         if ctx.accounts.instance_index.config.max_players == ctx.accounts.instance_index.players.len() as u16 {
             return err!(DominariError::PlayerCountExceeded)
@@ -317,6 +581,7 @@ pub mod dominarisystems {
         }.try_to_vec().unwrap();
         components.insert(reference.metadata.key(), SerializedComponent { 
             max_size: ComponentMetadata::get_max_size(), 
+            schema_hash: ComponentMetadata::get_schema_hash(),
             data:  metadata_component
         });
 
@@ -331,6 +596,7 @@ pub mod dominarisystems {
         }.try_to_vec().unwrap();
         components.insert(reference.player_stats.key(), SerializedComponent { 
             max_size: ComponentPlayerStats::get_max_size(), 
+            schema_hash: ComponentPlayerStats::get_schema_hash(),
             data:  player_stats_component
         });
 
@@ -359,90 +625,64 @@ pub mod dominarisystems {
         
         // Add player entity to instance index
         ctx.accounts.instance_index.players.push(entity_id);
+        ctx.accounts.instance_index.standings.push(PlayerStanding {
+            player: entity_id,
+            score: 0,
+            active_units: 0,
+            features: 0,
+            has_deployed: false
+        });
 
         Ok(())
     }
 
     /**
-     * Can only be called by a player that's in the game
+     * Any wallet can take one seat in an instance before it starts.
+     * Escrows the instance's entry fee (if any) into the vault.
      */
-    pub fn change_game_state(ctx:Context<ChangeGameState>, game_state: PlayPhase) -> Result<()> {
-        if !ctx.accounts.instance_index.players.contains(&ctx.accounts.player.entity_id) {
-            return err!(DominariError::InvalidPlayer)
-        }
-
-        ctx.accounts.instance_index.play_phase = game_state;
-        Ok(())
-    }
-
-    pub fn spawn_unit(ctx:Context<SpawnUnit>, unit_id: u64) -> Result<()> {
+    pub fn join_game(ctx:Context<JoinGame>, entity_id: u64, name:String, image: String) -> Result<()> {
         let reference = &ctx.accounts.system_signer.components;
-        // Check if the game is paused
-        if ctx.accounts.instance_index.play_phase != PlayPhase::Play {
-            return err!(DominariError::GamePaused)
-        }
-
-        // Check player belongs to payer
-        let player_stats_component = ctx.accounts.player.components.get(&reference.player_stats).unwrap();
-        let mut player_stats = ComponentPlayerStats::try_from_slice(&player_stats_component.data.as_slice()).unwrap();
-        if player_stats.key.key() != ctx.accounts.payer.key() {
-            return err!(ComponentErrors::InvalidOwner)
+        let play_phase = &ctx.accounts.instance_index.play_phase;
+        if *play_phase != PlayPhase::Lobby && *play_phase != PlayPhase::Build {
+            return err!(DominariError::GameAlreadyStarted)
         }
-
-        // Check that the Tile is Empty
-        let tile_occupant_component = ctx.accounts.tile.components.get(&reference.occupant).unwrap();
-        let mut tile_occupant = ComponentOccupant::try_from_slice(&tile_occupant_component.data.as_slice()).unwrap();
-        if tile_occupant.occupant_id.is_some() {
-            return err!(ComponentErrors::TileOccupied)
+        if ctx.accounts.instance_index.config.max_players == ctx.accounts.instance_index.players.len() as u16 {
+            return err!(DominariError::PlayerCountExceeded)
         }
 
-        // Check the Blueprint is in Player Hand
-        let card_idx = player_stats.cards.iter().position(|&card| card.key() == ctx.accounts.unit_blueprint.key());
-
-        if card_idx.is_none() {
-            return err!(ComponentErrors::InvalidCard)
+        if name.len() > STRING_MAX_SIZE as usize || image.len() > STRING_MAX_SIZE as usize {
+            return err!(ComponentErrors::StringTooLong)
         }
 
-        // Modify Player Hand to remove Blueprint
-        player_stats.cards.swap_remove(card_idx.unwrap());
-
-        // Create Unit Entity
+        // Create Player Entity
+        // Player has: Metadata and Player Stats
         let mut components: BTreeMap<Pubkey, SerializedComponent> = BTreeMap::new();
-        // Add Metadata, Owner, Location, Active + Blueprint components
         let metadata_component = ComponentMetadata {
-            name: ctx.accounts.unit_blueprint.name.clone(),
-            entity_type: EntityType::Unit,
-            world_instance: ctx.accounts.world_instance.key()
-
-        }.try_to_vec().unwrap();
-        components.insert(reference.metadata.key(), SerializedComponent {
-            max_size: ComponentMetadata::get_max_size(),
-            data: metadata_component
-        });
-        let owner_component = ComponentOwner {  
-            owner: Some(ctx.accounts.payer.key()),
-            player: Some(ctx.accounts.player.entity_id)
+            name: ctx.accounts.payer.key().to_string(),
+            entity_type: EntityType::Player,
+            world_instance: ctx.accounts.world_instance.key(),
         }.try_to_vec().unwrap();
-        components.insert(reference.owner.key(), SerializedComponent {
-            max_size: ComponentOwner::get_max_size(),
-            data: owner_component
+        components.insert(reference.metadata.key(), SerializedComponent { 
+            max_size: ComponentMetadata::get_max_size(), 
+            schema_hash: ComponentMetadata::get_schema_hash(),
+            data:  metadata_component
         });
-        let active_component = ComponentActive {
-            active: true
+
+        let player_stats_component = ComponentPlayerStats {
+            name,
+            image, 
+            key: ctx.accounts.payer.key(),
+            score: 0,
+            kills: 0,
+            // Give them Starting Card
+            cards: ctx.accounts.instance_index.config.starting_cards.clone()
         }.try_to_vec().unwrap();
-        components.insert(reference.active.key(), SerializedComponent{
-            max_size: ComponentActive::get_max_size(),
-            data: active_component
+        components.insert(reference.player_stats.key(), SerializedComponent { 
+            max_size: ComponentPlayerStats::get_max_size(), 
+            schema_hash: ComponentPlayerStats::get_schema_hash(),
+            data:  player_stats_component
         });
 
-        // Clone the Tile's location component to the Unit
-        components.insert(
-            reference.location.key(),
-            ctx.accounts.tile.components.get(&reference.location).unwrap().clone()
-        );
-        
-        components.extend(ctx.accounts.unit_blueprint.components.clone());
-        
         let system_signer_seeds:&[&[u8]] = &[
             b"System_Signer",
             &[*ctx.bumps.get("system_signer").unwrap()]
@@ -452,7 +692,7 @@ pub mod dominarisystems {
         let mint_entity_ctx = CpiContext::new_with_signer(
             ctx.accounts.world_program.to_account_info(),
             dominariworld::cpi::accounts::MintEntity{
-                entity: ctx.accounts.unit.to_account_info(),
+                entity: ctx.accounts.player_entity.to_account_info(),
                 payer: ctx.accounts.payer.to_account_info(),
                 system_program: ctx.accounts.system_program.to_account_info(),
                 world_instance: ctx.accounts.world_instance.to_account_info(),
@@ -464,64 +704,442 @@ pub mod dominarisystems {
             signer_seeds
         );
 
-        dominariworld::cpi::mint_entity(mint_entity_ctx, unit_id, components)?;
-        // Add the new Unit Entity to Instance index
-        ctx.accounts.instance_index.units.push(unit_id);
+        dominariworld::cpi::mint_entity(mint_entity_ctx, entity_id, components)?;
 
-        // Modify Tile to point to Unit Entity
-        tile_occupant.occupant_id = Some(unit_id);
-        let data = tile_occupant.try_to_vec().unwrap();
-        let modify_tile_ctx = CpiContext::new_with_signer(
-            ctx.accounts.world_program.to_account_info(),
-            dominariworld::cpi::accounts::ModifyComponent {
-                world_config: ctx.accounts.world_config.to_account_info(),
-                entity: ctx.accounts.tile.to_account_info(),
-                system: ctx.accounts.system_signer.to_account_info(),
-                system_registration: ctx.accounts.system_registration.to_account_info(),
-                universe: ctx.accounts.universe.to_account_info(),
-            },
-            signer_seeds
-        );
-        dominariworld::cpi::req_modify_component(modify_tile_ctx, vec![ctx.accounts.system_signer.components.occupant.key()], vec![data])?;
+        // Escrow the entry fee
+        let entry_fee = ctx.accounts.instance_index.config.entry_fee.unwrap_or(0);
+        if entry_fee > 0 {
+            anchor_lang::system_program::transfer(CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                }
+            ), entry_fee)?;
+            ctx.accounts.vault.pool += entry_fee;
+        }
+        ctx.accounts.vault.entrants.push(entity_id);
+        ctx.accounts.seat.player = entity_id;
+        ctx.accounts.seat.entry_fee = entry_fee;
 
-        // Update Player Stats to no longer have that card
-        let data = player_stats.try_to_vec().unwrap();
-        let modify_player_ctx = CpiContext::new_with_signer(
+        // Add player entity to instance index
+        ctx.accounts.instance_index.players.push(entity_id);
+        ctx.accounts.instance_index.standings.push(PlayerStanding {
+            player: entity_id,
+            score: 0,
+            active_units: 0,
+            features: 0,
+            has_deployed: false
+        });
+
+        emit!(PlayerJoined {
+            instance: ctx.accounts.world_instance.instance,
+            player: entity_id,
+            wallet: ctx.accounts.payer.key(),
+            entry_fee
+        });
+        Ok(())
+    }
+
+    /**
+     * Gives up a seat before the game starts.
+     * Refunds the entry fee, closes the player entity and frees the wallet to join again.
+     */
+    pub fn leave_game(ctx:Context<LeaveGame>) -> Result<()> {
+        let play_phase = &ctx.accounts.instance_index.play_phase;
+        if *play_phase != PlayPhase::Lobby && *play_phase != PlayPhase::Build {
+            return err!(DominariError::GameAlreadyStarted)
+        }
+
+        let player = ctx.accounts.seat.player;
+        let refund = ctx.accounts.seat.entry_fee;
+        if refund > 0 {
+            **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? -= refund;
+            **ctx.accounts.payer.to_account_info().try_borrow_mut_lamports()? += refund;
+            ctx.accounts.vault.pool -= refund;
+        }
+        ctx.accounts.vault.entrants.retain(|id| *id != player);
+
+        let index = &mut ctx.accounts.instance_index;
+        index.players.retain(|id| *id != player);
+        index.standings.retain(|standing| standing.player != player);
+        index.phase_votes.retain(|id| *id != player);
+
+        let system_signer_seeds:&[&[u8]] = &[
+            b"System_Signer",
+            &[*ctx.bumps.get("system_signer").unwrap()]
+        ];
+        let signer_seeds = &[system_signer_seeds];
+
+        let close_ctx = CpiContext::new_with_signer(
             ctx.accounts.world_program.to_account_info(),
-            dominariworld::cpi::accounts::ModifyComponent {
+            dominariworld::cpi::accounts::CloseEntities {
                 world_config: ctx.accounts.world_config.to_account_info(),
-                entity: ctx.accounts.player.to_account_info(),
                 system: ctx.accounts.system_signer.to_account_info(),
                 system_registration: ctx.accounts.system_registration.to_account_info(),
                 universe: ctx.accounts.universe.to_account_info(),
             },
             signer_seeds
-        );
-        dominariworld::cpi::req_modify_component(modify_player_ctx, vec![ctx.accounts.system_signer.components.player_stats.key()], vec![data])?;
+        ).with_remaining_accounts(vec![
+            ctx.accounts.player_entity.to_account_info(),
+            ctx.accounts.payer.to_account_info()
+        ]);
+        dominariworld::cpi::req_close_entities(close_ctx)?;
 
-        emit!(NewUnitSpawned {
+        emit!(PlayerLeft {
             instance: ctx.accounts.world_instance.instance,
-            tile: ctx.accounts.tile.entity_id,
-            player: ctx.accounts.player.entity_id,
-            unit: unit_id
+            player,
+            wallet: ctx.accounts.payer.key(),
+            refund
         });
-
         Ok(())
     }
 
-    pub fn move_unit(ctx:Context<MoveUnit>) -> Result<()> {
-        let reference = &ctx.accounts.system_signer.components;
-        // Check if the game is paused
-        if ctx.accounts.instance_index.play_phase != PlayPhase::Play {
-            return err!(DominariError::GamePaused)
+    /**
+     * Pays a seated player their share of the vault once the game is Finished.
+     * The pool is split by final score between everyone that joined through join_game,
+     * or evenly if nobody scored. Closing the seat stops a second claim.
+     */
+    pub fn claim_prize(ctx:Context<ClaimPrize>) -> Result<()> {
+        let index = &ctx.accounts.instance_index;
+        if index.play_phase != PlayPhase::Finished {
+            return err!(DominariError::GameNotFinished)
         }
 
-        // From.Occupant must be Unit
-        let from_occupant_component = ctx.accounts.from.components.get(&reference.occupant).unwrap();
-        let mut from_occupant = ComponentOccupant::try_from_slice(&from_occupant_component.data.as_slice()).unwrap();
-        if from_occupant.occupant_id.unwrap() != ctx.accounts.unit.entity_id {
-            return err!(ComponentErrors::InvalidUnit)
-        }
+        let player = ctx.accounts.seat.player;
+        let vault = &ctx.accounts.vault;
+        let entrant_scores: Vec<(u64, u64)> = index.standings.iter()
+            .filter(|standing| vault.entrants.contains(&standing.player))
+            .map(|standing| (standing.player, standing.score))
+            .collect();
+        let total_score: u64 = entrant_scores.iter().map(|(_, score)| score).sum();
+        let player_score = entrant_scores.iter().find(|(id, _)| *id == player).map(|(_, score)| *score).unwrap_or(0);
+
+        let amount = if total_score > 0 {
+            (vault.pool as u128 * player_score as u128 / total_score as u128) as u64
+        } else if vault.entrants.contains(&player) {
+            vault.pool / vault.entrants.len() as u64
+        } else {
+            0
+        };
+
+        if amount > 0 {
+            **ctx.accounts.vault.to_account_info().try_borrow_mut_lamports()? -= amount;
+            **ctx.accounts.payer.to_account_info().try_borrow_mut_lamports()? += amount;
+            ctx.accounts.vault.paid_out += amount;
+        }
+
+        emit!(PrizeClaimed {
+            instance: ctx.accounts.world_instance.instance,
+            player,
+            wallet: ctx.accounts.payer.key(),
+            amount
+        });
+        Ok(())
+    }
+
+    /**
+     * Players pause or resume their game.
+     * With a pause quorum set, each call is a vote and the phase only flips once enough players agree.
+     */
+    pub fn change_game_state(ctx:Context<ChangeGameState>, game_state: PlayPhase) -> Result<()> {
+        let reference = &ctx.accounts.system_signer.components;
+        let player_stats_c = ctx.accounts.player.components.get(&reference.player_stats).unwrap();
+        let player_stats = ComponentPlayerStats::try_from_slice(&player_stats_c.data.as_slice()).unwrap();
+        let index = &mut ctx.accounts.instance_index;
+        if player_stats.key != ctx.accounts.payer.key() || !index.players.contains(&ctx.accounts.player.entity_id) {
+            return err!(DominariError::InvalidPlayer)
+        }
+        if index.play_phase == PlayPhase::Finished {
+            return err!(DominariError::GameFinished)
+        }
+        check_transition(&index.play_phase, &game_state, &PhaseRole::Player)?;
+
+        // Every vote is for flipping the current phase, so votes reset whenever the phase changes
+        let player = ctx.accounts.player.entity_id;
+        if index.phase_votes.contains(&player) {
+            return err!(DominariError::AlreadyVoted)
+        }
+        index.phase_votes.push(player);
+
+        let quorum = get_quorum(index);
+        emit!(PhaseVoteCast {
+            instance: ctx.accounts.world_instance.instance,
+            player,
+            to: game_state.clone(),
+            votes: index.phase_votes.len() as u16,
+            quorum
+        });
+
+        if (index.phase_votes.len() as u16) < quorum {
+            return Ok(())
+        }
+
+        let from = set_phase(index, game_state, Clock::get().unwrap().slot);
+        emit!(PlayPhaseChanged {
+            instance: ctx.accounts.world_instance.instance,
+            from,
+            to: index.play_phase.clone(),
+            changed_by: ctx.accounts.payer.key()
+        });
+        Ok(())
+    }
+
+    /**
+     * Instance authority moves the game through Lobby -> Build -> Play, and can pause, resume or end it at any point.
+     * Authority is the World's Instance Authority, so it follows transfers and signer sets (co-signers go in remaining accounts).
+     */
+    pub fn set_play_phase(ctx:Context<SetPlayPhase>, game_state: PlayPhase) -> Result<()> {
+        ctx.accounts.instance_authority.check_authorized(&ctx.accounts.payer.key(), ctx.remaining_accounts)?;
+        let index = &mut ctx.accounts.instance_index;
+        if index.play_phase == PlayPhase::Finished {
+            return err!(DominariError::GameFinished)
+        }
+        check_transition(&index.play_phase, &game_state, &PhaseRole::Authority)?;
+
+        let from = set_phase(index, game_state, Clock::get().unwrap().slot);
+        emit!(PlayPhaseChanged {
+            instance: ctx.accounts.world_instance.instance,
+            from,
+            to: index.play_phase.clone(),
+            changed_by: ctx.accounts.payer.key()
+        });
+        Ok(())
+    }
+
+    /**
+     * Active player ends their turn, or any other player skips the active player once their turn has timed out.
+     */
+    pub fn end_turn(ctx:Context<EndTurn>) -> Result<()> {
+        let reference = &ctx.accounts.system_signer.components;
+        let index = &mut ctx.accounts.instance_index;
+        if index.config.turns.is_none() {
+            return err!(DominariError::NotTurnBased)
+        }
+        index.check_in_play()?;
+
+        // Caller has to be a player in this game
+        let player_stats_c = ctx.accounts.player.components.get(&reference.player_stats).unwrap();
+        let player_stats = ComponentPlayerStats::try_from_slice(&player_stats_c.data.as_slice()).unwrap();
+        if player_stats.key != ctx.accounts.payer.key() || !index.players.contains(&ctx.accounts.player.entity_id) {
+            return err!(DominariError::InvalidPlayer)
+        }
+
+        let clock = Clock::get().unwrap();
+        let skipped = ctx.accounts.player.entity_id != index.active_player;
+        if skipped && clock.slot <= index.turn_start + index.config.turns.as_ref().unwrap().timeout_slots {
+            return err!(DominariError::NotYourTurn)
+        }
+
+        let ended_player = index.active_player;
+        index.active_player = get_next_player(index);
+        index.turn_number += 1;
+        index.turn_start = clock.slot;
+
+        emit!(TurnEnded {
+            instance: ctx.accounts.world_instance.instance,
+            turn_number: index.turn_number,
+            ended_player,
+            active_player: index.active_player,
+            skipped
+        });
+        Ok(())
+    }
+
+    /**
+     * Spawns a unit from the player's hand onto an empty tile.
+     * On chunked maps the tile account is the Region holding the tile and location picks the cell.
+     */
+    pub fn spawn_unit(ctx:Context<SpawnUnit>, unit_id: u64, location: Option<ComponentLocation>) -> Result<()> {
+        let reference = &ctx.accounts.system_signer.components;
+        // Check if the game is paused or finished
+        ctx.accounts.instance_index.check_in_play()?;
+
+        // Check player belongs to payer
+        let player_stats_component = ctx.accounts.player.components.get(&reference.player_stats).unwrap();
+        let mut player_stats = ComponentPlayerStats::try_from_slice(&player_stats_component.data.as_slice()).unwrap();
+        if player_stats.key.key() != ctx.accounts.payer.key() {
+            return err!(ComponentErrors::InvalidOwner)
+        }
+        check_turn(&ctx.accounts.instance_index, Some(ctx.accounts.player.entity_id))?;
+
+        // Check that the Tile is Empty
+        let mut tile_region = match ctx.accounts.tile.components.get(&reference.region) {
+            Some(region_c) => Some(ComponentRegion::try_from_slice(&region_c.data.as_slice()).unwrap()),
+            None => None
+        };
+        let unit_location = match &tile_region {
+            Some(region) => {
+                if ctx.accounts.tile.instance != ctx.accounts.world_instance.instance {
+                    return err!(ComponentErrors::InvalidLocation)
+                }
+                let location = location.ok_or(ComponentErrors::InvalidLocation)?;
+                let region_tile = region.get_tile(&location).ok_or(ComponentErrors::InvalidLocation)?;
+                if region_tile.occupant_id.is_some() {
+                    return err!(ComponentErrors::TileOccupied)
+                }
+                location
+            },
+            None => {
+                let tile_occupant_component = ctx.accounts.tile.components.get(&reference.occupant).unwrap();
+                let tile_occupant = ComponentOccupant::try_from_slice(&tile_occupant_component.data.as_slice()).unwrap();
+                if tile_occupant.occupant_id.is_some() {
+                    return err!(ComponentErrors::TileOccupied)
+                }
+                let tile_location_c = ctx.accounts.tile.components.get(&reference.location).unwrap();
+                ComponentLocation::try_from_slice(&tile_location_c.data.as_slice()).unwrap()
+            }
+        };
+
+        // Check the Blueprint is in Player Hand
+        let card_idx = player_stats.cards.iter().position(|&card| card.key() == ctx.accounts.unit_blueprint.key());
+
+        if card_idx.is_none() {
+            return err!(ComponentErrors::InvalidCard)
+        }
+
+        // Modify Player Hand to remove Blueprint
+        player_stats.cards.swap_remove(card_idx.unwrap());
+
+        // Create Unit Entity
+        let mut components: BTreeMap<Pubkey, SerializedComponent> = BTreeMap::new();
+        // Add Metadata, Owner, Location, Active + Blueprint components
+        let metadata_component = ComponentMetadata {
+            name: ctx.accounts.unit_blueprint.name.clone(),
+            entity_type: EntityType::Unit,
+            world_instance: ctx.accounts.world_instance.key()
+
+        }.try_to_vec().unwrap();
+        components.insert(reference.metadata.key(), SerializedComponent {
+            max_size: ComponentMetadata::get_max_size(),
+            schema_hash: ComponentMetadata::get_schema_hash(),
+            data: metadata_component
+        });
+        let owner_component = ComponentOwner {  
+            owner: Some(ctx.accounts.payer.key()),
+            player: Some(ctx.accounts.player.entity_id)
+        }.try_to_vec().unwrap();
+        components.insert(reference.owner.key(), SerializedComponent {
+            max_size: ComponentOwner::get_max_size(),
+            schema_hash: ComponentOwner::get_schema_hash(),
+            data: owner_component
+        });
+        let active_component = ComponentActive {
+            active: true
+        }.try_to_vec().unwrap();
+        components.insert(reference.active.key(), SerializedComponent{
+            max_size: ComponentActive::get_max_size(),
+            schema_hash: ComponentActive::get_schema_hash(),
+            data: active_component
+        });
+
+        components.insert(reference.location.key(), unit_location.to_serialized_component());
+        
+        components.extend(ctx.accounts.unit_blueprint.components.clone());
+        
+        let system_signer_seeds:&[&[u8]] = &[
+            b"System_Signer",
+            &[*ctx.bumps.get("system_signer").unwrap()]
+        ];
+        let signer_seeds = &[system_signer_seeds];
+
+        let mint_entity_ctx = CpiContext::new_with_signer(
+            ctx.accounts.world_program.to_account_info(),
+            dominariworld::cpi::accounts::MintEntity{
+                entity: ctx.accounts.unit.to_account_info(),
+                payer: ctx.accounts.payer.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                world_instance: ctx.accounts.world_instance.to_account_info(),
+                world_config: ctx.accounts.world_config.to_account_info(),
+                system: ctx.accounts.system_signer.to_account_info(),
+                system_registration: ctx.accounts.system_registration.to_account_info(),
+                universe: ctx.accounts.universe.to_account_info(),
+            },
+            signer_seeds
+        );
+
+        dominariworld::cpi::mint_entity(mint_entity_ctx, unit_id, components)?;
+        // Add the new Unit Entity to Instance index
+        ctx.accounts.instance_index.units.push(unit_id);
+        if let Some(standing) = get_standing_mut(&mut ctx.accounts.instance_index, ctx.accounts.player.entity_id) {
+            standing.active_units += 1;
+            standing.has_deployed = true;
+        }
+
+        // Modify Tile (or its cell in the Region) to point to Unit Entity
+        let (tile_component, data) = match tile_region.as_mut() {
+            Some(region) => {
+                region.get_tile_mut(&unit_location).unwrap().occupant_id = Some(unit_id);
+                (reference.region.key(), region.try_to_vec().unwrap())
+            },
+            None => {
+                let tile_occupant = ComponentOccupant {
+                    occupant_id: Some(unit_id)
+                };
+                (reference.occupant.key(), tile_occupant.try_to_vec().unwrap())
+            }
+        };
+        let modify_tile_ctx = CpiContext::new_with_signer(
+            ctx.accounts.world_program.to_account_info(),
+            dominariworld::cpi::accounts::ModifyComponent {
+                world_config: ctx.accounts.world_config.to_account_info(),
+                entity: ctx.accounts.tile.to_account_info(),
+                system: ctx.accounts.system_signer.to_account_info(),
+                system_registration: ctx.accounts.system_registration.to_account_info(),
+                universe: ctx.accounts.universe.to_account_info(),
+            },
+            signer_seeds
+        );
+        dominariworld::cpi::req_modify_component(modify_tile_ctx, vec![tile_component], vec![data], None)?;
+
+        // Update Player Stats to no longer have that card
+        let data = player_stats.try_to_vec().unwrap();
+        let modify_player_ctx = CpiContext::new_with_signer(
+            ctx.accounts.world_program.to_account_info(),
+            dominariworld::cpi::accounts::ModifyComponent {
+                world_config: ctx.accounts.world_config.to_account_info(),
+                entity: ctx.accounts.player.to_account_info(),
+                system: ctx.accounts.system_signer.to_account_info(),
+                system_registration: ctx.accounts.system_registration.to_account_info(),
+                universe: ctx.accounts.universe.to_account_info(),
+            },
+            signer_seeds
+        );
+        dominariworld::cpi::req_modify_component(modify_player_ctx, vec![ctx.accounts.system_signer.components.player_stats.key()], vec![data], None)?;
+
+        emit!(NewUnitSpawned {
+            instance: ctx.accounts.world_instance.instance,
+            tile: ctx.accounts.tile.entity_id,
+            player: ctx.accounts.player.entity_id,
+            unit: unit_id
+        });
+
+        Ok(())
+    }
+
+    /**
+     * Moves a unit from one tile to another along a path of intermediate tiles (remaining accounts, in order).
+     * Every step has to be to an adjacent, empty, passable tile, and the steps plus terrain costs have to fit
+     * in the unit's movement. Moving to a neighbouring tile needs no path.
+     */
+    pub fn move_unit<'info>(ctx:Context<'_, '_, '_, 'info, MoveUnit<'info>>, expected_versions: Vec<EntityVersion>) -> Result<()> {
+        let reference = &ctx.accounts.system_signer.components;
+        // Check if the game is paused or finished
+        ctx.accounts.instance_index.check_in_play()?;
+
+        // Bail before doing anything if the client acted on stale entities
+        check_entity_versions(&expected_versions, &[
+            (ctx.accounts.from.key(), ctx.accounts.from.version),
+            (ctx.accounts.to.key(), ctx.accounts.to.version),
+            (ctx.accounts.unit.key(), ctx.accounts.unit.version),
+        ])?;
+
+        // From.Occupant must be Unit
+        let from_occupant_component = ctx.accounts.from.components.get(&reference.occupant).unwrap();
+        let mut from_occupant = ComponentOccupant::try_from_slice(&from_occupant_component.data.as_slice()).unwrap();
+        if from_occupant.occupant_id.unwrap() != ctx.accounts.unit.entity_id {
+            return err!(ComponentErrors::InvalidUnit)
+        }
         
         // Unit must be active
         let active_component = ctx.accounts.unit.components.get(&reference.active).unwrap();
@@ -537,33 +1155,701 @@ pub mod dominarisystems {
             return err!(ComponentErrors::TileOccupied)
         }
 
-        // Unit must be Owned by Player        
-        let unit_owner_component = ctx.accounts.unit.components.get(&reference.owner).unwrap();
-        let unit_owner = ComponentOwner::try_from_slice(&unit_owner_component.data.as_slice()).unwrap();
-        if unit_owner.owner.unwrap() != ctx.accounts.payer.key() {
-            return err!(ComponentErrors::InvalidOwner)
-        }
-        
-        // Unit must be recovered from last used
-        let clock = Clock::get().unwrap();
-        let unit_last_used_component = ctx.accounts.unit.components.get(&reference.last_used).unwrap();
-        let mut unit_last_used = ComponentLastUsed::try_from_slice(&unit_last_used_component.data.as_slice()).unwrap();
-        if unit_last_used.last_used != 0 && (unit_last_used.last_used + unit_last_used.recovery) >= clock.slot {
-            return err!(ComponentErrors::UnitRecovering)
+        // Unit must be Owned by Player        
+        let unit_owner_component = ctx.accounts.unit.components.get(&reference.owner).unwrap();
+        let unit_owner = ComponentOwner::try_from_slice(&unit_owner_component.data.as_slice()).unwrap();
+        if unit_owner.owner.unwrap() != ctx.accounts.payer.key() {
+            return err!(ComponentErrors::InvalidOwner)
+        }
+        check_turn(&ctx.accounts.instance_index, unit_owner.player)?;
+        
+        // Unit must be recovered from last used
+        let clock = Clock::get().unwrap();
+        let unit_last_used_component = ctx.accounts.unit.components.get(&reference.last_used).unwrap();
+        let mut unit_last_used = ComponentLastUsed::try_from_slice(&unit_last_used_component.data.as_slice()).unwrap();
+        if is_recovering(&ctx.accounts.instance_index, &unit_last_used, clock.slot) {
+            return err!(ComponentErrors::UnitRecovering)
+        }
+
+        // Walk the path From -> intermediate tiles -> To, paying for each step
+        let from_location_c = ctx.accounts.from.components.get(&reference.location).unwrap();
+        let from_location = ComponentLocation::try_from_slice(&from_location_c.data.as_slice()).unwrap();
+
+        let to_location_c = ctx.accounts.to.components.get(&reference.location).unwrap();
+        let to_location = ComponentLocation::try_from_slice(&to_location_c.data.as_slice()).unwrap();
+
+        let unit_range_component = ctx.accounts.unit.components.get(&reference.range).unwrap();
+        let unit_range = ComponentRange::try_from_slice(&unit_range_component.data.as_slice()).unwrap();
+        let unit_troop_class_c = ctx.accounts.unit.components.get(&reference.troop_class).unwrap();
+        let unit_troop_class = ComponentTroopClass::try_from_slice(&unit_troop_class_c.data.as_slice()).unwrap();
+
+        let mut movement_used: u64 = 0;
+        let mut last_location = from_location;
+        for tile_info in ctx.remaining_accounts.iter() {
+            // Path tiles are only read, so nothing downstream checks they belong to this world
+            let tile: Account<Entity> = Account::try_from(tile_info)?;
+            if tile.instance != ctx.accounts.world_instance.instance || tile.world_signer != ctx.accounts.world_config.key() {
+                return err!(ComponentErrors::InvalidLocation)
+            }
+            let tile_location_c = tile.components.get(&reference.location).ok_or(ComponentErrors::InvalidLocation)?;
+            let tile_location = ComponentLocation::try_from_slice(&tile_location_c.data.as_slice()).unwrap();
+            if !is_adjacent(&ctx.accounts.instance_index.config.grid_metric, &last_location, &tile_location) {
+                return err!(ComponentErrors::InvalidLocation)
+            }
+
+            // Can't pass through other units
+            let tile_occupant_c = tile.components.get(&reference.occupant).ok_or(ComponentErrors::InvalidLocation)?;
+            if ComponentOccupant::try_from_slice(&tile_occupant_c.data.as_slice()).unwrap().occupant_id.is_some() {
+                return err!(ComponentErrors::TileOccupied)
+            }
+
+            movement_used += get_step_cost(reference, &tile, &unit_troop_class.class)?;
+            last_location = tile_location;
+        }
+
+        if !is_adjacent(&ctx.accounts.instance_index.config.grid_metric, &last_location, &to_location) {
+            return err!(ComponentErrors::InvalidLocation)
+        }
+        movement_used += get_step_cost(reference, &ctx.accounts.to, &unit_troop_class.class)?;
+        if unit_range.movement < movement_used {
+            return err!(ComponentErrors::UnitLacksMovement)
+        }
+
+        let system_signer_seeds:&[&[u8]] = &[
+            b"System_Signer",
+            &[*ctx.bumps.get("system_signer").unwrap()]
+        ];
+        let signer_seeds = &[system_signer_seeds];
+
+        // Modify Unit's last_used & location
+        unit_last_used.last_used = get_pacing_mark(&ctx.accounts.instance_index, clock.slot);
+        // Modify From Occupant to be None
+        from_occupant.occupant_id = None;
+        // Modify To Occupant to be Unit
+        to_occupant.occupant_id = Some(ctx.accounts.unit.entity_id);
+
+        // All three entities are modified in a single batched CPI
+        let modify_ctx = CpiContext::new_with_signer(
+            ctx.accounts.world_program.to_account_info(),
+            dominariworld::cpi::accounts::ModifyComponentsBatch {
+                world_config: ctx.accounts.world_config.to_account_info(),
+                system: ctx.accounts.system_signer.to_account_info(),
+                system_registration: ctx.accounts.system_registration.to_account_info(),
+                universe: ctx.accounts.universe.to_account_info(),
+            },
+            signer_seeds
+        ).with_remaining_accounts(vec![
+            ctx.accounts.unit.to_account_info(),
+            ctx.accounts.from.to_account_info(),
+            ctx.accounts.to.to_account_info(),
+        ]);
+        dominariworld::cpi::req_modify_components_batch(modify_ctx, vec![
+            EntityModification {
+                components: vec![reference.last_used.key(), reference.location.key()],
+                data: vec![unit_last_used.try_to_vec().unwrap(), to_location_c.data.clone()],
+                expected_version: None
+            },
+            EntityModification {
+                components: vec![reference.occupant.key()],
+                data: vec![from_occupant.try_to_vec().unwrap()],
+                expected_version: None
+            },
+            EntityModification {
+                components: vec![reference.occupant.key()],
+                data: vec![to_occupant.try_to_vec().unwrap()],
+                expected_version: None
+            },
+        ])?;
+
+        // Emit Troop Movement
+        emit!(TroopMovement {
+            instance: ctx.accounts.world_instance.instance,
+            from: ctx.accounts.from.entity_id,
+            to: ctx.accounts.to.entity_id,
+            unit: ctx.accounts.unit.entity_id
+        });
+
+        Ok(())
+    }
+    
+    /**
+     * move_unit for chunked maps. Tiles are addressed by location, with the path ending at the destination.
+     * The Regions holding the unit's tile and every tile on the path are passed once each as remaining accounts.
+     */
+    pub fn move_unit_chunked<'info>(ctx:Context<'_, '_, '_, 'info, MoveUnitChunked<'info>>, path: Vec<ComponentLocation>, expected_versions: Vec<EntityVersion>) -> Result<()> {
+        let reference = &ctx.accounts.system_signer.components;
+        // Check if the game is paused or finished
+        ctx.accounts.instance_index.check_in_play()?;
+
+        // Unit must be active
+        let active_component = ctx.accounts.unit.components.get(&reference.active).unwrap();
+        let active = ComponentActive::try_from_slice(&active_component.data.as_slice()).unwrap();
+        if active.active == false {
+            return err!(ComponentErrors::UnitDead)
+        }
+
+        // Unit must be Owned by Player        
+        let unit_owner_component = ctx.accounts.unit.components.get(&reference.owner).unwrap();
+        let unit_owner = ComponentOwner::try_from_slice(&unit_owner_component.data.as_slice()).unwrap();
+        if unit_owner.owner.unwrap() != ctx.accounts.payer.key() {
+            return err!(ComponentErrors::InvalidOwner)
+        }
+        check_turn(&ctx.accounts.instance_index, unit_owner.player)?;
+
+        // Unit must be recovered from last used
+        let clock = Clock::get().unwrap();
+        let unit_last_used_component = ctx.accounts.unit.components.get(&reference.last_used).unwrap();
+        let mut unit_last_used = ComponentLastUsed::try_from_slice(&unit_last_used_component.data.as_slice()).unwrap();
+        if is_recovering(&ctx.accounts.instance_index, &unit_last_used, clock.slot) {
+            return err!(ComponentErrors::UnitRecovering)
+        }
+
+        // Load the Regions, each account only once so there's a single copy of each to modify
+        let mut region_infos: Vec<AccountInfo<'info>> = vec![];
+        let mut regions: Vec<ComponentRegion> = vec![];
+        let mut versions: Vec<(Pubkey, u64)> = vec![(ctx.accounts.unit.key(), ctx.accounts.unit.version)];
+        for region_info in ctx.remaining_accounts.iter() {
+            let region_entity: Account<Entity> = Account::try_from(region_info)?;
+            if region_entity.instance != ctx.accounts.world_instance.instance || region_entity.world_signer != ctx.accounts.world_config.key() || region_infos.iter().any(|info| info.key() == region_info.key()) {
+                return err!(ComponentErrors::InvalidLocation)
+            }
+            let region_c = region_entity.components.get(&reference.region).ok_or(ComponentErrors::InvalidLocation)?;
+            regions.push(ComponentRegion::try_from_slice(&region_c.data.as_slice()).unwrap());
+            region_infos.push(region_info.clone());
+            versions.push((region_info.key(), region_entity.version));
+        }
+        check_entity_versions(&expected_versions, &versions)?;
+
+        // Unit's tile must point to the Unit
+        let from_location_c = ctx.accounts.unit.components.get(&reference.location).unwrap();
+        let from_location = ComponentLocation::try_from_slice(&from_location_c.data.as_slice()).unwrap();
+        let from_idx = find_region(&regions, &from_location).ok_or(ComponentErrors::InvalidLocation)?;
+        if regions[from_idx].get_tile(&from_location).unwrap().occupant_id != Some(ctx.accounts.unit.entity_id) {
+            return err!(ComponentErrors::InvalidUnit)
+        }
+
+        // Walk the path, paying for each step
+        let to_location = path.last().ok_or(ComponentErrors::InvalidLocation)?.clone();
+        let unit_range_component = ctx.accounts.unit.components.get(&reference.range).unwrap();
+        let unit_range = ComponentRange::try_from_slice(&unit_range_component.data.as_slice()).unwrap();
+        let unit_troop_class_c = ctx.accounts.unit.components.get(&reference.troop_class).unwrap();
+        let unit_troop_class = ComponentTroopClass::try_from_slice(&unit_troop_class_c.data.as_slice()).unwrap();
+
+        let mut movement_used: u64 = 0;
+        let mut last_location = from_location.clone();
+        for location in path.iter() {
+            if !is_adjacent(&ctx.accounts.instance_index.config.grid_metric, &last_location, location) {
+                return err!(ComponentErrors::InvalidLocation)
+            }
+            let idx = find_region(&regions, location).ok_or(ComponentErrors::InvalidLocation)?;
+            let tile = regions[idx].get_tile(location).unwrap();
+            // Can't pass through other units
+            if tile.occupant_id.is_some() {
+                return err!(ComponentErrors::TileOccupied)
+            }
+            movement_used += get_region_step_cost(tile, &unit_troop_class.class)?;
+            last_location = location.clone();
+        }
+        if unit_range.movement < movement_used {
+            return err!(ComponentErrors::UnitLacksMovement)
+        }
+
+        // Modify Unit's last_used & location and move it between the Region cells
+        unit_last_used.last_used = get_pacing_mark(&ctx.accounts.instance_index, clock.slot);
+        regions[from_idx].get_tile_mut(&from_location).unwrap().occupant_id = None;
+        let to_idx = find_region(&regions, &to_location).unwrap();
+        regions[to_idx].get_tile_mut(&to_location).unwrap().occupant_id = Some(ctx.accounts.unit.entity_id);
+
+        let mut accounts = vec![
+            ctx.accounts.unit.to_account_info(),
+            region_infos[from_idx].clone(),
+        ];
+        let mut modifications = vec![
+            EntityModification {
+                components: vec![reference.last_used.key(), reference.location.key()],
+                data: vec![unit_last_used.try_to_vec().unwrap(), to_location.try_to_vec().unwrap()],
+                expected_version: None
+            },
+            EntityModification {
+                components: vec![reference.region.key()],
+                data: vec![regions[from_idx].try_to_vec().unwrap()],
+                expected_version: None
+            },
+        ];
+        if to_idx != from_idx {
+            accounts.push(region_infos[to_idx].clone());
+            modifications.push(EntityModification {
+                components: vec![reference.region.key()],
+                data: vec![regions[to_idx].try_to_vec().unwrap()],
+                expected_version: None
+            });
+        }
+
+        let system_signer_seeds:&[&[u8]] = &[
+            b"System_Signer",
+            &[*ctx.bumps.get("system_signer").unwrap()]
+        ];
+        let signer_seeds = &[system_signer_seeds];
+
+        let modify_ctx = CpiContext::new_with_signer(
+            ctx.accounts.world_program.to_account_info(),
+            dominariworld::cpi::accounts::ModifyComponentsBatch {
+                world_config: ctx.accounts.world_config.to_account_info(),
+                system: ctx.accounts.system_signer.to_account_info(),
+                system_registration: ctx.accounts.system_registration.to_account_info(),
+                universe: ctx.accounts.universe.to_account_info(),
+            },
+            signer_seeds
+        ).with_remaining_accounts(accounts);
+        dominariworld::cpi::req_modify_components_batch(modify_ctx, modifications)?;
+
+        emit!(RegionTroopMovement {
+            instance: ctx.accounts.world_instance.instance,
+            from: from_location,
+            to: to_location,
+            unit: ctx.accounts.unit.entity_id
+        });
+
+        Ok(())
+    }
+    
+    /**
+     * Only used when the game is in CommitReveal randomness mode.
+     * Player commits to hash(seed, defender) for a unit, then reveals the seed in a later slot when attacking that defender with it.
+     */
+    pub fn commit_randomness(ctx:Context<CommitRandomness>, commitment: [u8; 32]) -> Result<()> {
+        let reference = &ctx.accounts.system_signer.components;
+        ctx.accounts.instance_index.check_in_play()?;
+        if ctx.accounts.instance_index.config.randomness != RandomnessMode::CommitReveal {
+            return err!(DominariError::InvalidRandomnessMode)
+        }
+
+        // Only the unit's owner can commit for it
+        let attacker_owner_c = ctx.accounts.attacker.components.get(&reference.owner).unwrap();
+        let attacker_owner = ComponentOwner::try_from_slice(&attacker_owner_c.data.as_slice()).unwrap();
+        if attacker_owner.owner != Some(ctx.accounts.payer.key()) {
+            return err!(ComponentErrors::InvalidOwner)
+        }
+
+        ctx.accounts.randomness_commit.commitment = commitment;
+        ctx.accounts.randomness_commit.committer = ctx.accounts.payer.key();
+        ctx.accounts.randomness_commit.slot = Clock::get().unwrap().slot;
+        ctx.accounts.randomness_commit.target_slot = get_target_slot(ctx.accounts.randomness_commit.slot);
+        Ok(())
+    }
+
+    /**
+     * A commitment whose target slot hash has left SlotHashes can't be revealed anymore.
+     * Letting it expire counts as the unit's attack: it's marked used and has to recover like it attacked,
+     * so withholding a bad roll costs at least as much as taking it.
+     */
+    pub fn clear_expired_commitment(ctx:Context<ClearExpiredCommitment>) -> Result<()> {
+        let reference = &ctx.accounts.system_signer.components;
+        let clock = Clock::get().unwrap();
+        let target_slot = ctx.accounts.randomness_commit.target_slot;
+        if clock.slot <= target_slot || get_slot_hash(&ctx.accounts.slot_hashes, target_slot)?.is_some() {
+            return err!(DominariError::CommitmentNotExpired)
+        }
+
+        let attacker_last_used_c = ctx.accounts.attacker.components.get(&reference.last_used).unwrap();
+        let mut attacker_last_used = ComponentLastUsed::try_from_slice(&attacker_last_used_c.data.as_slice()).unwrap();
+        attacker_last_used.last_used = get_pacing_mark(&ctx.accounts.instance_index, clock.slot);
+
+        let system_signer_seeds:&[&[u8]] = &[
+            b"System_Signer",
+            &[*ctx.bumps.get("system_signer").unwrap()]
+        ];
+        let signer_seeds = &[system_signer_seeds];
+
+        let modify_attacker_ctx = CpiContext::new_with_signer(
+            ctx.accounts.world_program.to_account_info(),
+            dominariworld::cpi::accounts::ModifyComponent {
+                world_config: ctx.accounts.world_config.to_account_info(),
+                entity: ctx.accounts.attacker.to_account_info(),
+                system: ctx.accounts.system_signer.to_account_info(),
+                system_registration: ctx.accounts.system_registration.to_account_info(),
+                universe: ctx.accounts.universe.to_account_info(),
+            },
+            signer_seeds
+        );
+        dominariworld::cpi::req_modify_component(modify_attacker_ctx, vec![reference.last_used.key()], vec![attacker_last_used.try_to_vec().unwrap()], None)?;
+        Ok(())
+    }
+
+    pub fn attack_tile(ctx:Context<AttackTile>, seed: Option<[u8; 32]>, expected_versions: Vec<EntityVersion>) -> Result<()> {
+        // Attacker could be Feature or Unit (just needs Damage Component)
+        let attacker = &ctx.accounts.attacker;
+        let defender = &ctx.accounts.defender;
+        let reference = &ctx.accounts.system_signer.components;
+
+        // Check if the game is paused or finished
+        ctx.accounts.instance_index.check_in_play()?;
+
+        // Bail before doing anything if the client acted on stale entities
+        check_entity_versions(&expected_versions, &[
+            (attacker.key(), attacker.version),
+            (defender.key(), defender.version),
+            (ctx.accounts.defending_tile.key(), ctx.accounts.defending_tile.version),
+            (ctx.accounts.attacking_player.key(), ctx.accounts.attacking_player.version),
+        ])?;
+        
+        // Check that attacker is owned by Payer
+        let attacker_owner_c = attacker.components.get(&reference.owner).unwrap();
+        let attacker_owner = ComponentOwner::try_from_slice(&attacker_owner_c.data.as_slice()).unwrap();
+        if attacker_owner.owner != Some(ctx.accounts.payer.key()) {
+            return err!(ComponentErrors::InvalidOwner)
+        }
+        check_turn(&ctx.accounts.instance_index, attacker_owner.player)?;
+        
+        // Check that attacker is active
+        let attacker_active_c = attacker.components.get(&reference.active).unwrap();
+        let attacker_active = ComponentActive::try_from_slice(&attacker_active_c.data.as_slice()).unwrap();
+        if attacker_active.active == false {
+            return err!(ComponentErrors::UnitDead)
+        }
+
+        // Check that defender is NOT owned by Payer
+        let defender_owner_c = defender.components.get(&reference.owner).unwrap();
+        let defender_owner = ComponentOwner::try_from_slice(&defender_owner_c.data.as_slice()).unwrap();
+        if defender_owner.player == attacker_owner.player {
+            return err!(ComponentErrors::FriendlyFire)
+        }
+
+        // Check attacker has damage component
+        let attacker_damage_c = attacker.components.get(&reference.damage).unwrap();
+        let attacker_damage = ComponentDamage::try_from_slice(&attacker_damage_c.data.as_slice()).unwrap();
+
+        // Check defender is active and has health component
+        let defender_active_c = defender.components.get(&reference.active).unwrap();
+        let mut defender_active = ComponentActive::try_from_slice(&defender_active_c.data.as_slice()).unwrap();
+        if defender_active.active == false {
+            return err!(ComponentErrors::UnitDead)
+        }
+        let defender_health_c = defender.components.get(&reference.health);
+        if defender_health_c.is_none() {
+            return err!(ComponentErrors::NoHealthComponent)
+        }
+        let mut defender_health = ComponentHealth::try_from_slice(&defender_health_c.unwrap().data.as_slice()).unwrap();
+
+        // Defender must be in Range of Attacker
+        let attacker_location_c = attacker.components.get(&reference.location).unwrap();
+        let attacker_location = ComponentLocation::try_from_slice(&attacker_location_c.data.as_slice()).unwrap();
+        let defender_location_c = defender.components.get(&reference.location).unwrap();
+        let defender_location = ComponentLocation::try_from_slice(&defender_location_c.data.as_slice()).unwrap();
+        
+        // Defending tile (or the Region holding it on chunked maps) has to be the one the defender is standing on, its terrain protects the defender
+        let mut defending_tile = TileView::load(reference, &ctx.accounts.defending_tile, &defender_location)?;
+
+        let distance = get_distance(&ctx.accounts.instance_index.config.grid_metric, &attacker_location, &defender_location);
+        let attacker_range_c = attacker.components.get(&reference.range).unwrap();
+        let attacker_range = ComponentRange::try_from_slice(&attacker_range_c.data.as_slice()).unwrap();
+        if distance > attacker_range.attack_range {
+            return err!(ComponentErrors::OutOfRange)
+        }
+
+        // Check attacker last used isn't violated
+        let clock = Clock::get().unwrap();
+        let attacker_last_used_c = attacker.components.get(&reference.last_used).unwrap();
+        let mut attacker_last_used = ComponentLastUsed::try_from_slice(&attacker_last_used_c.data.as_slice()).unwrap();
+        if is_recovering(&ctx.accounts.instance_index, &attacker_last_used, clock.slot) {
+            return err!(ComponentErrors::UnitRecovering)
+        }
+        attacker_last_used.last_used = get_pacing_mark(&ctx.accounts.instance_index, clock.slot);        
+        
+        let system_signer_seeds:&[&[u8]] = &[
+            b"System_Signer",
+            &[*ctx.bumps.get("system_signer").unwrap()]
+        ];
+        let signer_seeds = &[system_signer_seeds];
+
+        // Every modification is collected and applied in one batched CPI at the end
+        let mut modified_entities: Vec<AccountInfo> = vec![ctx.accounts.attacker.to_account_info()];
+        let mut modifications: Vec<EntityModification> = vec![EntityModification {
+            components: vec![reference.last_used.key()],
+            data: vec![attacker_last_used.try_to_vec().unwrap()],
+            expected_version: None
+        }];
+
+        // Roll Damage for Attacker, apply modifiers 
+        let entropy = match ctx.accounts.instance_index.config.randomness {
+            RandomnessMode::SlotHashes => derive_entropy(&get_recent_slot_hash(&ctx.accounts.slot_hashes)?, &attacker.key(), &defender.key(), None),
+            RandomnessMode::CommitReveal => {
+                if ctx.accounts.randomness_commit.data_is_empty() || seed.is_none() {
+                    return err!(DominariError::MissingCommitment)
+                }
+                let commit: Account<RandomnessCommit> = Account::try_from(&ctx.accounts.randomness_commit)?;
+                if commit.committer != ctx.accounts.payer.key() || commit.commitment != get_commitment(seed.as_ref().unwrap(), &defender.key()) {
+                    return err!(DominariError::InvalidCommitment)
+                }
+                // Roll uses the hash of the target slot fixed at commit time, no matter when the seed is revealed
+                if clock.slot <= commit.target_slot {
+                    return err!(DominariError::CommitmentNotReady)
+                }
+                let target_hash = match get_slot_hash(&ctx.accounts.slot_hashes, commit.target_slot)? {
+                    Some(target_hash) => target_hash,
+                    None => return err!(DominariError::CommitmentExpired)
+                };
+                commit.close(ctx.accounts.payer.to_account_info())?;
+                derive_entropy(&target_hash, &attacker.key(), &defender.key(), seed.as_ref())
+            }
+        };
+        let mut dmg = get_random_u64(&entropy, attacker_damage.max_damage); 
+        
+        // check if defender is Feature, if not, look for it's TroopClass
+        let defender_metadata_c = defender.components.get(&reference.metadata).unwrap();
+        let defender_metadata = ComponentMetadata::try_from_slice(&defender_metadata_c.data.as_slice()).unwrap();
+
+        let mut terrain_defense = 0;
+        if defender_metadata.entity_type == EntityType::Feature {
+            dmg += attacker_damage.bonus_feature as u64;
+        } else {
+            let defender_troop_class_c = defender.components.get(&reference.troop_class).unwrap();
+            let defender_troop_class = ComponentTroopClass::try_from_slice(&defender_troop_class_c.data.as_slice()).unwrap();
+            match defender_troop_class.class {
+                TroopClass::Aircraft => dmg += attacker_damage.bonus_aircraft as u64,
+                TroopClass::Infantry => dmg += attacker_damage.bonus_infantry as u64,
+                TroopClass::Armor => dmg += attacker_damage.bonus_armor as u64,
+            }
+
+            // Units take cover in the terrain of the tile they're standing on
+            if let Some(terrain) = defending_tile.terrain() {
+                terrain_defense = terrain.get_defense(&defender_troop_class.class);
+            }
+        }
+
+        if dmg < attacker_damage.min_damage {
+            dmg = attacker_damage.min_damage;
+        }
+        dmg = dmg.saturating_sub(terrain_defense);
+
+        if dmg >= defender_health.health {
+            defender_health.health = 0;
+            defender_active.active = false;
+
+            // Modify the defending tile (or its cell in the Region) to remove the defender
+            modified_entities.push(ctx.accounts.defending_tile.to_account_info());
+            if defender_metadata.entity_type == EntityType::Feature {
+                modifications.push(defending_tile.clear_feature(reference));
+            } else {
+                modifications.push(defending_tile.clear_occupant(reference));
+            }
+
+            // Defender's player loses the unit or feature from their standing
+            if defender_owner.player.is_some() {
+                if let Some(standing) = get_standing_mut(&mut ctx.accounts.instance_index, defender_owner.player.unwrap()) {
+                    if defender_metadata.entity_type == EntityType::Feature {
+                        standing.features = standing.features.saturating_sub(1);
+                    } else {
+                        standing.active_units = standing.active_units.saturating_sub(1);
+                    }
+                }
+            }
+
+            // Credit the attacking player with the kill; attackers without a player (ie. admin features) get nothing
+            if attacker_owner.player.is_some() {
+                let attacking_player = &ctx.accounts.attacking_player;
+                if attacking_player.entity_id != attacker_owner.player.unwrap() {
+                    return err!(DominariError::InvalidPlayer)
+                }
+                let player_stats_c = attacking_player.components.get(&reference.player_stats).unwrap();
+                let mut player_stats = ComponentPlayerStats::try_from_slice(&player_stats_c.data.as_slice()).unwrap();
+
+                let value = match defender.components.get(&reference.value) {
+                    Some(value_c) => ComponentValue::try_from_slice(&value_c.data.as_slice()).unwrap().value,
+                    None => 0
+                };
+                player_stats.score += value;
+                player_stats.kills += 1;
+                if let Some(standing) = get_standing_mut(&mut ctx.accounts.instance_index, attacking_player.entity_id) {
+                    standing.score = player_stats.score;
+                }
+
+                // Roll a card from the defender's drop table if the attacker has room in their hand
+                let mut card: Option<Pubkey> = None;
+                if let Some(drop_table_c) = defender.components.get(&reference.drop_table) {
+                    let drop_table = ComponentDropTable::try_from_slice(&drop_table_c.data.as_slice()).unwrap();
+                    if drop_table.drop_table.len() > 0 && (player_stats.cards.len() as u64) < PLAYER_MAX_CARDS {
+                        let drop_entropy = derive_entropy(&entropy, &attacker.key(), &defender.key(), None);
+                        let idx = get_random_u64(&drop_entropy, drop_table.drop_table.len() as u64 - 1);
+                        card = Some(drop_table.drop_table[idx as usize]);
+                        player_stats.cards.push(card.unwrap());
+                    }
+                }
+
+                modified_entities.push(attacking_player.to_account_info());
+                modifications.push(EntityModification {
+                    components: vec![reference.player_stats.key()],
+                    data: vec![player_stats.try_to_vec().unwrap()],
+                    expected_version: None
+                });
+
+                emit!(UnitKilled {
+                    instance: ctx.accounts.world_instance.instance,
+                    attacker: attacker.entity_id,
+                    defender: defender.entity_id,
+                    player: attacking_player.entity_id,
+                    score: value,
+                    kills: player_stats.kills,
+                    card
+                });
+            }
+        } else {
+            defender_health.health -= dmg;
+        }
+
+        // Modify defender health
+            // If defender health at 0, Modify active as well
+        modified_entities.push(ctx.accounts.defender.to_account_info());
+        modifications.push(EntityModification {
+            components: vec![reference.health.key(), reference.active.key()],
+            data: vec![defender_health.try_to_vec().unwrap(), defender_active.try_to_vec().unwrap()],
+            expected_version: None
+        });
+
+        let modify_ctx = CpiContext::new_with_signer(
+            ctx.accounts.world_program.to_account_info(),
+            dominariworld::cpi::accounts::ModifyComponentsBatch {
+                world_config: ctx.accounts.world_config.to_account_info(),
+                system: ctx.accounts.system_signer.to_account_info(),
+                system_registration: ctx.accounts.system_registration.to_account_info(),
+                universe: ctx.accounts.universe.to_account_info(),
+            },
+            signer_seeds
+        ).with_remaining_accounts(modified_entities);
+        dominariworld::cpi::req_modify_components_batch(modify_ctx, modifications)?;
+
+
+        emit!(TileAttacked{
+            instance: ctx.accounts.world_instance.instance,
+            attacker: attacker.entity_id,
+            defender: defender.entity_id,
+            defending_tile: ctx.accounts.defending_tile.entity_id,
+            damage: dmg
+        });
+
+        // Check win conditions after every combat
+        let index = &mut ctx.accounts.instance_index;
+        if let Some((winner, condition)) = check_victory(index, clock.slot) {
+            let from = set_phase(index, PlayPhase::Finished, clock.slot);
+            index.winner = Some(winner);
+            emit!(PlayPhaseChanged {
+                instance: ctx.accounts.world_instance.instance,
+                from,
+                to: PlayPhase::Finished,
+                changed_by: ctx.accounts.payer.key()
+            });
+            emit!(GameFinished {
+                instance: ctx.accounts.world_instance.instance,
+                winner,
+                condition
+            });
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Occupant of a tile uses the feature on that tile.
+     * Currently only healers (HealingPower + FeatureRank) can be used.
+     */
+    pub fn use_feature(ctx:Context<UseFeature>) -> Result<()> {
+        let reference = &ctx.accounts.system_signer.components;
+        // Check if the game is paused or finished
+        ctx.accounts.instance_index.check_in_play()?;
+
+        // Unit must be the Tile's occupant and Feature must be the Tile's feature (tile is the Region holding it on chunked maps)
+        let unit_location_c = ctx.accounts.unit.components.get(&reference.location).unwrap();
+        let unit_location = ComponentLocation::try_from_slice(&unit_location_c.data.as_slice()).unwrap();
+        let tile = TileView::load(reference, &ctx.accounts.tile, &unit_location)?;
+        if tile.occupant_id() != Some(ctx.accounts.unit.entity_id) {
+            return err!(ComponentErrors::InvalidUnit)
+        }
+        if tile.feature_id() != Some(ctx.accounts.feature.entity_id) {
+            return err!(ComponentErrors::InvalidFeature)
+        }
+
+        // Unit must be owned by Player and active
+        let unit_owner_c = ctx.accounts.unit.components.get(&reference.owner).unwrap();
+        let unit_owner = ComponentOwner::try_from_slice(&unit_owner_c.data.as_slice()).unwrap();
+        if unit_owner.owner.unwrap() != ctx.accounts.payer.key() {
+            return err!(ComponentErrors::InvalidOwner)
+        }
+        check_turn(&ctx.accounts.instance_index, unit_owner.player)?;
+        let unit_active_c = ctx.accounts.unit.components.get(&reference.active).unwrap();
+        let unit_active = ComponentActive::try_from_slice(&unit_active_c.data.as_slice()).unwrap();
+        if unit_active.active == false {
+            return err!(ComponentErrors::UnitDead)
+        }
+        let unit_health_c = ctx.accounts.unit.components.get(&reference.health);
+        if unit_health_c.is_none() {
+            return err!(ComponentErrors::NoHealthComponent)
+        }
+        let mut unit_health = ComponentHealth::try_from_slice(&unit_health_c.unwrap().data.as_slice()).unwrap();
+
+        // Feature must be active and usable
+        let feature = &ctx.accounts.feature;
+        let feature_active_c = feature.components.get(&reference.active).unwrap();
+        let feature_active = ComponentActive::try_from_slice(&feature_active_c.data.as_slice()).unwrap();
+        if feature_active.active == false {
+            return err!(ComponentErrors::FeatureNotUsable)
+        }
+        let feature_healing_c = feature.components.get(&reference.healing_power);
+        let feature_rank_c = feature.components.get(&reference.feature_rank);
+        if feature_healing_c.is_none() || feature_rank_c.is_none() {
+            return err!(ComponentErrors::FeatureNotUsable)
+        }
+        let feature_healing = ComponentHealingPower::try_from_slice(&feature_healing_c.unwrap().data.as_slice()).unwrap();
+        let feature_rank = ComponentFeatureRank::try_from_slice(&feature_rank_c.unwrap().data.as_slice()).unwrap();
+
+        // Heal the unit, up to its max health. Nothing is spent if there's nothing to heal
+        let heals = feature_healing.heals.saturating_add(feature_rank.per_rank_stat_increase.saturating_mul(feature_rank.rank as u64));
+        let healed_health = unit_health.health.saturating_add(heals).min(unit_health.max_health).max(unit_health.health);
+        let healed = healed_health - unit_health.health;
+        if healed == 0 {
+            return err!(ComponentErrors::NothingToHeal)
+        }
+        unit_health.health = healed_health;
+
+        let mut feature_components: Vec<Pubkey> = vec![];
+        let mut feature_data: Vec<Vec<u8>> = vec![];
+
+        // Uses and LastUsed are optional on features; respect them when present
+        if let Some(uses_c) = feature.components.get(&reference.uses) {
+            let mut uses = ComponentUses::try_from_slice(&uses_c.data.as_slice()).unwrap();
+            if uses.uses_left == 0 {
+                return err!(ComponentErrors::NoUsesLeft)
+            }
+            uses.uses_left -= 1;
+            feature_components.push(reference.uses.key());
+            feature_data.push(uses.try_to_vec().unwrap());
         }
 
-        // Distance between From and To must be < Unit's Movement
-        let from_location_c = ctx.accounts.from.components.get(&reference.location).unwrap();
-        let from_location = ComponentLocation::try_from_slice(&from_location_c.data.as_slice()).unwrap();
+        if let Some(last_used_c) = feature.components.get(&reference.last_used) {
+            let clock = Clock::get().unwrap();
+            let mut last_used = ComponentLastUsed::try_from_slice(&last_used_c.data.as_slice()).unwrap();
+            if is_recovering(&ctx.accounts.instance_index, &last_used, clock.slot) {
+                return err!(ComponentErrors::FeatureRecovering)
+            }
+            last_used.last_used = get_pacing_mark(&ctx.accounts.instance_index, clock.slot);
+            feature_components.push(reference.last_used.key());
+            feature_data.push(last_used.try_to_vec().unwrap());
+        }
 
-        let to_location_c = ctx.accounts.to.components.get(&reference.location).unwrap();
-        let to_location = ComponentLocation::try_from_slice(&to_location_c.data.as_slice()).unwrap();
-        
-        let distance:f64 = (((to_location.x as f64 - from_location.x as f64).powf(2_f64) + (to_location.y as f64 - from_location.y as f64).powf(2_f64)) as f64).sqrt();
-        let unit_range_component = ctx.accounts.unit.components.get(&reference.range).unwrap();
-        let unit_range = ComponentRange::try_from_slice(&unit_range_component.data.as_slice()).unwrap();
-        if unit_range.movement < distance as u64 {
-            return err!(ComponentErrors::UnitLacksMovement)
+        // Charge the cost for the feature's current rank to the feature owner
+        let cost = *feature_rank.cost_for_use_ladder.get(feature_rank.rank as usize).unwrap_or(&0);
+        if cost > 0 {
+            let feature_owner_c = feature.components.get(&reference.owner).unwrap();
+            let feature_owner = ComponentOwner::try_from_slice(&feature_owner_c.data.as_slice()).unwrap();
+            if feature_owner.owner != Some(ctx.accounts.feature_owner.key()) {
+                return err!(ComponentErrors::InvalidOwner)
+            }
+            anchor_lang::system_program::transfer(CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.feature_owner.to_account_info(),
+                }
+            ), cost)?;
         }
 
         let system_signer_seeds:&[&[u8]] = &[
@@ -572,294 +1858,414 @@ pub mod dominarisystems {
         ];
         let signer_seeds = &[system_signer_seeds];
 
-        // Modify Unit's last_used & location
-        unit_last_used.last_used = clock.slot;
+        let mut modified_entities: Vec<AccountInfo> = vec![ctx.accounts.unit.to_account_info()];
+        let mut modifications: Vec<EntityModification> = vec![EntityModification {
+            components: vec![reference.health.key()],
+            data: vec![unit_health.try_to_vec().unwrap()],
+            expected_version: None
+        }];
+        if feature_components.len() > 0 {
+            modified_entities.push(ctx.accounts.feature.to_account_info());
+            modifications.push(EntityModification {
+                components: feature_components,
+                data: feature_data,
+                expected_version: None
+            });
+        }
 
-        let modify_unit_ctx = CpiContext::new_with_signer(
+        let modify_ctx = CpiContext::new_with_signer(
             ctx.accounts.world_program.to_account_info(),
-            dominariworld::cpi::accounts::ModifyComponent {
+            dominariworld::cpi::accounts::ModifyComponentsBatch {
                 world_config: ctx.accounts.world_config.to_account_info(),
-                entity: ctx.accounts.unit.to_account_info(),
                 system: ctx.accounts.system_signer.to_account_info(),
                 system_registration: ctx.accounts.system_registration.to_account_info(),
                 universe: ctx.accounts.universe.to_account_info(),
             },
             signer_seeds
-        );
-        dominariworld::cpi::req_modify_component(modify_unit_ctx, vec![
-                ctx.accounts.system_signer.components.last_used.key(),
-                ctx.accounts.system_signer.components.location.key(),    
-            ],
-            vec![
-                unit_last_used.try_to_vec().unwrap(),
-                to_location_c.data.clone()
-            ])?;
+        ).with_remaining_accounts(modified_entities);
+        dominariworld::cpi::req_modify_components_batch(modify_ctx, modifications)?;
 
-        // Modify From Occupant to be None
-        from_occupant.occupant_id = None;
-        let modify_from_ctx = CpiContext::new_with_signer(
+        emit!(FeatureUsed {
+            instance: ctx.accounts.world_instance.instance,
+            feature: ctx.accounts.feature.entity_id,
+            unit: ctx.accounts.unit.entity_id,
+            healed,
+            cost
+        });
+
+        Ok(())
+    }
+
+    /**
+     * Feature owner raises the feature's rank by one, up to max_rank.
+     */
+    pub fn upgrade_feature(ctx:Context<UpgradeFeature>) -> Result<()> {
+        let reference = &ctx.accounts.system_signer.components;
+        // Check if the game is paused or finished
+        ctx.accounts.instance_index.check_in_play()?;
+
+        let feature_owner_c = ctx.accounts.feature.components.get(&reference.owner).unwrap();
+        let feature_owner = ComponentOwner::try_from_slice(&feature_owner_c.data.as_slice()).unwrap();
+        if feature_owner.owner != Some(ctx.accounts.payer.key()) {
+            return err!(ComponentErrors::InvalidOwner)
+        }
+
+        let feature_rank_c = ctx.accounts.feature.components.get(&reference.feature_rank);
+        if feature_rank_c.is_none() {
+            return err!(ComponentErrors::FeatureNotUsable)
+        }
+        let mut feature_rank = ComponentFeatureRank::try_from_slice(&feature_rank_c.unwrap().data.as_slice()).unwrap();
+        if feature_rank.rank >= feature_rank.max_rank {
+            return err!(ComponentErrors::MaxRankReached)
+        }
+        feature_rank.rank += 1;
+
+        let system_signer_seeds:&[&[u8]] = &[
+            b"System_Signer",
+            &[*ctx.bumps.get("system_signer").unwrap()]
+        ];
+        let signer_seeds = &[system_signer_seeds];
+
+        let modify_feature_ctx = CpiContext::new_with_signer(
             ctx.accounts.world_program.to_account_info(),
             dominariworld::cpi::accounts::ModifyComponent {
                 world_config: ctx.accounts.world_config.to_account_info(),
-                entity: ctx.accounts.from.to_account_info(),
+                entity: ctx.accounts.feature.to_account_info(),
                 system: ctx.accounts.system_signer.to_account_info(),
                 system_registration: ctx.accounts.system_registration.to_account_info(),
                 universe: ctx.accounts.universe.to_account_info(),
             },
             signer_seeds
         );
-        dominariworld::cpi::req_modify_component(modify_from_ctx, vec![ctx.accounts.system_signer.components.occupant.key()], vec![from_occupant.try_to_vec().unwrap()])?;    
+        dominariworld::cpi::req_modify_component(modify_feature_ctx, vec![reference.feature_rank.key()], vec![feature_rank.try_to_vec().unwrap()], None)?;
 
-        // Modify To Occupant to be Unit
-        to_occupant.occupant_id = Some(ctx.accounts.unit.entity_id);
-        let modify_to_ctx = CpiContext::new_with_signer(
+        emit!(FeatureUpgraded {
+            instance: ctx.accounts.world_instance.instance,
+            feature: ctx.accounts.feature.entity_id,
+            rank: feature_rank.rank
+        });
+
+        Ok(())
+    }
+
+    /**
+     * Plays a mod card from the player's hand onto a unit they own.
+     * Components the unit already has are merged (see merge_mod_component), new ones are added to the unit.
+     */
+    pub fn play_mod(ctx:Context<PlayMod>) -> Result<()> {
+        let reference = &ctx.accounts.system_signer.components;
+        // Check if the game is paused or finished
+        ctx.accounts.instance_index.check_in_play()?;
+
+        // Check player belongs to payer
+        let player_stats_component = ctx.accounts.player.components.get(&reference.player_stats).unwrap();
+        let mut player_stats = ComponentPlayerStats::try_from_slice(&player_stats_component.data.as_slice()).unwrap();
+        if player_stats.key.key() != ctx.accounts.payer.key() {
+            return err!(ComponentErrors::InvalidOwner)
+        }
+
+        // Unit must be owned by the player and active
+        let unit_owner_component = ctx.accounts.unit.components.get(&reference.owner).unwrap();
+        let unit_owner = ComponentOwner::try_from_slice(&unit_owner_component.data.as_slice()).unwrap();
+        if unit_owner.player != Some(ctx.accounts.player.entity_id) {
+            return err!(ComponentErrors::InvalidOwner)
+        }
+        check_turn(&ctx.accounts.instance_index, unit_owner.player)?;
+        let unit_active_component = ctx.accounts.unit.components.get(&reference.active).unwrap();
+        let unit_active = ComponentActive::try_from_slice(&unit_active_component.data.as_slice()).unwrap();
+        if unit_active.active == false {
+            return err!(ComponentErrors::UnitDead)
+        }
+
+        // Only mod cards can be played onto units, unit cards would stack a whole second unit onto it
+        if !ctx.accounts.mod_blueprint.is_mod {
+            return err!(ComponentErrors::NotAMod)
+        }
+
+        // Check the Blueprint is in Player Hand and remove it
+        let card_idx = player_stats.cards.iter().position(|&card| card.key() == ctx.accounts.mod_blueprint.key());
+        if card_idx.is_none() {
+            return err!(ComponentErrors::InvalidCard)
+        }
+        player_stats.cards.swap_remove(card_idx.unwrap());
+
+        // Split the mod's components into ones to merge into the unit and ones to add to it
+        let mut modified_components: Vec<Pubkey> = vec![];
+        let mut modified_data: Vec<Vec<u8>> = vec![];
+        let mut new_components: Vec<(Pubkey, SerializedComponent)> = vec![];
+        for (key, modifier) in ctx.accounts.mod_blueprint.components.iter() {
+            if !is_moddable(key, reference) {
+                return err!(ComponentErrors::ProtectedComponent)
+            }
+            match ctx.accounts.unit.components.get(key) {
+                Some(existing) => {
+                    modified_components.push(key.key());
+                    modified_data.push(merge_mod_component(key, reference, &existing.data.as_slice(), &modifier.data.as_slice())?);
+                },
+                None => new_components.push((key.key(), modifier.clone()))
+            }
+        }
+
+        let system_signer_seeds:&[&[u8]] = &[
+            b"System_Signer",
+            &[*ctx.bumps.get("system_signer").unwrap()]
+        ];
+        let signer_seeds = &[system_signer_seeds];
+
+        let mut modified_entities: Vec<AccountInfo> = vec![ctx.accounts.player.to_account_info()];
+        let mut modifications: Vec<EntityModification> = vec![EntityModification {
+            components: vec![reference.player_stats.key()],
+            data: vec![player_stats.try_to_vec().unwrap()],
+            expected_version: None
+        }];
+        if modified_components.len() > 0 {
+            modified_entities.push(ctx.accounts.unit.to_account_info());
+            modifications.push(EntityModification {
+                components: modified_components,
+                data: modified_data,
+                expected_version: None
+            });
+        }
+
+        let modify_ctx = CpiContext::new_with_signer(
             ctx.accounts.world_program.to_account_info(),
-            dominariworld::cpi::accounts::ModifyComponent {
+            dominariworld::cpi::accounts::ModifyComponentsBatch {
                 world_config: ctx.accounts.world_config.to_account_info(),
-                entity: ctx.accounts.to.to_account_info(),
                 system: ctx.accounts.system_signer.to_account_info(),
                 system_registration: ctx.accounts.system_registration.to_account_info(),
                 universe: ctx.accounts.universe.to_account_info(),
             },
             signer_seeds
-        );
-        dominariworld::cpi::req_modify_component(modify_to_ctx, vec![ctx.accounts.system_signer.components.occupant.key()], vec![to_occupant.try_to_vec().unwrap()])?;
-        // Emit Troop Movement
-        emit!(TroopMovement {
+        ).with_remaining_accounts(modified_entities);
+        dominariworld::cpi::req_modify_components_batch(modify_ctx, modifications)?;
+
+        if new_components.len() > 0 {
+            let add_components_ctx = CpiContext::new_with_signer(
+                ctx.accounts.world_program.to_account_info(),
+                dominariworld::cpi::accounts::AddComponents {
+                    payer: ctx.accounts.payer.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    world_config: ctx.accounts.world_config.to_account_info(),
+                    entity: ctx.accounts.unit.to_account_info(),
+                    system: ctx.accounts.system_signer.to_account_info(),
+                    system_registration: ctx.accounts.system_registration.to_account_info(),
+                    universe: ctx.accounts.universe.to_account_info(),
+                },
+                signer_seeds
+            );
+            dominariworld::cpi::req_add_component(add_components_ctx, new_components, None)?;
+        }
+
+        emit!(ModPlayed {
             instance: ctx.accounts.world_instance.instance,
-            from: ctx.accounts.from.entity_id,
-            to: ctx.accounts.to.entity_id,
-            unit: ctx.accounts.unit.entity_id
+            player: ctx.accounts.player.entity_id,
+            unit: ctx.accounts.unit.entity_id,
+            blueprint: ctx.accounts.mod_blueprint.key()
         });
 
         Ok(())
     }
-    
-    pub fn attack_tile(ctx:Context<AttackTile>) -> Result<()> {
-        // Attacker could be Feature or Unit (just needs Damage Component)
-        let attacker = &ctx.accounts.attacker;
-        let defender = &ctx.accounts.defender;
-        let reference = &ctx.accounts.system_signer.components;
 
-        // Check if the game is paused
-        if ctx.accounts.instance_index.play_phase != PlayPhase::Play {
-            return err!(DominariError::GamePaused)
-        }
-        
-        // Check that attacker is owned by Payer
-        let attacker_owner_c = attacker.components.get(&reference.owner).unwrap();
-        let attacker_owner = ComponentOwner::try_from_slice(&attacker_owner_c.data.as_slice()).unwrap();
-        if attacker_owner.owner != Some(ctx.accounts.payer.key()) {
-            return err!(ComponentErrors::InvalidOwner)
-        }
-        
-        // Check that attacker is active
-        let attacker_active_c = attacker.components.get(&reference.active).unwrap();
-        let attacker_active = ComponentActive::try_from_slice(&attacker_active_c.data.as_slice()).unwrap();
-        if attacker_active.active == false {
-            return err!(ComponentErrors::UnitDead)
-        }
+    //pub fn build_feature(ctx:Context<BuildFeature>) -> Result<()> {}
 
-        // Check that defender is NOT owned by Payer
-        let defender_owner_c = defender.components.get(&reference.owner).unwrap();
-        let defender_owner = ComponentOwner::try_from_slice(&defender_owner_c.data.as_slice()).unwrap();
-        if defender_owner.player == attacker_owner.player {
-            return err!(ComponentErrors::FriendlyFire)
+    /**
+     * Pass in multiple entities through remaining accounts as (entity, original payer) pairs.
+     * Closes them if they are marked inactive, or if the game is finished, and prunes them from the Instance Index.
+     * Anyone can call this; the rent always goes back to whoever paid for the entity.
+     */
+    pub fn reclaim_entities<'info>(ctx:Context<'_, '_, '_, 'info, ReclaimEntities<'info>>) -> Result<()> {
+        let reference = &ctx.accounts.system_signer.components;
+        if ctx.remaining_accounts.len() == 0 || ctx.remaining_accounts.len() % 2 != 0 {
+            return err!(DominariError::InvalidEntity)
         }
 
-        // Check attacker has damage component
-        let attacker_damage_c = attacker.components.get(&reference.damage).unwrap();
-        let attacker_damage = ComponentDamage::try_from_slice(&attacker_damage_c.data.as_slice()).unwrap();
+        let game_finished = ctx.accounts.instance_index.play_phase == PlayPhase::Finished;
+        let mut reclaimed: Vec<u64> = vec![];
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let entity: Account<Entity> = Account::try_from(&pair[0])?;
+            if entity.instance != ctx.accounts.world_instance.instance {
+                return err!(DominariError::InvalidEntity)
+            }
 
-        // Check defender is active and has health component
-        let defender_active_c = defender.components.get(&reference.active).unwrap();
-        let mut defender_active = ComponentActive::try_from_slice(&defender_active_c.data.as_slice()).unwrap();
-        if defender_active.active == false {
-            return err!(ComponentErrors::UnitDead)
+            let inactive = match entity.components.get(&reference.active) {
+                Some(active_c) => !ComponentActive::try_from_slice(&active_c.data.as_slice()).unwrap().active,
+                None => false
+            };
+            if !game_finished && !inactive {
+                return err!(DominariError::EntityNotReclaimable)
+            }
+            reclaimed.push(entity.entity_id);
         }
-        let defender_health_c = defender.components.get(&reference.health);
-        if defender_health_c.is_none() {
-            return err!(ComponentErrors::NoHealthComponent)
+
+        // Prune the closed entities from the Instance Index
+        let index = &mut ctx.accounts.instance_index;
+        index.tiles.retain(|id| !reclaimed.contains(id));
+        index.features.retain(|id| !reclaimed.contains(id));
+        index.units.retain(|id| !reclaimed.contains(id));
+        index.players.retain(|id| !reclaimed.contains(id));
+        // Finished games keep their final standings around for claim_prize
+        if !game_finished {
+            index.standings.retain(|standing| !reclaimed.contains(&standing.player));
         }
-        let mut defender_health = ComponentHealth::try_from_slice(&defender_health_c.unwrap().data.as_slice()).unwrap();
 
-        // Defender must be in Range of Attacker
-        let attacker_location_c = attacker.components.get(&reference.location).unwrap();
-        let attacker_location = ComponentLocation::try_from_slice(&attacker_location_c.data.as_slice()).unwrap();
-        let defender_location_c = defender.components.get(&reference.location).unwrap();
-        let defender_location = ComponentLocation::try_from_slice(&defender_location_c.data.as_slice()).unwrap();
-        
-        let distance:f64 = (((defender_location.x as f64 - attacker_location.x as f64).powf(2_f64) + (defender_location.y as f64 - attacker_location.y as f64 ).powf(2_f64)) as f64).sqrt();
-        let attacker_range_c = attacker.components.get(&reference.range).unwrap();
-        let attacker_range = ComponentRange::try_from_slice(&attacker_range_c.data.as_slice()).unwrap();
-        if distance as u64 > attacker_range.attack_range {
-            return err!(ComponentErrors::OutOfRange)
+        let system_signer_seeds:&[&[u8]] = &[
+            b"System_Signer",
+            &[*ctx.bumps.get("system_signer").unwrap()]
+        ];
+        let signer_seeds = &[system_signer_seeds];
+
+        let close_ctx = CpiContext::new_with_signer(
+            ctx.accounts.world_program.to_account_info(),
+            dominariworld::cpi::accounts::CloseEntities {
+                world_config: ctx.accounts.world_config.to_account_info(),
+                system: ctx.accounts.system_signer.to_account_info(),
+                system_registration: ctx.accounts.system_registration.to_account_info(),
+                universe: ctx.accounts.universe.to_account_info(),
+            },
+            signer_seeds
+        ).with_remaining_accounts(ctx.remaining_accounts.to_vec());
+        dominariworld::cpi::req_close_entities(close_ctx)?;
+
+        emit!(EntitiesReclaimed {
+            instance: ctx.accounts.world_instance.instance,
+            entities: reclaimed
+        });
+
+        Ok(())
+    }
+
+    /**
+     * Mints a 1-of-1 token for a unit or feature the payer owns, so it can be traded off chain.
+     * Whoever holds the token can then take the entity over with claim_entity.
+     */
+    pub fn tokenize_entity(ctx:Context<TokenizeEntity>) -> Result<()> {
+        let reference = &ctx.accounts.system_signer.components;
+
+        let metadata_c = ctx.accounts.entity.components.get(&reference.metadata).unwrap();
+        let metadata = ComponentMetadata::try_from_slice(&metadata_c.data.as_slice()).unwrap();
+        match metadata.entity_type {
+            EntityType::Unit | EntityType::Feature => {},
+            _ => return err!(ComponentErrors::NotTokenizable)
         }
 
-        // Check attacker last used isn't violated
-        let clock = Clock::get().unwrap();
-        let attacker_last_used_c = attacker.components.get(&reference.last_used).unwrap();
-        let mut attacker_last_used = ComponentLastUsed::try_from_slice(&attacker_last_used_c.data.as_slice()).unwrap();
-        if attacker_last_used.last_used != 0 && (attacker_last_used.last_used + attacker_last_used.recovery) >= clock.slot {
-            return err!(ComponentErrors::UnitRecovering)
+        let owner_c = ctx.accounts.entity.components.get(&reference.owner).unwrap();
+        let owner = ComponentOwner::try_from_slice(&owner_c.data.as_slice()).unwrap();
+        if owner.owner != Some(ctx.accounts.payer.key()) {
+            return err!(ComponentErrors::InvalidOwner)
         }
-        attacker_last_used.last_used = clock.slot;        
-        
+
         let system_signer_seeds:&[&[u8]] = &[
             b"System_Signer",
             &[*ctx.bumps.get("system_signer").unwrap()]
         ];
         let signer_seeds = &[system_signer_seeds];
 
-        // Modify attacker last used
-        let modify_attacker_ctx = CpiContext::new_with_signer(
+        let tokenize_ctx = CpiContext::new_with_signer(
             ctx.accounts.world_program.to_account_info(),
-            dominariworld::cpi::accounts::ModifyComponent {
+            dominariworld::cpi::accounts::TokenizeEntity {
+                payer: ctx.accounts.payer.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
                 world_config: ctx.accounts.world_config.to_account_info(),
-                entity: ctx.accounts.attacker.to_account_info(),
+                entity: ctx.accounts.entity.to_account_info(),
+                entity_nft: ctx.accounts.entity_nft.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                owner: ctx.accounts.payer.to_account_info(),
+                owner_ata: ctx.accounts.owner_ata.to_account_info(),
                 system: ctx.accounts.system_signer.to_account_info(),
                 system_registration: ctx.accounts.system_registration.to_account_info(),
                 universe: ctx.accounts.universe.to_account_info(),
             },
             signer_seeds
         );
-        dominariworld::cpi::req_modify_component(modify_attacker_ctx, vec![
-                reference.last_used.key(),
-            ],
-            vec![
-                attacker_last_used.try_to_vec().unwrap(),
-            ])?;
-
-        // Roll Damage for Attacker, apply modifiers 
-        let mut dmg = get_random_u64(attacker_damage.max_damage); 
-        
-        // check if defender is Feature, if not, look for it's TroopClass
-        let defender_metadata_c = defender.components.get(&reference.metadata).unwrap();
-        let defender_metadata = ComponentMetadata::try_from_slice(&defender_metadata_c.data.as_slice()).unwrap();
+        dominariworld::cpi::req_tokenize_entity(tokenize_ctx)?;
+        Ok(())
+    }
 
-        if defender_metadata.entity_type == EntityType::Feature {
-            dmg += attacker_damage.bonus_feature as u64;
-        } else {
-            let defender_troop_class_c = defender.components.get(&reference.troop_class).unwrap();
-            let defender_troop_class = ComponentTroopClass::try_from_slice(&defender_troop_class_c.data.as_slice()).unwrap();
-            match defender_troop_class.class {
-                TroopClass::Aircraft => dmg += attacker_damage.bonus_aircraft as u64,
-                TroopClass::Infantry => dmg += attacker_damage.bonus_infantry as u64,
-                TroopClass::Armor => dmg += attacker_damage.bonus_armor as u64,
-            }
+    /**
+     * Hands a tokenized entity over to whoever holds its token, for one of their players.
+     * Every other system checks the Owner component, so once claimed the holder controls the entity as usual.
+     */
+    pub fn claim_entity(ctx:Context<ClaimEntity>) -> Result<()> {
+        let reference = &ctx.accounts.system_signer.components;
+        if ctx.accounts.instance_index.play_phase == PlayPhase::Finished {
+            return err!(DominariError::GameFinished)
         }
 
-        if dmg < attacker_damage.min_damage {
-            dmg = attacker_damage.min_damage;
+        if !ctx.accounts.entity_nft.is_held_by(&ctx.accounts.holder_ata, &ctx.accounts.payer.key()) {
+            return err!(ComponentErrors::NotTokenHolder)
         }
 
-        if dmg >= defender_health.health {
-            defender_health.health = 0;
-            defender_active.active = false;
+        // Player must belong to payer
+        let player_stats_c = ctx.accounts.player.components.get(&reference.player_stats).ok_or(DominariError::InvalidPlayer)?;
+        let player_stats = ComponentPlayerStats::try_from_slice(&player_stats_c.data.as_slice()).unwrap();
+        if player_stats.key.key() != ctx.accounts.payer.key() {
+            return err!(ComponentErrors::InvalidOwner)
+        }
 
-            // Modify the defending tile to remove the defender
-            let defending_tile = &ctx.accounts.defending_tile;
-            // Require Defender Location and Defending Tile Location are the same
-            let defending_tile_loc_c = defending_tile.components.get(&reference.location).unwrap();
-            let defending_tile_loc = ComponentLocation::try_from_slice(&defending_tile_loc_c.data.as_slice()).unwrap();
-            if defending_tile_loc.x != defender_location.x && defending_tile_loc.y != defender_location.y {
-                return err!(ComponentErrors::InvalidLocation)
+        let owner_c = ctx.accounts.entity.components.get(&reference.owner).unwrap();
+        let mut owner = ComponentOwner::try_from_slice(&owner_c.data.as_slice()).unwrap();
+
+        // Active units and features count towards their player's standing, so the count moves with them
+        let entity_id = ctx.accounts.entity.entity_id;
+        let active = match ctx.accounts.entity.components.get(&reference.active) {
+            Some(active_c) => ComponentActive::try_from_slice(&active_c.data.as_slice()).unwrap().active,
+            None => false
+        };
+        let is_unit = ctx.accounts.instance_index.units.contains(&entity_id);
+        let is_feature = ctx.accounts.instance_index.features.contains(&entity_id);
+        if let Some(previous) = owner.player {
+            if let Some(standing) = get_standing_mut(&mut ctx.accounts.instance_index, previous) {
+                if is_unit && active {
+                    standing.active_units = standing.active_units.saturating_sub(1);
+                }
+                if is_feature {
+                    standing.features = standing.features.saturating_sub(1);
+                }
             }
-
-            if defender_metadata.entity_type == EntityType::Feature {
-                let tile_feature_c = defending_tile.components.get(&reference.feature).unwrap();
-                let mut tile_feature = ComponentFeature::try_from_slice(&tile_feature_c.data.as_slice()).unwrap();
-                tile_feature.feature_id = None;
-                let modify_tile_ctx = CpiContext::new_with_signer(
-                    ctx.accounts.world_program.to_account_info(),
-                    dominariworld::cpi::accounts::ModifyComponent {
-                        world_config: ctx.accounts.world_config.to_account_info(),
-                        entity: ctx.accounts.defending_tile.to_account_info(),
-                        system: ctx.accounts.system_signer.to_account_info(),
-                        system_registration: ctx.accounts.system_registration.to_account_info(),
-                        universe: ctx.accounts.universe.to_account_info(),
-                    },
-                    signer_seeds
-                );
-                dominariworld::cpi::req_modify_component(modify_tile_ctx, vec![
-                        reference.feature.key(),
-                    ],
-                    vec![
-                        tile_feature.try_to_vec().unwrap(),
-                    ])?;
-            } else {
-                let tile_occupant_c = defending_tile.components.get(&reference.occupant).unwrap();
-                let mut tile_occupant = ComponentOccupant::try_from_slice(&tile_occupant_c.data.as_slice()).unwrap();
-                tile_occupant.occupant_id = None;
-                let modify_tile_ctx = CpiContext::new_with_signer(
-                    ctx.accounts.world_program.to_account_info(),
-                    dominariworld::cpi::accounts::ModifyComponent {
-                        world_config: ctx.accounts.world_config.to_account_info(),
-                        entity: ctx.accounts.defending_tile.to_account_info(),
-                        system: ctx.accounts.system_signer.to_account_info(),
-                        system_registration: ctx.accounts.system_registration.to_account_info(),
-                        universe: ctx.accounts.universe.to_account_info(),
-                    },
-                    signer_seeds
-                );
-                dominariworld::cpi::req_modify_component(modify_tile_ctx, vec![
-                        reference.occupant.key(),
-                    ],
-                    vec![
-                        tile_occupant.try_to_vec().unwrap(),
-                    ])?;
+        }
+        if let Some(standing) = get_standing_mut(&mut ctx.accounts.instance_index, ctx.accounts.player.entity_id) {
+            if is_unit && active {
+                standing.active_units += 1;
+                standing.has_deployed = true;
+            }
+            if is_feature {
+                standing.features += 1;
             }
-        } else {
-            defender_health.health -= dmg;
         }
 
-        // Modify defender health
-            // If defender health at 0, Modify active as well
-        let modify_defender_ctx = CpiContext::new_with_signer(
+        owner.owner = Some(ctx.accounts.payer.key());
+        owner.player = Some(ctx.accounts.player.entity_id);
+
+        let system_signer_seeds:&[&[u8]] = &[
+            b"System_Signer",
+            &[*ctx.bumps.get("system_signer").unwrap()]
+        ];
+        let signer_seeds = &[system_signer_seeds];
+
+        let modify_entity_ctx = CpiContext::new_with_signer(
             ctx.accounts.world_program.to_account_info(),
             dominariworld::cpi::accounts::ModifyComponent {
                 world_config: ctx.accounts.world_config.to_account_info(),
-                entity: ctx.accounts.defender.to_account_info(),
+                entity: ctx.accounts.entity.to_account_info(),
                 system: ctx.accounts.system_signer.to_account_info(),
                 system_registration: ctx.accounts.system_registration.to_account_info(),
                 universe: ctx.accounts.universe.to_account_info(),
             },
             signer_seeds
         );
-        dominariworld::cpi::req_modify_component(modify_defender_ctx, vec![
-                reference.health.key(),
-                reference.active.key(),
-            ],
-            vec![
-                defender_health.try_to_vec().unwrap(),
-                defender_active.try_to_vec().unwrap()
-            ])?;
-    
+        dominariworld::cpi::req_modify_component(modify_entity_ctx, vec![reference.owner.key()], vec![owner.try_to_vec().unwrap()], None)?;
 
-        emit!(TileAttacked{
+        emit!(EntityOwnerClaimed {
             instance: ctx.accounts.world_instance.instance,
-            attacker: attacker.entity_id,
-            defender: defender.entity_id,
-            defending_tile: ctx.accounts.defending_tile.entity_id,
-            damage: dmg
+            entity: entity_id,
+            owner: ctx.accounts.payer.key(),
+            player: ctx.accounts.player.entity_id
         });
 
         Ok(())
     }
-    //pub fn modify_unit(ctx:Context<ModUnit>) -> Result<()> {}
-
-    //pub fn build_feature(ctx:Context<BuildFeature>) -> Result<()> {}
-    //pub fn use_[feature](ctx:Context<UseFeature>) -> Result<()> {}
-
-    // Pass in multiple entities through remaining accounts; will iterate and remove them if they are marked inactive
-    //pub fn reclaim_entity_sol(ctx:Context<ReclaimSol>) -> Result<()> {}
 
 }
-
-pub fn get_random_u64(max: u64) -> u64 {
-    let clock = Clock::get().unwrap();
-    let slice = &hash(&clock.slot.to_be_bytes()).to_bytes()[0..8];
-    let num: u64 = u64::from_be_bytes(slice.try_into().unwrap());
-    let target = num/(u64::MAX/max);
-    return target;
-}
\ No newline at end of file
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use ecs::account::Entity;
+
+use crate::component::*;
+use crate::error::ComponentErrors;
+use crate::state::RelevantComponentKeys;
+
+/**
+ * Movement spent stepping onto a tile: 1 plus whatever its terrain charges the unit's class.
+ * Fails if the terrain is impassable for the class.
+ */
+pub fn get_step_cost(reference: &RelevantComponentKeys, tile: &Entity, class: &TroopClass) -> Result<u64> {
+    let terrain_c = match tile.components.get(&reference.terrain) {
+        Some(terrain_c) => terrain_c,
+        None => return Ok(1)
+    };
+    let terrain = ComponentTerrain::try_from_slice(&terrain_c.data.as_slice()).unwrap();
+    match terrain.get_move_cost(class) {
+        Some(cost) => Ok(1 + cost),
+        None => err!(ComponentErrors::ImpassableTerrain)
+    }
+}
+
+/// Same as get_step_cost for a tile packed in a Region, which only stores its terrain type
+pub fn get_region_step_cost(tile: &RegionTile, class: &TroopClass) -> Result<u64> {
+    let terrain = match &tile.terrain {
+        Some(terrain) => ComponentTerrain::from_type(terrain.clone()),
+        None => return Ok(1)
+    };
+    match terrain.get_move_cost(class) {
+        Some(cost) => Ok(1 + cost),
+        None => err!(ComponentErrors::ImpassableTerrain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use super::*;
+
+    fn keys() -> RelevantComponentKeys {
+        RelevantComponentKeys::from_schema_urls(|_| Pubkey::new_unique())
+    }
+
+    fn tile(keys: &RelevantComponentKeys, terrain: Option<TerrainType>) -> Entity {
+        let mut components = BTreeMap::new();
+        if let Some(terrain) = terrain {
+            components.insert(keys.terrain, ComponentTerrain::from_type(terrain).to_serialized_component());
+        }
+        Entity {
+            entity_id: 1,
+            instance: 0,
+            world: Pubkey::default(),
+            world_signer: Pubkey::default(),
+            payer: Pubkey::default(),
+            version: 0,
+            components,
+        }
+    }
+
+    #[test]
+    fn tiles_without_terrain_cost_one_step() {
+        let keys = keys();
+        assert_eq!(get_step_cost(&keys, &tile(&keys, None), &TroopClass::Armor).unwrap(), 1);
+        assert_eq!(get_step_cost(&keys, &tile(&keys, Some(TerrainType::Plains)), &TroopClass::Armor).unwrap(), 1);
+    }
+
+    #[test]
+    fn terrain_adds_its_move_cost_for_the_class() {
+        let keys = keys();
+        let forest = tile(&keys, Some(TerrainType::Forest));
+        assert_eq!(get_step_cost(&keys, &forest, &TroopClass::Infantry).unwrap(), 2);
+        assert_eq!(get_step_cost(&keys, &forest, &TroopClass::Armor).unwrap(), 3);
+        assert_eq!(get_step_cost(&keys, &forest, &TroopClass::Aircraft).unwrap(), 1);
+    }
+
+    #[test]
+    fn impassable_terrain_fails() {
+        let keys = keys();
+        let water = tile(&keys, Some(TerrainType::Water));
+        assert_eq!(get_step_cost(&keys, &water, &TroopClass::Infantry).unwrap_err(), error!(ComponentErrors::ImpassableTerrain));
+        assert_eq!(get_step_cost(&keys, &water, &TroopClass::Aircraft).unwrap(), 1);
+        let mountain = tile(&keys, Some(TerrainType::Mountain));
+        assert_eq!(get_step_cost(&keys, &mountain, &TroopClass::Armor).unwrap_err(), error!(ComponentErrors::ImpassableTerrain));
+    }
+
+    #[test]
+    fn region_tiles_cost_the_same_as_tile_entities() {
+        let keys = keys();
+        for terrain in [None, Some(TerrainType::Plains), Some(TerrainType::Forest), Some(TerrainType::Mountain), Some(TerrainType::Water)] {
+            let region_tile = RegionTile { terrain: terrain.clone(), feature_id: None, occupant_id: None };
+            for class in [TroopClass::Infantry, TroopClass::Armor, TroopClass::Aircraft] {
+                assert_eq!(
+                    get_region_step_cost(&region_tile, &class).ok(),
+                    get_step_cost(&keys, &tile(&keys, terrain.clone()), &class).ok()
+                );
+            }
+        }
+    }
+}
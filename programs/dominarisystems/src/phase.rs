@@ -0,0 +1,143 @@
+use anchor_lang::prelude::*;
+
+use crate::account::{InstanceIndex, PlayPhase};
+use crate::error::DominariError;
+
+/**
+ * Who is asking for a phase change.
+ * Authority is the World's Instance Authority (or its signer set), Player is anyone in the instance's player list.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum PhaseRole {
+    Authority,
+    Player,
+}
+
+/**
+ * Transition table: Lobby -> Build -> Play <-> Paused -> Finished
+ * Players can only toggle Play and Paused (by vote if the game has a pause quorum),
+ * everything else belongs to the instance authority.
+ */
+pub fn check_transition(from: &PlayPhase, to: &PlayPhase, role: &PhaseRole) -> Result<()> {
+    let player_allowed = match (from, to) {
+        (PlayPhase::Lobby, PlayPhase::Build) => false,
+        (PlayPhase::Build, PlayPhase::Play) => false,
+        (PlayPhase::Play, PlayPhase::Paused) => true,
+        (PlayPhase::Paused, PlayPhase::Play) => true,
+        (PlayPhase::Play, PlayPhase::Finished) => false,
+        (PlayPhase::Paused, PlayPhase::Finished) => false,
+        _ => return err!(DominariError::InvalidPhaseTransition)
+    };
+
+    if *role == PhaseRole::Player && !player_allowed {
+        return err!(DominariError::InvalidPlayPhase)
+    }
+    Ok(())
+}
+
+/**
+ * Number of player votes needed to pause or resume.
+ * Capped at the number of players so a quorum can never lock the game.
+ */
+pub fn get_quorum(index: &InstanceIndex) -> u16 {
+    match index.config.pause_quorum {
+        Some(quorum) => quorum.min(index.players.len() as u16).max(1),
+        None => 1
+    }
+}
+
+/**
+ * Moves the instance to the new phase and clears any pending votes.
+ * Turn based games start on the first player's turn the first time they're put in Play.
+ * Returns the phase the game moved out of.
+ */
+pub fn set_phase(index: &mut InstanceIndex, to: PlayPhase, slot: u64) -> PlayPhase {
+    let from = index.play_phase.clone();
+    index.play_phase = to;
+    index.phase_votes.clear();
+
+    if index.config.turns.is_some() && index.play_phase == PlayPhase::Play && index.turn_number == 0 && index.players.len() > 0 {
+        index.turn_number = 1;
+        index.active_player = index.players[0];
+        index.turn_start = slot;
+    }
+    from
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::GridMetric;
+    use crate::state::{GameConfig, RandomnessMode, VictoryConditions};
+
+    fn index(players: Vec<u64>, pause_quorum: Option<u16>) -> InstanceIndex {
+        InstanceIndex {
+            config: GameConfig {
+                max_players: 4,
+                starting_cards: vec![],
+                randomness: RandomnessMode::SlotHashes,
+                turns: None,
+                victory: VictoryConditions {
+                    last_player_standing: true,
+                    score_threshold: None,
+                    features_to_control: None,
+                    deadline_slot: None,
+                },
+                pause_quorum,
+                entry_fee: None,
+                grid_metric: GridMetric::Manhattan,
+            },
+            map: 0,
+            tiles: vec![],
+            features: vec![],
+            units: vec![],
+            players,
+            play_phase: PlayPhase::Play,
+            turn_number: 0,
+            active_player: 0,
+            turn_start: 0,
+            standings: vec![],
+            winner: None,
+            phase_votes: vec![],
+            features_built: 0,
+        }
+    }
+
+    #[test]
+    fn lobby_cant_skip_to_play() {
+        assert_eq!(
+            check_transition(&PlayPhase::Lobby, &PlayPhase::Play, &PhaseRole::Authority).unwrap_err(),
+            error!(DominariError::InvalidPhaseTransition)
+        );
+        assert!(check_transition(&PlayPhase::Lobby, &PlayPhase::Build, &PhaseRole::Authority).is_ok());
+    }
+
+    #[test]
+    fn players_can_only_pause_and_resume() {
+        assert_eq!(
+            check_transition(&PlayPhase::Play, &PlayPhase::Finished, &PhaseRole::Player).unwrap_err(),
+            error!(DominariError::InvalidPlayPhase)
+        );
+        assert!(check_transition(&PlayPhase::Play, &PlayPhase::Finished, &PhaseRole::Authority).is_ok());
+        assert!(check_transition(&PlayPhase::Play, &PlayPhase::Paused, &PhaseRole::Player).is_ok());
+        assert!(check_transition(&PlayPhase::Paused, &PlayPhase::Play, &PhaseRole::Player).is_ok());
+    }
+
+    #[test]
+    fn quorum_is_capped_at_player_count() {
+        assert_eq!(get_quorum(&index(vec![1, 2], Some(5))), 2);
+        assert_eq!(get_quorum(&index(vec![1, 2, 3], Some(2))), 2);
+        assert_eq!(get_quorum(&index(vec![1, 2, 3], None)), 1);
+        assert_eq!(get_quorum(&index(vec![], Some(3))), 1);
+    }
+
+    #[test]
+    fn phase_change_clears_votes() {
+        let mut index = index(vec![1, 2, 3], Some(2));
+        index.phase_votes = vec![1, 2];
+        let from = set_phase(&mut index, PlayPhase::Paused, 10);
+        assert_eq!(from, PlayPhase::Play);
+        assert_eq!(index.play_phase, PlayPhase::Paused);
+        assert!(index.phase_votes.is_empty());
+    }
+}
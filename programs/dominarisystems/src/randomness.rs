@@ -0,0 +1,164 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::error::DominariError;
+
+/**
+ * Reads the most recent entry of the SlotHashes sysvar.
+ * The sysvar is too large to deserialize on chain, so we read the raw bytes:
+ * u64 length prefix followed by (slot: u64, hash: [u8; 32]) entries, newest first.
+ */
+pub fn get_recent_slot_hash(slot_hashes: &AccountInfo) -> Result<[u8; 32]> {
+    let data = slot_hashes.try_borrow_data()?;
+    if data.len() < 8 + 8 + 32 {
+        return err!(DominariError::InvalidSlotHashes)
+    }
+    let mut recent = [0u8; 32];
+    recent.copy_from_slice(&data[16..48]);
+    Ok(recent)
+}
+
+/**
+ * Looks up the hash of one exact slot in the raw SlotHashes data.
+ * None if the slot was skipped or has already aged out of the sysvar (it only keeps the last 512 slots).
+ */
+pub fn find_slot_hash(data: &[u8], slot: u64) -> Result<Option<[u8; 32]>> {
+    if data.len() < 8 {
+        return err!(DominariError::InvalidSlotHashes)
+    }
+    let len = u64::from_le_bytes(data[0..8].try_into().unwrap()) as usize;
+    for idx in 0..len {
+        let start = 8 + idx * 40;
+        if data.len() < start + 40 {
+            return err!(DominariError::InvalidSlotHashes)
+        }
+        let entry_slot = u64::from_le_bytes(data[start..start+8].try_into().unwrap());
+        if entry_slot == slot {
+            let mut slot_hash = [0u8; 32];
+            slot_hash.copy_from_slice(&data[start+8..start+40]);
+            return Ok(Some(slot_hash))
+        }
+        // Entries are newest first, so once we're past the slot it isn't there
+        if entry_slot < slot {
+            break;
+        }
+    }
+    Ok(None)
+}
+
+pub fn get_slot_hash(slot_hashes: &AccountInfo, slot: u64) -> Result<Option<[u8; 32]>> {
+    let data = slot_hashes.try_borrow_data()?;
+    find_slot_hash(&data, slot)
+}
+
+/**
+ * Slot whose hash a commitment made in commit_slot gets mixed with.
+ * It's in the future at commit time, so neither side knows it, and fixed, so the reveal can't be timed to a favourable hash.
+ */
+pub fn get_target_slot(commit_slot: u64) -> u64 {
+    commit_slot + 1
+}
+
+/**
+ * What a player commits to before revealing their seed in the attack.
+ * Binding the defender in means the roll can't be tried against other targets once the target slot hash is public.
+ */
+pub fn get_commitment(seed: &[u8; 32], defender: &Pubkey) -> [u8; 32] {
+    hashv(&[seed, defender.as_ref()]).to_bytes()
+}
+
+/**
+ * Mixes the slot hash with the attacker & defender entity addresses (and the revealed seed in commit-reveal mode)
+ * so that two attacks landing in the same slot don't roll identical damage.
+ */
+pub fn derive_entropy(slot_hash: &[u8; 32], attacker: &Pubkey, defender: &Pubkey, seed: Option<&[u8; 32]>) -> [u8; 32] {
+    let seed_bytes: &[u8] = match seed {
+        Some(s) => &s[..],
+        None => &[],
+    };
+    hashv(&[slot_hash, attacker.as_ref(), defender.as_ref(), seed_bytes]).to_bytes()
+}
+
+/// Returns a number in [0, max]
+pub fn get_random_u64(entropy: &[u8; 32], max: u64) -> u64 {
+    if max == 0 {
+        return 0;
+    }
+    let num: u64 = u64::from_be_bytes(entropy[0..8].try_into().unwrap());
+    return num % max.saturating_add(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolls_differ_within_a_slot() {
+        let slot_hash = [7u8; 32];
+        let defender = Pubkey::new_unique();
+        let rolls: Vec<u64> = (0..10).map(|_| {
+            let attacker = Pubkey::new_unique();
+            get_random_u64(&derive_entropy(&slot_hash, &attacker, &defender, None), 1_000_000)
+        }).collect();
+        assert!(rolls.iter().any(|roll| *roll != rolls[0]));
+    }
+
+    #[test]
+    fn revealed_seed_changes_roll() {
+        let slot_hash = [7u8; 32];
+        let attacker = Pubkey::new_unique();
+        let defender = Pubkey::new_unique();
+        let a = derive_entropy(&slot_hash, &attacker, &defender, Some(&[1u8; 32]));
+        let b = derive_entropy(&slot_hash, &attacker, &defender, Some(&[2u8; 32]));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn commitment_binds_the_defender() {
+        let seed = [1u8; 32];
+        let defender = Pubkey::new_unique();
+        assert_eq!(get_commitment(&seed, &defender), get_commitment(&seed, &defender));
+        assert_ne!(get_commitment(&seed, &defender), get_commitment(&seed, &Pubkey::new_unique()));
+        assert_ne!(get_commitment(&seed, &defender), get_commitment(&[2u8; 32], &defender));
+    }
+
+    fn slot_hashes_data(newest_slot: u64, count: u64) -> Vec<u8> {
+        let mut data = count.to_le_bytes().to_vec();
+        for slot in (newest_slot + 1 - count..=newest_slot).rev() {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(&[(slot % 256) as u8; 32]);
+        }
+        data
+    }
+
+    #[test]
+    fn seed_rolls_the_same_whenever_revealed() {
+        let seed = [3u8; 32];
+        let attacker = Pubkey::new_unique();
+        let defender = Pubkey::new_unique();
+        let target_slot = get_target_slot(100);
+
+        let rolls: Vec<u64> = (102..110).map(|newest_slot| {
+            let data = slot_hashes_data(newest_slot, 16);
+            let slot_hash = find_slot_hash(&data, target_slot).unwrap().unwrap();
+            get_random_u64(&derive_entropy(&slot_hash, &attacker, &defender, Some(&seed)), 1_000_000)
+        }).collect();
+        assert!(rolls.iter().all(|roll| *roll == rolls[0]));
+    }
+
+    #[test]
+    fn target_slot_missing_once_aged_out() {
+        let target_slot = get_target_slot(100);
+        // Not produced yet
+        assert_eq!(find_slot_hash(&slot_hashes_data(100, 8), target_slot).unwrap(), None);
+        // Aged out of the sysvar
+        assert_eq!(find_slot_hash(&slot_hashes_data(200, 8), target_slot).unwrap(), None);
+        assert!(find_slot_hash(&[0u8; 4], target_slot).is_err());
+    }
+
+    #[test]
+    fn zero_max_damage_rolls_zero() {
+        assert_eq!(get_random_u64(&[255u8; 32], 0), 0);
+        assert!(get_random_u64(&[255u8; 32], 10) <= 10);
+    }
+}
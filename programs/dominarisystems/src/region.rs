@@ -0,0 +1,255 @@
+use anchor_lang::prelude::*;
+use ecs::account::Entity;
+use ecs::state::EntityModification;
+
+use crate::component::*;
+use crate::constant::REGION_SIZE;
+use crate::error::ComponentErrors;
+use crate::state::RelevantComponentKeys;
+
+// High bits tag Region ids so they're unlikely to collide with randomly generated entity ids
+pub const REGION_ID_PREFIX: u64 = 0x5245_4749_0000_0000;
+
+/**
+ * Region entities are found by coordinates rather than through the Instance Index,
+ * so their entity id is derived from the region's position on the region grid.
+ */
+pub fn get_region_id(region_x: u16, region_y: u16) -> u64 {
+    REGION_ID_PREFIX | ((region_x as u64) << 16) | region_y as u64
+}
+
+/// Region grid coordinates of the region holding a location
+pub fn get_region_coords(location: &ComponentLocation) -> (u16, u16) {
+    (location.x / REGION_SIZE, location.y / REGION_SIZE)
+}
+
+/// Entity id of the region holding a location
+pub fn get_region_id_for(location: &ComponentLocation) -> u64 {
+    let (region_x, region_y) = get_region_coords(location);
+    get_region_id(region_x, region_y)
+}
+
+/// Index of the region in the list that holds a location on the map
+pub fn find_region(regions: &[ComponentRegion], location: &ComponentLocation) -> Option<usize> {
+    regions.iter().position(|region| region.get_tile(location).is_some())
+}
+
+/// Region grid coordinates of every region needed to cover the map
+pub fn get_map_regions(mapmeta: &ComponentMapMeta) -> Vec<(u16, u16)> {
+    let regions_x = (mapmeta.max_x as u32 + REGION_SIZE as u32 - 1) / REGION_SIZE as u32;
+    let regions_y = (mapmeta.max_y as u32 + REGION_SIZE as u32 - 1) / REGION_SIZE as u32;
+    let mut regions = vec![];
+    for region_x in 0..regions_x {
+        for region_y in 0..regions_y {
+            regions.push((region_x as u16, region_y as u16));
+        }
+    }
+    regions
+}
+
+/**
+ * A map tile the way actions see it, whether it's its own Entity or a cell in a Region entity.
+ * Lets attacks and features work the same on chunked and unchunked maps.
+ */
+pub enum TileView {
+    Tile {
+        terrain: Option<ComponentTerrain>,
+        feature: ComponentFeature,
+        occupant: ComponentOccupant,
+    },
+    Region {
+        location: ComponentLocation,
+        region: ComponentRegion,
+    },
+}
+
+impl TileView {
+    /// Loads the tile at location from tile, which is either that tile's Entity or the Region holding it
+    pub fn load(reference: &RelevantComponentKeys, tile: &Entity, location: &ComponentLocation) -> Result<TileView> {
+        if let Some(region_c) = tile.components.get(&reference.region) {
+            let region = ComponentRegion::try_from_slice(&region_c.data.as_slice()).unwrap();
+            if region.get_tile(location).is_none() {
+                return err!(ComponentErrors::InvalidLocation)
+            }
+            return Ok(TileView::Region { location: location.clone(), region })
+        }
+
+        let tile_location_c = tile.components.get(&reference.location).unwrap();
+        let tile_location = ComponentLocation::try_from_slice(&tile_location_c.data.as_slice()).unwrap();
+        if tile_location.x != location.x || tile_location.y != location.y {
+            return err!(ComponentErrors::InvalidLocation)
+        }
+        let terrain = tile.components.get(&reference.terrain).map(|terrain_c| ComponentTerrain::try_from_slice(&terrain_c.data.as_slice()).unwrap());
+        let feature_c = tile.components.get(&reference.feature).unwrap();
+        let occupant_c = tile.components.get(&reference.occupant).unwrap();
+        Ok(TileView::Tile {
+            terrain,
+            feature: ComponentFeature::try_from_slice(&feature_c.data.as_slice()).unwrap(),
+            occupant: ComponentOccupant::try_from_slice(&occupant_c.data.as_slice()).unwrap(),
+        })
+    }
+
+    pub fn terrain(&self) -> Option<ComponentTerrain> {
+        match self {
+            TileView::Tile { terrain, .. } => terrain.clone(),
+            TileView::Region { location, region } => region.get_tile(location).unwrap().terrain.clone().map(ComponentTerrain::from_type),
+        }
+    }
+
+    pub fn feature_id(&self) -> Option<u64> {
+        match self {
+            TileView::Tile { feature, .. } => feature.feature_id,
+            TileView::Region { location, region } => region.get_tile(location).unwrap().feature_id,
+        }
+    }
+
+    pub fn occupant_id(&self) -> Option<u64> {
+        match self {
+            TileView::Tile { occupant, .. } => occupant.occupant_id,
+            TileView::Region { location, region } => region.get_tile(location).unwrap().occupant_id,
+        }
+    }
+
+    /// Removes the feature from the tile, returns the modification to apply to the tile (or Region) entity
+    pub fn clear_feature(&mut self, reference: &RelevantComponentKeys) -> EntityModification {
+        match self {
+            TileView::Tile { feature, .. } => {
+                feature.feature_id = None;
+                EntityModification {
+                    components: vec![reference.feature.key()],
+                    data: vec![feature.try_to_vec().unwrap()],
+                    expected_version: None
+                }
+            },
+            TileView::Region { location, region } => {
+                region.get_tile_mut(location).unwrap().feature_id = None;
+                EntityModification {
+                    components: vec![reference.region.key()],
+                    data: vec![region.try_to_vec().unwrap()],
+                    expected_version: None
+                }
+            }
+        }
+    }
+
+    /// Removes the occupant from the tile, returns the modification to apply to the tile (or Region) entity
+    pub fn clear_occupant(&mut self, reference: &RelevantComponentKeys) -> EntityModification {
+        match self {
+            TileView::Tile { occupant, .. } => {
+                occupant.occupant_id = None;
+                EntityModification {
+                    components: vec![reference.occupant.key()],
+                    data: vec![occupant.try_to_vec().unwrap()],
+                    expected_version: None
+                }
+            },
+            TileView::Region { location, region } => {
+                region.get_tile_mut(location).unwrap().occupant_id = None;
+                EntityModification {
+                    components: vec![reference.region.key()],
+                    data: vec![region.try_to_vec().unwrap()],
+                    expected_version: None
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use super::*;
+
+    fn loc(x: u16, y: u16) -> ComponentLocation {
+        ComponentLocation { x, y }
+    }
+
+    /// Region at region grid (region_x, region_y) whose tiles record their own index as the occupant
+    fn region(region_x: u16, region_y: u16) -> ComponentRegion {
+        let cells = REGION_SIZE as usize * REGION_SIZE as usize;
+        ComponentRegion {
+            origin_x: region_x * REGION_SIZE,
+            origin_y: region_y * REGION_SIZE,
+            tiles: (0..cells).map(|idx| Some(RegionTile { terrain: None, feature_id: None, occupant_id: Some(idx as u64) })).collect(),
+        }
+    }
+
+    #[test]
+    fn locations_map_to_regions_across_chunk_edges() {
+        let last = REGION_SIZE - 1;
+        assert_eq!(get_region_coords(&loc(0, 0)), (0, 0));
+        assert_eq!(get_region_coords(&loc(last, last)), (0, 0));
+        assert_eq!(get_region_coords(&loc(REGION_SIZE, last)), (1, 0));
+        assert_eq!(get_region_coords(&loc(last, REGION_SIZE)), (0, 1));
+        assert_eq!(get_region_coords(&loc(REGION_SIZE, REGION_SIZE)), (1, 1));
+        assert_eq!(get_region_id_for(&loc(REGION_SIZE, 0)), get_region_id(1, 0));
+        assert_ne!(get_region_id(1, 0), get_region_id(0, 1));
+    }
+
+    #[test]
+    fn region_tiles_are_row_major_from_the_origin() {
+        let region = region(1, 0);
+        assert!(region.contains(&loc(REGION_SIZE, 0)));
+        assert!(region.contains(&loc(2 * REGION_SIZE - 1, REGION_SIZE - 1)));
+        assert!(!region.contains(&loc(REGION_SIZE - 1, 0)));
+        assert!(!region.contains(&loc(2 * REGION_SIZE, 0)));
+        assert!(!region.contains(&loc(REGION_SIZE, REGION_SIZE)));
+
+        assert_eq!(region.get_tile(&loc(REGION_SIZE, 0)).unwrap().occupant_id, Some(0));
+        assert_eq!(region.get_tile(&loc(REGION_SIZE + 1, 0)).unwrap().occupant_id, Some(1));
+        assert_eq!(region.get_tile(&loc(REGION_SIZE, 1)).unwrap().occupant_id, Some(REGION_SIZE as u64));
+        assert!(region.get_tile(&loc(0, 0)).is_none());
+    }
+
+    #[test]
+    fn iter_tiles_gives_back_each_tiles_location() {
+        let mut region = region(0, 1);
+        region.tiles[3] = None;
+        let tiles: Vec<(ComponentLocation, &RegionTile)> = region.iter_tiles().collect();
+        assert_eq!(tiles.len(), REGION_SIZE as usize * REGION_SIZE as usize - 1);
+        for (location, tile) in tiles {
+            assert_eq!(region.get_tile(&location).unwrap().occupant_id, tile.occupant_id);
+        }
+    }
+
+    #[test]
+    fn map_regions_cover_partial_chunks() {
+        let mapmeta = |max_x: u16, max_y: u16| ComponentMapMeta { max_x, max_y, topology: MapTopology::Square, chunked: true };
+        assert_eq!(get_map_regions(&mapmeta(REGION_SIZE, REGION_SIZE)), vec![(0, 0)]);
+        assert_eq!(get_map_regions(&mapmeta(REGION_SIZE + 1, REGION_SIZE)), vec![(0, 0), (1, 0)]);
+        assert_eq!(get_map_regions(&mapmeta(1, 2 * REGION_SIZE + 1)), vec![(0, 0), (0, 1), (0, 2)]);
+        assert!(get_map_regions(&mapmeta(0, 0)).is_empty());
+
+        let regions = vec![region(0, 0), region(1, 0)];
+        assert_eq!(find_region(&regions, &loc(REGION_SIZE - 1, 0)), Some(0));
+        assert_eq!(find_region(&regions, &loc(REGION_SIZE, 0)), Some(1));
+        assert_eq!(find_region(&regions, &loc(0, REGION_SIZE)), None);
+    }
+
+    #[test]
+    fn tile_view_clears_the_right_region_cell() {
+        let keys = RelevantComponentKeys::from_schema_urls(|_| Pubkey::new_unique());
+        let mut components = BTreeMap::new();
+        components.insert(keys.region, region(0, 0).to_serialized_component());
+        let region_entity = Entity {
+            entity_id: get_region_id(0, 0),
+            instance: 0,
+            world: Pubkey::default(),
+            world_signer: Pubkey::default(),
+            payer: Pubkey::default(),
+            version: 0,
+            components,
+        };
+
+        assert!(TileView::load(&keys, &region_entity, &loc(REGION_SIZE, 0)).is_err());
+        let mut view = TileView::load(&keys, &region_entity, &loc(2, 1)).unwrap();
+        let idx = REGION_SIZE as u64 + 2;
+        assert_eq!(view.occupant_id(), Some(idx));
+
+        let modification = view.clear_occupant(&keys);
+        assert_eq!(modification.components, vec![keys.region]);
+        let region = ComponentRegion::try_from_slice(&modification.data[0]).unwrap();
+        assert_eq!(region.get_tile(&loc(2, 1)).unwrap().occupant_id, None);
+        assert_eq!(region.get_tile(&loc(1, 1)).unwrap().occupant_id, Some(idx - 1));
+    }
+}
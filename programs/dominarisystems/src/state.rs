@@ -1,36 +1,77 @@
 use anchor_lang::prelude::*;
 
-use crate::component::MaxSize;
+use crate::component::*;
+use crate::geometry::GridMetric;
 
-#[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
-#[derive(AnchorDeserialize, AnchorSerialize, Debug, Clone)]
-pub struct RelevantComponentKeys {
-    pub metadata: Pubkey,
-    pub mapmeta: Pubkey,
-    pub location: Pubkey,
-    pub feature: Pubkey,
-    pub owner: Pubkey,
-    pub value: Pubkey,
-    pub occupant: Pubkey,
-    pub player_stats: Pubkey,
-    pub last_used: Pubkey,
-    pub feature_rank: Pubkey,
-    pub range: Pubkey,
-    pub drop_table: Pubkey,
-    pub uses: Pubkey,
-    pub healing_power: Pubkey,
-    pub health: Pubkey,
-    pub damage: Pubkey,
-    pub troop_class: Pubkey,
-    pub active: Pubkey,
-    pub cost: Pubkey,
-    pub offchain_metadata: Pubkey
-}
-
-impl MaxSize for RelevantComponentKeys {
-    fn get_max_size() -> u64 {
-        return 32*20;
-    }
+/**
+ * Every component the systems program knows about, listed once as `key field: component struct`.
+ * Generates RelevantComponentKeys (the World's key for each component, field order is the on chain layout),
+ * and each component's Component impl, with the field name doubling as its schema URL.
+ */
+macro_rules! relevant_components {
+    ($($field:ident: $component:ident),* $(,)?) => {
+        #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
+        #[derive(AnchorDeserialize, AnchorSerialize, Debug, Clone)]
+        pub struct RelevantComponentKeys {
+            $(pub $field: Pubkey),*
+        }
+
+        impl MaxSize for RelevantComponentKeys {
+            fn get_max_size() -> u64 {
+                return 32 * [$(stringify!($field)),*].len() as u64;
+            }
+        }
+
+        impl RelevantComponentKeys {
+            /// Schema URL of every component, in field order
+            pub fn get_schema_urls() -> Vec<&'static str> {
+                vec![$($component::SCHEMA_URL),*]
+            }
+
+            /// Fills in each key by looking up the component's schema URL
+            pub fn from_schema_urls(get_key: impl Fn(&str) -> Pubkey) -> Self {
+                RelevantComponentKeys {
+                    $($field: get_key($component::SCHEMA_URL)),*
+                }
+            }
+        }
+
+        $(
+            impl Component for $component {
+                const SCHEMA_URL: &'static str = stringify!($field);
+
+                fn get_component_key(keys: &RelevantComponentKeys) -> Pubkey {
+                    return keys.$field
+                }
+            }
+        )*
+    };
+}
+
+relevant_components! {
+    metadata: ComponentMetadata,
+    mapmeta: ComponentMapMeta,
+    location: ComponentLocation,
+    feature: ComponentFeature,
+    owner: ComponentOwner,
+    value: ComponentValue,
+    occupant: ComponentOccupant,
+    player_stats: ComponentPlayerStats,
+    last_used: ComponentLastUsed,
+    feature_rank: ComponentFeatureRank,
+    range: ComponentRange,
+    drop_table: ComponentDropTable,
+    uses: ComponentUses,
+    healing_power: ComponentHealingPower,
+    health: ComponentHealth,
+    damage: ComponentDamage,
+    troop_class: ComponentTroopClass,
+    active: ComponentActive,
+    cost: ComponentCost,
+    offchain_metadata: ComponentOffchainMetadata,
+    vision: ComponentVision,
+    terrain: ComponentTerrain,
+    region: ComponentRegion,
 }
 
 #[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
@@ -38,14 +79,95 @@ impl MaxSize for RelevantComponentKeys {
 pub struct GameConfig {
     pub max_players: u16,
     pub starting_cards: Vec<Pubkey>,
+    pub randomness: RandomnessMode,
+    pub turns: Option<TurnConfig>, // None plays in real time, paced only by unit recovery slots
+    pub victory: VictoryConditions,
+    pub pause_quorum: Option<u16>, // Player votes needed to pause or resume, None lets any one player do it
+    pub entry_fee: Option<u64>, // Lamports escrowed into the instance vault by join_game, paid back out by final score
+    pub grid_metric: GridMetric, // Distance and adjacency used for movement, attack range and vision
 }
 
 impl DependentMaxSize for GameConfig {
     fn get_max_size(&self) -> u64 {
-        return 2 + 4 + (self.starting_cards.len() as u64 * 32_u64);
+        return 2 + 4 + (self.starting_cards.len() as u64 * 32_u64) + 1 + 1 + TurnConfig::get_max_size() + VictoryConditions::get_max_size() + 3 + 9 + 1;
+    }
+}
+
+/**
+ * Win conditions, checked after every combat. The first one met ends the game.
+ * deadline_slot: when it passes, the player with the highest score wins
+ */
+#[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct VictoryConditions {
+    pub last_player_standing: bool,
+    pub score_threshold: Option<u64>,
+    pub features_to_control: Option<u16>,
+    pub deadline_slot: Option<u64>,
+}
+
+impl MaxSize for VictoryConditions {
+    fn get_max_size() -> u64 {
+        return 1 + 9 + 3 + 9;
+    }
+}
+
+#[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq)]
+pub enum VictoryCondition {
+    LastPlayerStanding,
+    ScoreThreshold,
+    FeatureControl,
+    Deadline,
+}
+
+/**
+ * Per player tallies kept on the Instance Index so win conditions can be checked without loading every entity
+ */
+#[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct PlayerStanding {
+    pub player: u64,
+    pub score: u64,
+    pub active_units: u16,
+    pub features: u16,
+    pub has_deployed: bool, // Fielded a unit at some point, only then can losing all units knock the player out
+}
+
+impl MaxSize for PlayerStanding {
+    fn get_max_size() -> u64 {
+        return 8 + 8 + 2 + 2 + 1;
     }
 }
 
+/**
+ * Turn based play: players act one at a time and end their turn with end_turn
+ * If the active player idles for timeout_slots, any other player can skip them
+ */
+#[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct TurnConfig {
+    pub timeout_slots: u64,
+}
+
+impl MaxSize for TurnConfig {
+    fn get_max_size() -> u64 {
+        return 8;
+    }
+}
+
+/**
+ * How combat rolls are seeded
+ * SlotHashes: most recent slot hash mixed with attacker & defender, no extra transactions needed
+ * CommitReveal: player commits to a seed hash before attacking and reveals it in the attack
+ */
+#[cfg_attr(feature = "sdk", derive(serde::Serialize, serde::Deserialize))]
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone, PartialEq)]
+pub enum RandomnessMode {
+    SlotHashes,
+    CommitReveal,
+}
+
 pub trait DependentMaxSize {
     fn get_max_size(&self) -> u64;
 }
\ No newline at end of file
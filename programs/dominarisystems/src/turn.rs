@@ -0,0 +1,143 @@
+use anchor_lang::prelude::*;
+
+use crate::account::InstanceIndex;
+use crate::component::ComponentLastUsed;
+use crate::error::DominariError;
+
+/**
+ * In turn based games only the active player can act.
+ * Entities without a player (admin features) can never act in a turn based game.
+ */
+pub fn check_turn(index: &InstanceIndex, player: Option<u64>) -> Result<()> {
+    if index.config.turns.is_none() {
+        return Ok(())
+    }
+    if player.is_none() || index.active_player != player.unwrap() {
+        return err!(DominariError::NotYourTurn)
+    }
+    Ok(())
+}
+
+/**
+ * Value to stamp into ComponentLastUsed.last_used.
+ * Slot based games pace by slot, turn based games by turn number.
+ */
+pub fn get_pacing_mark(index: &InstanceIndex, slot: u64) -> u64 {
+    match index.config.turns {
+        Some(_) => index.turn_number,
+        None => slot
+    }
+}
+
+/**
+ * Slot based: entity has to wait `recovery` slots after its last use.
+ * Turn based: entity can be used once per turn, recovery is ignored.
+ */
+pub fn is_recovering(index: &InstanceIndex, last_used: &ComponentLastUsed, slot: u64) -> bool {
+    match index.config.turns {
+        Some(_) => last_used.last_used == index.turn_number,
+        None => last_used.last_used != 0 && (last_used.last_used + last_used.recovery) >= slot
+    }
+}
+
+/**
+ * Player after the active one, wrapping around.
+ * If the active player is no longer in the game, the first player goes next.
+ */
+pub fn get_next_player(index: &InstanceIndex) -> u64 {
+    match index.players.iter().position(|id| *id == index.active_player) {
+        Some(idx) => index.players[(idx + 1) % index.players.len()],
+        None => index.players[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::PlayPhase;
+    use crate::geometry::GridMetric;
+    use crate::state::{GameConfig, RandomnessMode, TurnConfig, VictoryConditions};
+
+    fn index(players: Vec<u64>, turns: Option<TurnConfig>) -> InstanceIndex {
+        InstanceIndex {
+            config: GameConfig {
+                max_players: 4,
+                starting_cards: vec![],
+                randomness: RandomnessMode::SlotHashes,
+                turns,
+                victory: VictoryConditions {
+                    last_player_standing: true,
+                    score_threshold: None,
+                    features_to_control: None,
+                    deadline_slot: None,
+                },
+                pause_quorum: None,
+                entry_fee: None,
+                grid_metric: GridMetric::Manhattan,
+            },
+            map: 0,
+            tiles: vec![],
+            features: vec![],
+            units: vec![],
+            players,
+            play_phase: PlayPhase::Play,
+            turn_number: 3,
+            active_player: 0,
+            turn_start: 0,
+            standings: vec![],
+            winner: None,
+            phase_votes: vec![],
+            features_built: 0,
+        }
+    }
+
+    fn turn_based(players: Vec<u64>) -> InstanceIndex {
+        index(players, Some(TurnConfig { timeout_slots: 100 }))
+    }
+
+    #[test]
+    fn next_player_wraps_around() {
+        let mut index = turn_based(vec![1, 2, 3]);
+        index.active_player = 2;
+        assert_eq!(get_next_player(&index), 3);
+        index.active_player = 3;
+        assert_eq!(get_next_player(&index), 1);
+    }
+
+    #[test]
+    fn first_player_goes_when_active_player_left() {
+        let mut index = turn_based(vec![1, 2, 3]);
+        index.active_player = 9;
+        assert_eq!(get_next_player(&index), 1);
+    }
+
+    #[test]
+    fn slot_based_recovery_waits_out_recovery_slots() {
+        let index = index(vec![1, 2], None);
+        let last_used = ComponentLastUsed { last_used: 100, recovery: 10 };
+        assert!(is_recovering(&index, &last_used, 105));
+        assert!(is_recovering(&index, &last_used, 110));
+        assert!(!is_recovering(&index, &last_used, 111));
+        // Never used yet
+        assert!(!is_recovering(&index, &ComponentLastUsed { last_used: 0, recovery: 10 }, 5));
+    }
+
+    #[test]
+    fn turn_based_recovery_is_once_per_turn() {
+        let index = turn_based(vec![1, 2]);
+        assert!(is_recovering(&index, &ComponentLastUsed { last_used: 3, recovery: 1000 }, 0));
+        assert!(!is_recovering(&index, &ComponentLastUsed { last_used: 2, recovery: 1000 }, 0));
+        assert_eq!(get_pacing_mark(&index, 500), 3);
+        assert_eq!(get_pacing_mark(&self::index(vec![1, 2], None), 500), 500);
+    }
+
+    #[test]
+    fn only_active_player_acts_in_turn_based_games() {
+        let mut index = turn_based(vec![1, 2]);
+        index.active_player = 1;
+        assert!(check_turn(&index, Some(1)).is_ok());
+        assert_eq!(check_turn(&index, Some(2)).unwrap_err(), error!(DominariError::NotYourTurn));
+        assert_eq!(check_turn(&index, None).unwrap_err(), error!(DominariError::NotYourTurn));
+        assert!(check_turn(&self::index(vec![1, 2], None), Some(2)).is_ok());
+    }
+}
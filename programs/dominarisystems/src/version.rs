@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use ecs::state::EntityVersion;
+
+use crate::error::DominariError;
+
+/**
+ * Client pins entities at the versions it built the transaction from, the action only goes through if none of them changed.
+ * involved is (entity, current version) for every entity the instruction reads or writes. Pinning anything else is an error.
+ */
+pub fn check_entity_versions(expected_versions: &Vec<EntityVersion>, involved: &[(Pubkey, u64)]) -> Result<()> {
+    for expected in expected_versions.iter() {
+        match involved.iter().find(|(entity, _)| *entity == expected.entity) {
+            Some((_, version)) => {
+                if *version != expected.version {
+                    return err!(DominariError::StaleEntity)
+                }
+            },
+            None => return err!(DominariError::UnknownVersionedEntity)
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_code(result: Result<()>) -> u32 {
+        match result.unwrap_err() {
+            Error::AnchorError(err) => err.error_code_number,
+            Error::ProgramError(err) => panic!("expected an anchor error, got {:?}", err),
+        }
+    }
+
+    #[test]
+    fn matching_versions_pass() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let involved = [(a, 3), (b, 0)];
+        assert!(check_entity_versions(&vec![], &involved).is_ok());
+        assert!(check_entity_versions(&vec![EntityVersion { entity: b, version: 0 }], &involved).is_ok());
+        assert!(check_entity_versions(&vec![EntityVersion { entity: a, version: 3 }, EntityVersion { entity: b, version: 0 }], &involved).is_ok());
+    }
+
+    #[test]
+    fn changed_entity_is_stale() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let involved = [(a, 3), (b, 0)];
+        let expected = vec![EntityVersion { entity: b, version: 0 }, EntityVersion { entity: a, version: 2 }];
+        assert_eq!(error_code(check_entity_versions(&expected, &involved)), u32::from(DominariError::StaleEntity));
+    }
+
+    #[test]
+    fn pinning_an_uninvolved_entity_fails() {
+        let involved = [(Pubkey::new_unique(), 1)];
+        let expected = vec![EntityVersion { entity: Pubkey::new_unique(), version: 1 }];
+        assert_eq!(error_code(check_entity_versions(&expected, &involved)), u32::from(DominariError::UnknownVersionedEntity));
+    }
+}
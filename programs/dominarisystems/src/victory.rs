@@ -0,0 +1,95 @@
+use crate::account::InstanceIndex;
+use crate::state::{PlayerStanding, VictoryCondition};
+
+pub fn get_standing_mut(index: &mut InstanceIndex, player: u64) -> Option<&mut PlayerStanding> {
+    index.standings.iter_mut().find(|standing| standing.player == player)
+}
+
+/**
+ * A player is only out once they fielded a unit and lost all of them, so nobody wins before their opponents deploy.
+ * Returns the one player left in, if every other player has been knocked out.
+ */
+pub fn get_last_player_standing(standings: &Vec<PlayerStanding>) -> Option<u64> {
+    if standings.len() < 2 {
+        return None
+    }
+    let mut remaining: Vec<&PlayerStanding> = standings.iter().filter(|s| !(s.has_deployed && s.active_units == 0)).collect();
+    if remaining.len() == 1 {
+        return Some(remaining.pop().unwrap().player)
+    }
+    None
+}
+
+/**
+ * Checks the instance's win conditions against the current standings.
+ * Returns the winning player and the condition they won by, if the game is over.
+ */
+pub fn check_victory(index: &InstanceIndex, slot: u64) -> Option<(u64, VictoryCondition)> {
+    let victory = &index.config.victory;
+
+    if victory.last_player_standing {
+        if let Some(winner) = get_last_player_standing(&index.standings) {
+            return Some((winner, VictoryCondition::LastPlayerStanding))
+        }
+    }
+
+    if let Some(threshold) = victory.score_threshold {
+        if let Some(winner) = index.standings.iter().find(|s| s.score >= threshold) {
+            return Some((winner.player, VictoryCondition::ScoreThreshold))
+        }
+    }
+
+    if let Some(features) = victory.features_to_control {
+        if let Some(winner) = index.standings.iter().find(|s| s.features >= features) {
+            return Some((winner.player, VictoryCondition::FeatureControl))
+        }
+    }
+
+    if let Some(deadline) = victory.deadline_slot {
+        if slot >= deadline && index.standings.len() > 0 {
+            // Highest score wins, ties go to whoever joined first
+            let mut winner = &index.standings[0];
+            for standing in index.standings.iter() {
+                if standing.score > winner.score {
+                    winner = standing;
+                }
+            }
+            return Some((winner.player, VictoryCondition::Deadline))
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn standing(player: u64, active_units: u16, has_deployed: bool) -> PlayerStanding {
+        PlayerStanding { player, score: 0, active_units, features: 0, has_deployed }
+    }
+
+    #[test]
+    fn no_winner_before_opponent_deploys() {
+        let standings = vec![standing(1, 1, true), standing(2, 0, false)];
+        assert_eq!(get_last_player_standing(&standings), None);
+    }
+
+    #[test]
+    fn last_player_with_units_wins_once_others_are_wiped_out() {
+        let standings = vec![standing(1, 2, true), standing(2, 0, true), standing(3, 0, true)];
+        assert_eq!(get_last_player_standing(&standings), Some(1));
+    }
+
+    #[test]
+    fn no_winner_while_two_players_remain() {
+        let standings = vec![standing(1, 1, true), standing(2, 3, true), standing(3, 0, true)];
+        assert_eq!(get_last_player_standing(&standings), None);
+    }
+
+    #[test]
+    fn no_winner_alone_or_when_everyone_is_out() {
+        assert_eq!(get_last_player_standing(&vec![standing(1, 1, true)]), None);
+        assert_eq!(get_last_player_standing(&vec![standing(1, 0, true), standing(2, 0, true)]), None);
+    }
+}
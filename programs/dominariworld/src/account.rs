@@ -1,7 +1,9 @@
 use anchor_lang::prelude::*;
 use std::collections::BTreeMap;
 
-//use crate::state::*;
+use crate::state::*;
+use crate::constant::*;
+use crate::error::WorldError;
 
 #[account]
 pub struct WorldConfig{
@@ -9,10 +11,46 @@ pub struct WorldConfig{
     pub components: u64,
 }
 
+/**
+ * Governs an instance. With no signer set the authority key alone acts for the instance,
+ * otherwise at least threshold of the signers have to sign (authority alone isn't enough).
+ */
 #[account]
 pub struct InstanceAuthority{
     pub instance: u64,
-    pub authority: Pubkey
+    pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>, // Proposed new authority, takes over once it accepts
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+impl InstanceAuthority {
+    pub const SIZE: usize = 8+8+32+33+4+(32*MAX_AUTHORITY_SIGNERS)+1;
+
+    /**
+     * Checks the instance authority approved this transaction.
+     * payer is the signing authority, or one of the signers with the rest passed in as signing remaining accounts.
+     */
+    pub fn check_authorized(&self, payer: &Pubkey, remaining_accounts: &[AccountInfo]) -> Result<()> {
+        if self.signers.is_empty() {
+            if self.authority != *payer {
+                return err!(WorldError::Unauthorized)
+            }
+            return Ok(())
+        }
+
+        let mut approvals: Vec<Pubkey> = vec![];
+        let signed = std::iter::once(*payer).chain(remaining_accounts.iter().filter(|acc| acc.is_signer).map(|acc| acc.key()));
+        for key in signed {
+            if self.signers.contains(&key) && !approvals.contains(&key) {
+                approvals.push(key);
+            }
+        }
+        if approvals.len() < self.threshold as usize {
+            return err!(WorldError::Unauthorized)
+        }
+        Ok(())
+    }
 }
 
 // PDA'd by Component ID which is just WorldSigner.Components + 1
@@ -25,5 +63,5 @@ pub struct ComponentSchema{
 pub struct SystemRegistration{
     pub system: Pubkey,
     pub instance: u64,
-    pub components: BTreeMap<Pubkey, bool>, //PDA of the Component Schema
+    pub components: BTreeMap<Pubkey, ComponentPermissions>, //PDA of the Component Schema
 }
\ No newline at end of file
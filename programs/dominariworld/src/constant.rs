@@ -1 +1,2 @@
-pub const STRING_MAX_SIZE:u64 = 256;
\ No newline at end of file
+pub const STRING_MAX_SIZE:u64 = 256;
+pub const MAX_AUTHORITY_SIGNERS:usize = 10;
\ No newline at end of file
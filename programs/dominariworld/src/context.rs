@@ -3,12 +3,13 @@ use std::collections::BTreeMap;
 
 use crate::account::*;
 use crate::constant::*;
+use crate::state::*;
 
 use ecs::{
     self,
     account::{WorldInstance, Entity},
     program::Ecs,
-    state::SerializedComponent
+    state::{SerializedComponent, EntityModification}
 };
 
 #[derive(Accounts)]
@@ -54,12 +55,53 @@ pub struct InstanceWorld<'info>{
             world_instance.key().as_ref()
         ],
         bump,
-        space=8+8+32,
+        space=InstanceAuthority::SIZE,
     )]
     pub instance_authority: Account<'info, InstanceAuthority>
 
 }
 
+#[derive(Accounts)]
+pub struct UpdateInstanceAuthority<'info>{
+    pub payer: Signer<'info>,
+
+    #[account(
+        constraint = world_instance.world.key() == program_id.key()
+    )]
+    pub world_instance: Account<'info, WorldInstance>,
+
+    // Current authority approval is checked in the instruction
+    #[account(
+        mut,
+        seeds=[
+            b"Instance_Authority",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_authority: Account<'info, InstanceAuthority>
+}
+
+#[derive(Accounts)]
+pub struct AcceptInstanceAuthority<'info>{
+    pub new_authority: Signer<'info>,
+
+    #[account(
+        constraint = world_instance.world.key() == program_id.key()
+    )]
+    pub world_instance: Account<'info, WorldInstance>,
+
+    #[account(
+        mut,
+        seeds=[
+            b"Instance_Authority",
+            world_instance.key().as_ref()
+        ],
+        bump,
+    )]
+    pub instance_authority: Account<'info, InstanceAuthority>
+}
+
 #[derive(Accounts)]
 #[instruction(schema:String)]
 pub struct RegisterComponent<'info>{
@@ -99,7 +141,7 @@ pub struct RegisterSystem <'info> {
     )]
     pub world_instance: Account<'info, WorldInstance>,
 
-    /// Make sure the instance authority is of the world instance that's passed in
+    /// Only the instance authority of the world instance that's passed in can add systems to it (checked in the instruction)
     #[account(
         constraint = instance_authority.instance == world_instance.instance
     )]
@@ -125,7 +167,7 @@ pub struct RegisterSystem <'info> {
 
 #[derive(Accounts)]
 #[instruction(components: Vec<Pubkey>)]
-pub struct AddComponentsToSystemRegistration <'info> {
+pub struct GrantComponentPermissions <'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
     pub system_program: Program<'info, System>,
@@ -137,7 +179,7 @@ pub struct AddComponentsToSystemRegistration <'info> {
     )]
     pub world_instance: Account<'info, WorldInstance>,
 
-    /// Make sure the instance authority is of the world instance that's passed in
+    /// Only the instance authority of the world instance that's passed in can grant permissions (checked in the instruction)
     #[account(
         constraint = instance_authority.instance == world_instance.instance
     )]
@@ -145,7 +187,7 @@ pub struct AddComponentsToSystemRegistration <'info> {
     
     #[account(
         mut,
-        realloc = system_registration.to_account_info().data_len() + (components.len()*32) + components.len(),
+        realloc = system_registration.to_account_info().data_len() + get_new_components_size(&components, &system_registration.components),
         realloc::payer = payer,
         realloc::zero = false,
         seeds=[
@@ -162,6 +204,114 @@ pub struct AddComponentsToSystemRegistration <'info> {
     pub system: AccountInfo<'info>,
 }
 
+#[derive(Accounts)]
+pub struct RevokeComponentPermissions <'info> {
+    pub payer: Signer<'info>,
+
+    /// Universe World Instance Account
+    /// Make sure that its a world instance that belongs to *this* world
+    #[account(
+        constraint = world_instance.world.key() == program_id.key()
+    )]
+    pub world_instance: Account<'info, WorldInstance>,
+
+    /// Only the instance authority of the world instance that's passed in can revoke permissions (checked in the instruction)
+    #[account(
+        constraint = instance_authority.instance == world_instance.instance
+    )]
+    pub instance_authority: Account<'info, InstanceAuthority>,
+    
+    #[account(
+        mut,
+        seeds=[
+            b"System_Registration",
+            world_instance.key().as_ref(),
+            system.key().as_ref()
+        ],
+        bump,
+    )]
+    pub system_registration: Account<'info, SystemRegistration>,
+
+    /// CHECK: This can be any pubkey, but likely will be pubkey of 
+    /// PDA Signer from System
+    pub system: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(components: Vec<Pubkey>)]
+pub struct RemoveComponentsFromSystemRegistration <'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+
+    /// Universe World Instance Account
+    /// Make sure that its a world instance that belongs to *this* world
+    #[account(
+        constraint = world_instance.world.key() == program_id.key()
+    )]
+    pub world_instance: Account<'info, WorldInstance>,
+
+    /// Only the instance authority of the world instance that's passed in can remove components (checked in the instruction)
+    #[account(
+        constraint = instance_authority.instance == world_instance.instance
+    )]
+    pub instance_authority: Account<'info, InstanceAuthority>,
+    
+    // Shrinking the registration returns the freed rent to the payer
+    #[account(
+        mut,
+        realloc = system_registration.to_account_info().data_len() - get_removed_components_size(&components, &system_registration.components),
+        realloc::payer = payer,
+        realloc::zero = false,
+        seeds=[
+            b"System_Registration",
+            world_instance.key().as_ref(),
+            system.key().as_ref()
+        ],
+        bump,
+    )]
+    pub system_registration: Account<'info, SystemRegistration>,
+
+    /// CHECK: This can be any pubkey, but likely will be pubkey of 
+    /// PDA Signer from System
+    pub system: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnregisterSystem <'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Universe World Instance Account
+    /// Make sure that its a world instance that belongs to *this* world
+    #[account(
+        constraint = world_instance.world.key() == program_id.key()
+    )]
+    pub world_instance: Account<'info, WorldInstance>,
+
+    /// Only the instance authority of the world instance that's passed in can remove systems from it (checked in the instruction)
+    #[account(
+        constraint = instance_authority.instance == world_instance.instance
+    )]
+    pub instance_authority: Account<'info, InstanceAuthority>,
+    
+    #[account(
+        mut,
+        close = payer,
+        seeds=[
+            b"System_Registration",
+            world_instance.key().as_ref(),
+            system.key().as_ref()
+        ],
+        bump,
+    )]
+    pub system_registration: Account<'info, SystemRegistration>,
+
+    /// CHECK: This can be any pubkey, but likely will be pubkey of 
+    /// PDA Signer from System
+    pub system: AccountInfo<'info>,
+}
+
 #[derive(Accounts)]
 #[instruction(entity_id:u64, components: BTreeMap<Pubkey, SerializedComponent>)]
 pub struct MintEntity<'info> {
@@ -185,9 +335,9 @@ pub struct MintEntity<'info> {
     )]
     pub world_instance: Account<'info, WorldInstance>,
     pub system: Signer<'info>,
-    // All systems can make any entities they want
+    // System needs Mint permission for every component on the new entity
     #[account(
-        constraint = system_registration.system.key() == system.key() && check_sys_registry(&components.keys().cloned().collect(), &system_registration.components)
+        constraint = system_registration.system.key() == system.key() && check_sys_registry(&components.keys().cloned().collect(), &system_registration.components, Permission::Mint)
     )]
     pub system_registration: Account<'info, SystemRegistration>,
     pub universe: Program<'info, Ecs>,     
@@ -215,10 +365,10 @@ pub struct AddComponents<'info>{
     
     pub system: Signer<'info>,
     
-    // System is allowed to modify the component it's adding
+    // System is allowed to add the components it's adding
     // System is a signer
     #[account(
-        constraint = system_registration.system.key() == system.key() && check_sys_registry(&components.iter().map(|tuple| tuple.0.clone() ).collect(), &system_registration.components)
+        constraint = system_registration.system.key() == system.key() && check_sys_registry(&components.iter().map(|tuple| tuple.0.clone() ).collect(), &system_registration.components, Permission::Add)
     )]
     pub system_registration: Account<'info, SystemRegistration>,
 
@@ -247,10 +397,10 @@ pub struct RemoveComponent<'info>{
     
     pub system: Signer<'info>,
     
-    // System is allowed to modify the component it's adding
+    // System is allowed to remove the components it's removing
     // System is a signer
     #[account(
-        constraint = system_registration.system.key() == system.key() && check_sys_registry(&components, &system_registration.components)
+        constraint = system_registration.system.key() == system.key() && check_sys_registry(&components, &system_registration.components, Permission::Remove)
     )]
     pub system_registration: Account<'info, SystemRegistration>,
 
@@ -275,10 +425,34 @@ pub struct ModifyComponent<'info>{
     
     pub system: Signer<'info>,
     
-    // System is allowed to modify the component it's adding
+    // System is allowed to modify the components it's modifying
+    // System is a signer
+    #[account(
+        constraint = system_registration.system.key() == system.key() && check_sys_registry(&components, &system_registration.components, Permission::Modify)
+    )]
+    pub system_registration: Account<'info, SystemRegistration>,
+
+    pub universe: Program<'info, Ecs>, 
+}
+
+#[derive(Accounts)]
+#[instruction(modifications: Vec<EntityModification>)]
+pub struct ModifyComponentsBatch<'info>{
+    //Used to Sign Tx for the CPI
+    #[account(
+        seeds=[b"world_signer"],
+        bump,
+    )]
+    pub world_config: Account<'info, WorldConfig>,
+
+    // Entities are passed in as remaining accounts
+    
+    pub system: Signer<'info>,
+    
+    // System is allowed to modify every component in the batch
     // System is a signer
     #[account(
-        constraint = system_registration.system.key() == system.key() && check_sys_registry(&components, &system_registration.components)
+        constraint = system_registration.system.key() == system.key() && check_sys_registry(&modifications.iter().flat_map(|m| m.components.clone()).collect(), &system_registration.components, Permission::Modify)
     )]
     pub system_registration: Account<'info, SystemRegistration>,
 
@@ -316,13 +490,99 @@ pub struct RemoveEntity<'info>{
 }
 
 
+#[derive(Accounts)]
+pub struct CloseEntities<'info>{
+    //Used to Sign Tx for the CPI
+    #[account(
+        seeds=[b"world_signer"],
+        bump,
+    )]
+    pub world_config: Account<'info, WorldConfig>,
+
+    // Entities and their original payers are passed in as remaining accounts
+
+    pub system: Signer<'info>,
+
+    // System needs Close permission for every component on the entities, checked per entity
+    #[account(
+        constraint = system_registration.system.key() == system.key()
+    )]
+    pub system_registration: Account<'info, SystemRegistration>,
+
+    pub universe: Program<'info, Ecs>, 
+}
+
+#[derive(Accounts)]
+pub struct TokenizeEntity<'info>{
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    /// CHECK: Checked by the Universe
+    pub token_program: AccountInfo<'info>,
+    /// CHECK: Checked by the Universe
+    pub associated_token_program: AccountInfo<'info>,
+    /// CHECK: Checked by the Universe
+    pub rent: AccountInfo<'info>,
+
+    //Used to Sign Tx for the CPI
+    #[account(
+        seeds=[b"world_signer"],
+        bump,
+    )]
+    pub world_config: Account<'info, WorldConfig>,
+
+    #[account(
+        constraint = entity.world.key() == program_id.key() && entity.instance == system_registration.instance
+    )]
+    pub entity: Account<'info, Entity>,
+
+    /// CHECK: Initialized by the Universe
+    #[account(mut)]
+    pub entity_nft: AccountInfo<'info>,
+    /// CHECK: Initialized by the Universe
+    #[account(mut)]
+    pub mint: AccountInfo<'info>,
+    /// CHECK: Any wallet can receive the token
+    pub owner: AccountInfo<'info>,
+    /// CHECK: Initialized by the Universe
+    #[account(mut)]
+    pub owner_ata: AccountInfo<'info>,
+
+    pub system: Signer<'info>,
+
+    // ANY registered system can tokenize entities in its instance
+    #[account(
+        constraint = system_registration.system.key() == system.key()
+    )]
+    pub system_registration: Account<'info, SystemRegistration>,
+
+    pub universe: Program<'info, Ecs>, 
+}
+
 /*************************************************UTIL Functions */
 
-pub fn check_sys_registry(components: &Vec<Pubkey>, system_components: &BTreeMap<Pubkey, bool>) -> bool {
+pub fn check_sys_registry(components: &Vec<Pubkey>, system_components: &BTreeMap<Pubkey, ComponentPermissions>, permission: Permission) -> bool {
     for comp in components {
-        if !system_components.contains_key(comp) {
-            return false;
+        match system_components.get(comp) {
+            Some(permissions) if permissions.allows(permission) => {},
+            _ => return false
         }
     }
     return true;
+}
+
+/// Space freed by dropping the components that are in the registration
+pub fn get_removed_components_size(components: &Vec<Pubkey>, system_components: &BTreeMap<Pubkey, ComponentPermissions>) -> usize {
+    let mut removed_components: Vec<&Pubkey> = components.iter().filter(|comp| system_components.contains_key(comp)).collect();
+    removed_components.sort();
+    removed_components.dedup();
+    removed_components.len() * (32 + ComponentPermissions::SIZE)
+}
+
+/// Space needed for the components that aren't in the registration yet
+pub fn get_new_components_size(components: &Vec<Pubkey>, system_components: &BTreeMap<Pubkey, ComponentPermissions>) -> usize {
+    let mut new_components: Vec<&Pubkey> = components.iter().filter(|comp| !system_components.contains_key(comp)).collect();
+    new_components.sort();
+    new_components.dedup();
+    new_components.len() * (32 + ComponentPermissions::SIZE)
 }
\ No newline at end of file
@@ -1 +1,19 @@
-//use anchor_lang::prelude::*;
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum WorldError {
+    #[msg("Entity doesn't belong to this World instance!")]
+    InvalidEntity,
+
+    #[msg("System doesn't have permission to do that with this component!")]
+    MissingPermission,
+
+    #[msg("Instance authority didn't approve this!")]
+    Unauthorized,
+
+    #[msg("Signer set needs 1 to MAX_AUTHORITY_SIGNERS unique signers and a threshold between 1 and the number of signers!")]
+    InvalidSignerSet,
+
+    #[msg("Only the proposed authority can accept the transfer!")]
+    NotPendingAuthority,
+}
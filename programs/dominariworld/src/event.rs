@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::state::ComponentPermissions;
+
 #[event]
 pub struct NewWorldInstance {
     pub world_instance: Pubkey,
@@ -17,4 +19,41 @@ pub struct NewSystemRegistration {
     pub world_instance: Pubkey,
     pub system: Pubkey,
     pub system_registration: Pubkey
+}
+
+#[event]
+pub struct SystemUnregistered {
+    pub world_instance: Pubkey,
+    pub system: Pubkey,
+    pub system_registration: Pubkey
+}
+
+#[event]
+pub struct ComponentPermissionsChanged {
+    pub system_registration: Pubkey,
+    pub system: Pubkey,
+    pub components: Vec<Pubkey>,
+    pub permissions: ComponentPermissions,
+    pub granted: bool
+}
+
+#[event]
+pub struct InstanceAuthorityProposed {
+    pub world_instance: Pubkey,
+    pub authority: Pubkey,
+    pub pending_authority: Pubkey
+}
+
+#[event]
+pub struct InstanceAuthorityTransferred {
+    pub world_instance: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey
+}
+
+#[event]
+pub struct InstanceSignersChanged {
+    pub world_instance: Pubkey,
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8
 }
\ No newline at end of file
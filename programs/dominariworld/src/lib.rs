@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
 use std::collections::BTreeMap;
 
-use ecs::state::SerializedComponent;
+use ecs::state::{SerializedComponent, EntityModification};
+use ecs::account::Entity;
 
 declare_id!("H5mieGWWK6qukHoNzbR6ysLxReeQC4JHZcNM6JkPQnm3");
 
@@ -14,10 +15,10 @@ pub mod state;
 
 //use account::*;
 use context::*;
-//use constant::*;
-//use error::*;
+use constant::*;
+use error::*;
 use event::*;
-//use state::*;
+use state::*;
 
 #[program]
 pub mod dominariworld {
@@ -70,6 +71,67 @@ pub mod dominariworld {
         Ok(())
     }
 
+    /**
+     * Instance Authority proposes handing the instance to a new key, which takes over once it accepts.
+     * Proposing again replaces the pending proposal.
+     */
+    pub fn propose_instance_authority(ctx:Context<UpdateInstanceAuthority>, new_authority: Pubkey) -> Result<()> {
+        ctx.accounts.instance_authority.check_authorized(&ctx.accounts.payer.key(), ctx.remaining_accounts)?;
+        ctx.accounts.instance_authority.pending_authority = Some(new_authority);
+
+        emit!(InstanceAuthorityProposed {
+            world_instance: ctx.accounts.world_instance.key(),
+            authority: ctx.accounts.instance_authority.authority,
+            pending_authority: new_authority
+        });
+        Ok(())
+    }
+
+    pub fn accept_instance_authority(ctx:Context<AcceptInstanceAuthority>) -> Result<()> {
+        if ctx.accounts.instance_authority.pending_authority != Some(ctx.accounts.new_authority.key()) {
+            return err!(WorldError::NotPendingAuthority)
+        }
+        let previous = ctx.accounts.instance_authority.authority;
+        ctx.accounts.instance_authority.authority = ctx.accounts.new_authority.key();
+        ctx.accounts.instance_authority.pending_authority = None;
+
+        emit!(InstanceAuthorityTransferred {
+            world_instance: ctx.accounts.world_instance.key(),
+            from: previous,
+            to: ctx.accounts.new_authority.key()
+        });
+        Ok(())
+    }
+
+    /**
+     * Instance Authority sets the M-of-N signer set that governs the instance from then on.
+     * An empty set (with threshold 0) hands control back to the authority key alone.
+     */
+    pub fn set_instance_signers(ctx:Context<UpdateInstanceAuthority>, signers: Vec<Pubkey>, threshold: u8) -> Result<()> {
+        ctx.accounts.instance_authority.check_authorized(&ctx.accounts.payer.key(), ctx.remaining_accounts)?;
+        let mut unique = signers.clone();
+        unique.sort();
+        unique.dedup();
+        let valid = if signers.is_empty() {
+            threshold == 0
+        } else {
+            unique.len() == signers.len() && signers.len() <= MAX_AUTHORITY_SIGNERS && threshold >= 1 && threshold as usize <= signers.len()
+        };
+        if !valid {
+            return err!(WorldError::InvalidSignerSet)
+        }
+
+        ctx.accounts.instance_authority.signers = signers.clone();
+        ctx.accounts.instance_authority.threshold = threshold;
+
+        emit!(InstanceSignersChanged {
+            world_instance: ctx.accounts.world_instance.key(),
+            signers,
+            threshold
+        });
+        Ok(())
+    }
+
     /**
      * Anyone can register new components as long as they use unique URIs
      */
@@ -85,6 +147,7 @@ pub mod dominariworld {
     }
 
     pub fn register_system(ctx: Context<RegisterSystem>) -> Result<()> {
+        ctx.accounts.instance_authority.check_authorized(&ctx.accounts.payer.key(), ctx.remaining_accounts)?;
         ctx.accounts.system_registration.system = ctx.accounts.system.key();
         ctx.accounts.system_registration.instance = ctx.accounts.world_instance.instance;
 
@@ -96,10 +159,83 @@ pub mod dominariworld {
         Ok(())
     }
 
-    pub fn add_components_to_system_registration(ctx:Context<AddComponentsToSystemRegistration>, components:Vec<Pubkey>) -> Result<()> {
-        for comp in components {
-            ctx.accounts.system_registration.components.insert(comp, true);
+    /**
+     * Instance Authority grants a system permissions on components, on top of whatever it already has.
+     */
+    pub fn grant_component_permissions(ctx:Context<GrantComponentPermissions>, components:Vec<Pubkey>, permissions: ComponentPermissions) -> Result<()> {
+        ctx.accounts.instance_authority.check_authorized(&ctx.accounts.payer.key(), ctx.remaining_accounts)?;
+        for comp in components.iter() {
+            ctx.accounts.system_registration.components
+                .entry(*comp)
+                .or_insert(ComponentPermissions::default())
+                .grant(&permissions);
+        }
+
+        emit!(ComponentPermissionsChanged {
+            system_registration: ctx.accounts.system_registration.key(),
+            system: ctx.accounts.system.key(),
+            components,
+            permissions,
+            granted: true
+        });
+        Ok(())
+    }
+
+    /**
+     * Instance Authority takes permissions on components away from a system.
+     * Components the system has no permissions left on are dropped from the registration.
+     */
+    pub fn revoke_component_permissions(ctx:Context<RevokeComponentPermissions>, components:Vec<Pubkey>, permissions: ComponentPermissions) -> Result<()> {
+        ctx.accounts.instance_authority.check_authorized(&ctx.accounts.payer.key(), ctx.remaining_accounts)?;
+        for comp in components.iter() {
+            if let Some(existing) = ctx.accounts.system_registration.components.get_mut(comp) {
+                existing.revoke(&permissions);
+                if existing.is_empty() {
+                    ctx.accounts.system_registration.components.remove(comp);
+                }
+            }
+        }
+
+        emit!(ComponentPermissionsChanged {
+            system_registration: ctx.accounts.system_registration.key(),
+            system: ctx.accounts.system.key(),
+            components,
+            permissions,
+            granted: false
+        });
+        Ok(())
+    }
+
+    /**
+     * Instance Authority drops components from a system's registration entirely, returning the rent for them.
+     */
+    pub fn remove_components_from_system_registration(ctx:Context<RemoveComponentsFromSystemRegistration>, components:Vec<Pubkey>) -> Result<()> {
+        ctx.accounts.instance_authority.check_authorized(&ctx.accounts.payer.key(), ctx.remaining_accounts)?;
+        for comp in components.iter() {
+            ctx.accounts.system_registration.components.remove(comp);
         }
+
+        emit!(ComponentPermissionsChanged {
+            system_registration: ctx.accounts.system_registration.key(),
+            system: ctx.accounts.system.key(),
+            components,
+            permissions: ComponentPermissions::all(),
+            granted: false
+        });
+        Ok(())
+    }
+
+    /**
+     * Instance Authority cuts a system off from the instance by closing its registration.
+     * The system can't mint or touch entities in this instance until it's registered again.
+     */
+    pub fn unregister_system(ctx:Context<UnregisterSystem>) -> Result<()> {
+        ctx.accounts.instance_authority.check_authorized(&ctx.accounts.payer.key(), ctx.remaining_accounts)?;
+        emit!(SystemUnregistered {
+            world_instance: ctx.accounts.world_instance.key(),
+            system: ctx.accounts.system.key(),
+            system_registration: ctx.accounts.system_registration.key()
+        });
         Ok(())
     }
 
@@ -126,7 +262,7 @@ pub mod dominariworld {
         Ok(())
     }
 
-    pub fn req_add_component(ctx:Context<AddComponents>, components: Vec<(Pubkey,SerializedComponent)>) -> Result<()> {
+    pub fn req_add_component(ctx:Context<AddComponents>, components: Vec<(Pubkey,SerializedComponent)>, expected_version: Option<u64>) -> Result<()> {
         let accounts = ecs::cpi::accounts::AddComponent {
             payer: ctx.accounts.payer.to_account_info(),
             system_program: ctx.accounts.system_program.to_account_info(),
@@ -143,13 +279,13 @@ pub mod dominariworld {
             ctx.accounts.universe.to_account_info(),
             accounts,
             signer_seeds
-        ), components)?;
+        ), components, expected_version)?;
 
         //No need to emit an event, as Universe will do so
         Ok(())
     }
 
-    pub fn req_remove_component(ctx:Context<RemoveComponent>, components: Vec<Pubkey>) -> Result<()> {
+    pub fn req_remove_component(ctx:Context<RemoveComponent>, components: Vec<Pubkey>, expected_version: Option<u64>) -> Result<()> {
         let accounts = ecs::cpi::accounts::RemoveComponent {
             benefactor: ctx.accounts.benefactor.to_account_info(),
             system_program: ctx.accounts.system_program.to_account_info(),
@@ -166,13 +302,13 @@ pub mod dominariworld {
             ctx.accounts.universe.to_account_info(),
             accounts,
             signer_seeds
-        ), components)?;
+        ), components, expected_version)?;
 
         //No need to emit an event, as Universe will do so
         Ok(())
     }
 
-    pub fn req_modify_component(ctx:Context<ModifyComponent>, components: Vec<Pubkey>, data:Vec<Vec<u8>>) -> Result<()> {
+    pub fn req_modify_component(ctx:Context<ModifyComponent>, components: Vec<Pubkey>, data:Vec<Vec<u8>>, expected_version: Option<u64>) -> Result<()> {
         let accounts = ecs::cpi::accounts::ModifyComponent {
             entity: ctx.accounts.entity.to_account_info(),
             world_signer: ctx.accounts.world_config.to_account_info()
@@ -187,7 +323,39 @@ pub mod dominariworld {
             ctx.accounts.universe.to_account_info(),
             accounts,
             signer_seeds
-        ), components, data)?;
+        ), components, data, expected_version)?;
+
+        //No need to emit an event, as Universe will do so
+        Ok(())
+    }
+
+    /**
+     * Modifies components on several entities (passed in as remaining accounts) with a single CPI into the Universe.
+     * modifications[i] is applied to remaining_accounts[i].
+     */
+    pub fn req_modify_components_batch<'info>(ctx:Context<'_, '_, '_, 'info, ModifyComponentsBatch<'info>>, modifications: Vec<EntityModification>) -> Result<()> {
+        // Entities must belong to this world and the instance the system is registered for
+        for entity_info in ctx.remaining_accounts.iter() {
+            let entity: Account<Entity> = Account::try_from(entity_info)?;
+            if entity.world.key() != ctx.program_id.key() || entity.instance != ctx.accounts.system_registration.instance {
+                return err!(WorldError::InvalidEntity)
+            }
+        }
+
+        let accounts = ecs::cpi::accounts::ModifyComponentsBatch {
+            world_signer: ctx.accounts.world_config.to_account_info()
+        };
+        let world_signer_seeds:&[&[u8]] = &[
+            b"world_signer",
+            &[*ctx.bumps.get("world_config").unwrap()]
+        ];
+        let signer_seeds = &[world_signer_seeds];
+        
+        ecs::cpi::modify_components_batch(CpiContext::new_with_signer(
+            ctx.accounts.universe.to_account_info(),
+            accounts,
+            signer_seeds
+        ).with_remaining_accounts(ctx.remaining_accounts.to_vec()), modifications)?;
 
         //No need to emit an event, as Universe will do so
         Ok(())
@@ -216,5 +384,74 @@ pub mod dominariworld {
         Ok(())
     }
 
+    /**
+     * Closes entities (with all their components) and returns the rent to their original payers.
+     * remaining_accounts are (entity, original payer) pairs.
+     */
+    pub fn req_close_entities<'info>(ctx:Context<'_, '_, '_, 'info, CloseEntities<'info>>) -> Result<()> {
+        // Entities must belong to this world and the instance the system is registered for,
+        // and the system needs Close permission on everything they hold
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let entity: Account<Entity> = Account::try_from(&pair[0])?;
+            if entity.world.key() != ctx.program_id.key() || entity.instance != ctx.accounts.system_registration.instance {
+                return err!(WorldError::InvalidEntity)
+            }
+            if !check_sys_registry(&entity.components.keys().cloned().collect(), &ctx.accounts.system_registration.components, Permission::Close) {
+                return err!(WorldError::MissingPermission)
+            }
+        }
+
+        let accounts = ecs::cpi::accounts::CloseEntities {
+            world_signer: ctx.accounts.world_config.to_account_info()
+        };
+        let world_signer_seeds:&[&[u8]] = &[
+            b"world_signer",
+            &[*ctx.bumps.get("world_config").unwrap()]
+        ];
+        let signer_seeds = &[world_signer_seeds];
+        
+        ecs::cpi::close_entities(CpiContext::new_with_signer(
+            ctx.accounts.universe.to_account_info(),
+            accounts,
+            signer_seeds
+        ).with_remaining_accounts(ctx.remaining_accounts.to_vec()))?;
+
+        //No need to emit an event, as Universe will do so
+        Ok(())
+    }
+
+    /**
+     * Mints the 1-of-1 token for an entity in the system's instance. It's up to the system who may tokenize what.
+     */
+    pub fn req_tokenize_entity(ctx:Context<TokenizeEntity>) -> Result<()> {
+        let accounts = ecs::cpi::accounts::TokenizeEntity {
+            payer: ctx.accounts.payer.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+            entity: ctx.accounts.entity.to_account_info(),
+            entity_nft: ctx.accounts.entity_nft.to_account_info(),
+            mint: ctx.accounts.mint.to_account_info(),
+            owner: ctx.accounts.owner.to_account_info(),
+            owner_ata: ctx.accounts.owner_ata.to_account_info(),
+            world_signer: ctx.accounts.world_config.to_account_info()
+        };
+        let world_signer_seeds:&[&[u8]] = &[
+            b"world_signer",
+            &[*ctx.bumps.get("world_config").unwrap()]
+        ];
+        let signer_seeds = &[world_signer_seeds];
+
+        ecs::cpi::tokenize_entity(CpiContext::new_with_signer(
+            ctx.accounts.universe.to_account_info(),
+            accounts,
+            signer_seeds
+        ))?;
+
+        //No need to emit an event, as Universe will do so
+        Ok(())
+    }
+
     
 }
\ No newline at end of file
@@ -1,2 +1,65 @@
-//use anchor_lang::prelude::*;
+use anchor_lang::prelude::*;
 
+/**
+ * Something a system can do with a component
+ * Add/Modify/Remove: the component on an existing entity
+ * Mint/Close: an entity that has the component
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum Permission {
+    Add,
+    Modify,
+    Remove,
+    Mint,
+    Close,
+}
+
+/**
+ * What a registered system is allowed to do with one component, set by the instance authority.
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ComponentPermissions {
+    pub add: bool,
+    pub modify: bool,
+    pub remove: bool,
+    pub mint: bool,
+    pub close: bool,
+}
+
+impl ComponentPermissions {
+    pub const SIZE: usize = 5;
+
+    pub fn all() -> Self {
+        ComponentPermissions { add: true, modify: true, remove: true, mint: true, close: true }
+    }
+
+    pub fn allows(&self, permission: Permission) -> bool {
+        match permission {
+            Permission::Add => self.add,
+            Permission::Modify => self.modify,
+            Permission::Remove => self.remove,
+            Permission::Mint => self.mint,
+            Permission::Close => self.close,
+        }
+    }
+
+    pub fn grant(&mut self, granted: &ComponentPermissions) {
+        self.add |= granted.add;
+        self.modify |= granted.modify;
+        self.remove |= granted.remove;
+        self.mint |= granted.mint;
+        self.close |= granted.close;
+    }
+
+    pub fn revoke(&mut self, revoked: &ComponentPermissions) {
+        self.add &= !revoked.add;
+        self.modify &= !revoked.modify;
+        self.remove &= !revoked.remove;
+        self.mint &= !revoked.mint;
+        self.close &= !revoked.close;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == ComponentPermissions::default()
+    }
+}
@@ -1,6 +1,8 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
 use std::collections::BTreeMap;
 use crate::state::*;
+use crate::error::ComponentError;
 
 
 #[account]
@@ -17,11 +19,33 @@ pub struct Entity {
     pub instance: u64,
     pub world: Pubkey,
     pub world_signer: Pubkey,
+    pub payer: Pubkey, // Paid the rent for the Entity, gets it back when the Entity is closed
+    pub version: u64, // Bumped on every add/remove/modify so clients can tell if they acted on stale state
     pub components: BTreeMap<Pubkey, SerializedComponent>,
 }
 
+impl Entity {
+    /// Fails if the caller expected a different version than the Entity is at
+    pub fn check_version(&self, expected_version: Option<u64>) -> Result<()> {
+        if expected_version.is_some() && expected_version.unwrap() != self.version {
+            return err!(ComponentError::StaleEntity)
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Links an Entity to the 1-of-1 SPL token minted for it by tokenize_entity.
+ */
 #[account] 
 pub struct EntityNFT {
     pub entity: Pubkey,
     pub mint: Pubkey,
+}
+
+impl EntityNFT {
+    /// Whether the token account is wallet's and holds the Entity's token
+    pub fn is_held_by(&self, token_account: &TokenAccount, wallet: &Pubkey) -> bool {
+        token_account.mint == self.mint && token_account.owner == *wallet && token_account.amount == 1
+    }
 }
\ No newline at end of file
@@ -1 +1 @@
-pub const SERIALIZED_COMPONENT_EXTRA_SPACE:u64 = 44;
\ No newline at end of file
+pub const SERIALIZED_COMPONENT_EXTRA_SPACE:u64 = 52; // pubkey (32) + max_size (8) + schema_hash (8) + data vec length (4)
\ No newline at end of file
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use anchor_spl::{token::{Token, Mint, TokenAccount}, associated_token::AssociatedToken};
 use std::collections::BTreeMap;
 
 use crate::account::*;
@@ -48,7 +49,7 @@ pub struct MintEntity<'info>{
     #[account(
         init,
         payer=payer,
-        space=8+8+8+32+32+4+compute_comp_arr_max_size(&components.values().cloned().collect()), //It is expected this will get Realloc'd every time a component is added
+        space=8+8+8+32+32+32+8+4+compute_comp_arr_max_size(&components.values().cloned().collect()), //It is expected this will get Realloc'd every time a component is added
         seeds = [
             b"Entity",
             entity_id.to_be_bytes().as_ref(),
@@ -143,6 +144,13 @@ pub struct ModifyComponent<'info> {
     pub world_signer: Signer<'info>
 }
 
+#[derive(Accounts)]
+#[instruction(modifications: Vec<EntityModification>)]
+pub struct ModifyComponentsBatch<'info> {
+    // Entities are passed in as remaining accounts, each one is checked against this signer
+    pub world_signer: Signer<'info>
+}
+
 #[derive(Accounts)]
 pub struct RemoveEntity<'info>{
     #[account(mut)]
@@ -167,6 +175,74 @@ pub struct RemoveEntity<'info>{
     pub world_signer: Signer<'info>
 }
 
+#[derive(Accounts)]
+pub struct CloseEntities<'info>{
+    // Entities and their original payers are passed in as remaining accounts, each one is checked against this signer
+    pub world_signer: Signer<'info>
+}
+
+#[derive(Accounts)]
+pub struct TokenizeEntity<'info>{
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+
+    #[account(
+        constraint = entity.world_signer.key() == world_signer.key()
+    )]
+    pub entity: Account<'info, Entity>,
+
+    // One NFT per Entity
+    #[account(
+        init,
+        payer=payer,
+        space=8+32+32,
+        seeds = [
+            b"Entity_NFT",
+            entity.key().as_ref()
+        ],
+        bump,
+    )]
+    pub entity_nft: Account<'info, EntityNFT>,
+
+    #[account(
+        init,
+        payer=payer,
+        seeds = [
+            b"Entity_Mint",
+            entity.key().as_ref()
+        ],
+        bump,
+        mint::decimals = 0,
+        mint::authority = entity_nft,
+    )]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: Any wallet can receive the token
+    pub owner: AccountInfo<'info>,
+
+    #[account(
+        init,
+        payer=payer,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_ata: Account<'info, TokenAccount>,
+
+    // Only the Entity's World can tokenize the Entity
+    #[account(
+        seeds = [
+            b"world_signer",
+        ],
+        bump,
+        seeds::program = entity.world.key()
+    )]
+    pub world_signer: Signer<'info>
+}
+
 /************************************************ Utility Functions */
 pub fn compute_comp_arr_max_size(components: &Vec<SerializedComponent>) -> usize {
     let mut max_size:usize = 0;
@@ -4,4 +4,22 @@ use anchor_lang::prelude::*;
 pub enum ComponentError {
     #[msg("Invalid Data Length!")]
     InvalidDataLengthError,
+
+    #[msg("Component not found on Entity!")]
+    ComponentNotFound,
+
+    #[msg("Number of components and data entries don't match!")]
+    ComponentDataMismatch,
+
+    #[msg("Entity doesn't belong to the signing World!")]
+    InvalidWorldSigner,
+
+    #[msg("Entity passed in more than once!")]
+    DuplicateEntity,
+
+    #[msg("Rent can only be returned to the Entity's original payer!")]
+    InvalidPayer,
+
+    #[msg("Entity has changed since the expected version!")]
+    StaleEntity,
 }
\ No newline at end of file
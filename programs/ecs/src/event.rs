@@ -33,3 +33,18 @@ pub struct ComponentModified{
     pub entity: Pubkey,
     pub components: Vec<Pubkey>
 }
+
+
+#[event]
+pub struct EntityTokenized{
+    pub entity: Pubkey,
+    pub mint: Pubkey,
+    pub owner: Pubkey
+}
+
+#[event]
+pub struct EntityClosed{
+    pub entity: Pubkey,
+    pub payer: Pubkey,
+    pub components: Vec<Pubkey>
+}
\ No newline at end of file
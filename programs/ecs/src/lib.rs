@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::{self, MintTo, SetAuthority, spl_token::instruction::AuthorityType};
 use std::collections::BTreeMap;
 
 declare_id!("GN5Ww5qa8ej4evFCJxMhV6AFEPKhD1Drdu8qYYptVgDJ");
@@ -10,10 +11,10 @@ pub mod error;
 pub mod event;
 pub mod state;
 
-//use account::*;
+use account::*;
 use context::*;
 //use constant::*;
-//use error::*;
+use error::*;
 use event::*;
 use state::*;
 
@@ -39,37 +40,81 @@ pub mod ecs {
         // Increment World Instance Entities
         ctx.accounts.world_instance.entities += 1;
 
+        // Components can't be minted with more data than they reserve space for
+        for comp in components.values() {
+            if !comp.fits() {
+                return err!(ComponentError::InvalidDataLengthError)
+            }
+        }
+
         // Set Entity Data
         ctx.accounts.entity.entity_id = entity_id;
         ctx.accounts.entity.world = ctx.accounts.world_instance.world.key();
         ctx.accounts.entity.world_signer = ctx.accounts.world_signer.key();
+        ctx.accounts.entity.payer = ctx.accounts.payer.key();
         ctx.accounts.entity.instance = ctx.accounts.world_instance.instance;
         ctx.accounts.entity.components = components;
         
         Ok(())
     }
     
-    pub fn add_components(ctx:Context<AddComponent>, components:Vec<(Pubkey,SerializedComponent)>) -> Result<()> {
+    pub fn add_components(ctx:Context<AddComponent>, components:Vec<(Pubkey,SerializedComponent)>, expected_version: Option<u64>) -> Result<()> {
+        ctx.accounts.entity.check_version(expected_version)?;
         for comp in components {
+            if !comp.1.fits() {
+                return err!(ComponentError::InvalidDataLengthError)
+            }
             ctx.accounts.entity.components.insert(comp.0, comp.1);
         }
+        ctx.accounts.entity.version += 1;
         
         Ok(())
     }
 
-    pub fn remove_component(ctx:Context<RemoveComponent>, removed_components: Vec<Pubkey>) -> Result<()> {
+    pub fn remove_component(ctx:Context<RemoveComponent>, removed_components: Vec<Pubkey>, expected_version: Option<u64>) -> Result<()> {
+        ctx.accounts.entity.check_version(expected_version)?;
         for comp in removed_components {
             ctx.accounts.entity.components.remove(&comp);
         }   
+        ctx.accounts.entity.version += 1;
 
         Ok(())
     }
 
-    pub fn modify_components(ctx:Context<ModifyComponent>, components: Vec<Pubkey>, data:Vec<Vec<u8>>) -> Result<()> {
-        for (idx, comp) in components.iter().enumerate() {
-            let mut new_comp = ctx.accounts.entity.components.get(comp).unwrap().clone();
-            new_comp.data = data.get(idx).unwrap().clone();
-            ctx.accounts.entity.components.insert(comp.clone(), new_comp);
+    pub fn modify_components(ctx:Context<ModifyComponent>, components: Vec<Pubkey>, data:Vec<Vec<u8>>, expected_version: Option<u64>) -> Result<()> {
+        ctx.accounts.entity.check_version(expected_version)?;
+        apply_modifications(&mut ctx.accounts.entity, &components, &data)?;
+        Ok(())
+    }
+
+    /**
+     * Modifies components on every entity passed in as remaining accounts in one go.
+     * modifications[i] is applied to remaining_accounts[i].
+     */
+    pub fn modify_components_batch<'info>(ctx:Context<'_, '_, '_, 'info, ModifyComponentsBatch<'info>>, modifications: Vec<EntityModification>) -> Result<()> {
+        if modifications.len() != ctx.remaining_accounts.len() {
+            return err!(ComponentError::ComponentDataMismatch)
+        }
+
+        for (idx, entity_info) in ctx.remaining_accounts.iter().enumerate() {
+            if ctx.remaining_accounts[..idx].iter().any(|prev| prev.key() == entity_info.key()) {
+                return err!(ComponentError::DuplicateEntity)
+            }
+
+            let mut entity: Account<Entity> = Account::try_from(entity_info)?;
+            if entity.world_signer.key() != ctx.accounts.world_signer.key() {
+                return err!(ComponentError::InvalidWorldSigner)
+            }
+
+            let modification = modifications.get(idx).unwrap();
+            entity.check_version(modification.expected_version)?;
+            apply_modifications(&mut entity, &modification.components, &modification.data)?;
+            entity.exit(ctx.program_id)?;
+
+            emit!(ComponentModified {
+                entity: entity_info.key(),
+                components: modification.components.clone()
+            });
         }
 
         Ok(())
@@ -79,52 +124,105 @@ pub mod ecs {
         Ok(())
     }
 
+    /**
+     * Strips and closes every entity passed in as remaining accounts, returning the rent to whoever paid for it.
+     * remaining_accounts are (entity, original payer) pairs.
+     */
+    pub fn close_entities<'info>(ctx:Context<'_, '_, '_, 'info, CloseEntities<'info>>) -> Result<()> {
+        if ctx.remaining_accounts.len() % 2 != 0 {
+            return err!(ComponentError::ComponentDataMismatch)
+        }
+
+        for pair in ctx.remaining_accounts.chunks(2) {
+            let entity_info = &pair[0];
+            let payer_info = &pair[1];
+
+            let mut entity: Account<Entity> = Account::try_from(entity_info)?;
+            if entity.world_signer.key() != ctx.accounts.world_signer.key() {
+                return err!(ComponentError::InvalidWorldSigner)
+            }
+            if entity.payer.key() != payer_info.key() {
+                return err!(ComponentError::InvalidPayer)
+            }
+
+            let removed: Vec<Pubkey> = entity.components.keys().cloned().collect();
+            entity.components.clear();
+            entity.close(payer_info.clone())?;
+
+            emit!(EntityClosed {
+                entity: entity_info.key(),
+                payer: payer_info.key(),
+                components: removed
+            });
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Mints a 1-of-1 SPL token for the Entity to owner and records the link in an EntityNFT account.
+     * The mint authority is dropped once the token is minted, so the supply can never grow past one.
+     */
+    pub fn tokenize_entity(ctx:Context<TokenizeEntity>) -> Result<()> {
+        ctx.accounts.entity_nft.entity = ctx.accounts.entity.key();
+        ctx.accounts.entity_nft.mint = ctx.accounts.mint.key();
+
+        let entity_key = ctx.accounts.entity.key();
+        let entity_nft_seeds:&[&[u8]] = &[
+            b"Entity_NFT",
+            entity_key.as_ref(),
+            &[*ctx.bumps.get("entity_nft").unwrap()]
+        ];
+        let signer_seeds = &[entity_nft_seeds];
+
+        token::mint_to(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.owner_ata.to_account_info(),
+                authority: ctx.accounts.entity_nft.to_account_info()
+            },
+            signer_seeds
+        ), 1)?;
+
+        token::set_authority(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SetAuthority {
+                current_authority: ctx.accounts.entity_nft.to_account_info(),
+                account_or_mint: ctx.accounts.mint.to_account_info()
+            },
+            signer_seeds
+        ), AuthorityType::MintTokens, None)?;
+
+        emit!(EntityTokenized {
+            entity: ctx.accounts.entity.key(),
+            mint: ctx.accounts.mint.key(),
+            owner: ctx.accounts.owner.key()
+        });
+
+        Ok(())
+    }
+
 }
 
-/*
-    Entity Mint that's also a SPL Token
-        // Initalize SPL Token
-        let mint_ix = spl_token::instruction::initialize_mint2(
-            &spl_token::ID,
-            &ctx.accounts.mint.key(), 
-            &ctx.accounts.entity_owner.key(), 
-            Some(&ctx.accounts.entity_owner.key()), 
-            1
-        )?;
-        invoke(
-            &mint_ix,
-            &[ctx.accounts.mint.to_account_info()],
-        )?;
-
-        // Create ATA Account
-        let create_ata_ix = spl_associated_token_account::instruction::create_associated_token_account(
-            &ctx.accounts.payer.key(), 
-            &ctx.accounts.entity_owner.key(),
-            &ctx.accounts.mint.key()
-        );
-
-        invoke(
-            &create_ata_ix,
-            &[  
-                ctx.accounts.payer.to_account_info(),
-                ctx.accounts.mint_ata.to_account_info(),
-                ctx.accounts.entity_owner.to_account_info(),
-                ctx.accounts.mint.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-                ctx.accounts.spl_token_program.to_account_info()
-            ]
-        )?;
-
-        // Mint SPL Token (1) to Mint ATA
-        anchor_spl::token::mint_to(
-            CpiContext::new(
-                ctx.accounts.spl_token_program.to_account_info(),
-                anchor_spl::token::MintTo {
-                    mint: ctx.accounts.mint.to_account_info(),
-                    to: ctx.accounts.mint_ata.to_account_info(),
-                    authority: ctx.accounts.entity_owner.to_account_info()
-                }
-            ),
-            1
-        )?;
- */
\ No newline at end of file
+pub fn apply_modifications(entity: &mut Entity, components: &Vec<Pubkey>, data: &Vec<Vec<u8>>) -> Result<()> {
+    if components.len() != data.len() {
+        return err!(ComponentError::ComponentDataMismatch)
+    }
+
+    for (idx, comp) in components.iter().enumerate() {
+        let existing = entity.components.get(comp);
+        if existing.is_none() {
+            return err!(ComponentError::ComponentNotFound)
+        }
+        let mut new_comp = existing.unwrap().clone();
+        new_comp.data = data.get(idx).unwrap().clone();
+        // Modifications must fit in the space reserved when the component was added
+        if !new_comp.fits() {
+            return err!(ComponentError::InvalidDataLengthError)
+        }
+        entity.components.insert(comp.clone(), new_comp);
+    }
+    entity.version += 1;
+    Ok(())
+}
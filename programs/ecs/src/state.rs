@@ -3,5 +3,32 @@ use anchor_lang::prelude::*;
 #[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
 pub struct SerializedComponent{
     pub max_size: u64,
+    pub schema_hash: u64, // Hash of the component layout, lets clients detect drift between the schema and on chain data
     pub data: Vec<u8>,
-}
\ No newline at end of file
+}
+
+impl SerializedComponent {
+    pub fn fits(&self) -> bool {
+        return self.data.len() as u64 <= self.max_size;
+    }
+}
+
+/**
+ * One entity's worth of changes in a batched modify.
+ * Applied to the remaining account at the same position.
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct EntityModification {
+    pub components: Vec<Pubkey>,
+    pub data: Vec<Vec<u8>>,
+    pub expected_version: Option<u64>,
+}
+
+/**
+ * Version a client read an Entity at, passed back in to only act if the Entity hasn't changed since.
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, Debug, Clone)]
+pub struct EntityVersion {
+    pub entity: Pubkey,
+    pub version: u64,
+}
@@ -2,8 +2,9 @@ use std::collections::HashMap;
 use std::collections::BTreeMap;
 use anchor_lang::{prelude::*, InstructionData};
 use anchor_lang::system_program::ID as system_program;
+use anchor_spl::associated_token::get_associated_token_address;
 use dominarisystems::state::RelevantComponentKeys;
-use ecs::state::SerializedComponent;
+use ecs::state::{SerializedComponent, EntityVersion};
 use serde::Deserialize;
 use solana_client_wasm::WasmClient;
 use solana_sdk::instruction::Instruction;
@@ -52,7 +53,7 @@ impl Dominari {
         }]
     }
 
-    pub fn init_map(&self, payer:Pubkey, instance:u64, max_x:u8, max_y:u8) -> Vec<Instruction> {
+    pub fn init_map(&self, payer:Pubkey, instance:u64, max_x:u16, max_y:u16, topology: MapTopology, chunked: bool) -> Vec<Instruction> {
         let world_program = self.world;
         let system_signer = self.get_system_signer();
         let world_config = Pubkey::find_program_address(&[
@@ -105,6 +106,8 @@ impl Dominari {
                 entity_id,
                 max_x,
                 max_y,
+                topology,
+                chunked,
             }.data()
         }]
     }
@@ -134,6 +137,9 @@ impl Dominari {
             world_instance.key().as_ref()
         ], &world_program).0;
 
+        let treasury = self.get_treasury(world_instance);
+        let vault = self.get_vault(world_instance);
+
         vec![Instruction {
             program_id: dominarisystems::id(),
             accounts: dominarisystems::accounts::CreateGameInstance {
@@ -146,6 +152,8 @@ impl Dominari {
                 world_instance,
                 instance_index,
                 instance_authority,
+                treasury,
+                vault,
             }.to_account_metas(None),
             data: dominarisystems::instruction::CreateGameInstance {
                 instance,
@@ -154,7 +162,7 @@ impl Dominari {
         }]
     }
 
-    pub fn init_tile(&self, payer:Pubkey, instance:u64, x:u8, y:u8, cost:u64) -> Vec<Instruction> {
+    pub fn init_tile(&self, payer:Pubkey, instance:u64, x:u16, y:u16, cost:u64, terrain: Option<ComponentTerrain>) -> Vec<Instruction> {
         let world_program = self.world;
         let system_signer = self.get_system_signer();
         let world_config = Pubkey::find_program_address(&[
@@ -207,12 +215,17 @@ impl Dominari {
                 entity_id,
                 x,
                 y,
-                cost
+                cost,
+                terrain
             }.data()
         }]
     }
 
-    pub fn init_feature(&self, payer:Pubkey, instance:u64, tile_id:u64 ,  blueprint: Pubkey) -> Vec<Instruction> {
+    /**
+     * Regions hold the tiles of chunked maps. Their entity ids come from their coordinates (see region::get_region_id).
+     * terrain is per cell, row major from the region's origin, or empty for all plains.
+     */
+    pub fn init_region(&self, payer:Pubkey, instance:u64, map_id:u64, region_x:u16, region_y:u16, terrain: Vec<Option<TerrainType>>) -> Vec<Instruction> {
         let world_program = self.world;
         let system_signer = self.get_system_signer();
         let world_config = Pubkey::find_program_address(&[
@@ -233,12 +246,15 @@ impl Dominari {
             self.get_system_signer().as_ref()
         ], &world_program).0;
 
-        let mut rng = rand::thread_rng();
-        let entity_id:u64 = rng.gen();
+        let map = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            map_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
 
-        let feature_entity = Pubkey::find_program_address(&[
+        let region_entity = Pubkey::find_program_address(&[
             b"Entity".as_ref(),
-            entity_id.to_be_bytes().as_ref(),
+            get_region_id(region_x, region_y).to_be_bytes().as_ref(),
             world_instance.as_ref()
         ], &ecs::id()).0;
 
@@ -247,11 +263,9 @@ impl Dominari {
             world_instance.key().as_ref()
         ], &dominarisystems::id()).0;
 
-        vec![
-        //ComputeBudgetInstruction::set_compute_unit_limit(1_400_000u32),    
-        Instruction{
+        vec![Instruction{
             program_id: dominarisystems::id(),
-            accounts: dominarisystems::accounts::SystemInstanceFeature {
+            accounts: dominarisystems::accounts::SystemInitRegion {
                 payer,
                 system_program,
                 system_signer,
@@ -260,51 +274,28 @@ impl Dominari {
                 universe,
                 system_registration,
                 world_instance,
-                feature_entity,
-                blueprint,
-                tile_entity: Universe::get_keys_from_id(world_instance, vec![tile_id]).get(0).unwrap().clone(),
+                map,
+                region_entity,
                 instance_index
             }.to_account_metas(Some(true)),
-            data: dominarisystems::instruction::SystemInstanceFeature {
-                entity_id,
-            }.data()
-        }]
-    }
-
-    pub fn get_blueprint_key(blueprint: &String) -> Pubkey {
-        Pubkey::find_program_address(&[
-            b"Blueprint",
-            blueprint.as_bytes().as_ref()
-        ], &dominarisystems::id()).0
-    }
-
-    pub async fn register_blueprint(&self,payer:Pubkey, name: String, components: BTreeMap<Pubkey, SerializedComponent>) -> Vec<Instruction> {
-        let system_signer = self.get_system_signer();
-        
-        let blueprint = Dominari::get_blueprint_key(&name);
-        println!("Registering Blueprint {} at Key {}", name, blueprint);
-
-        vec![Instruction {
-            program_id: dominarisystems::id(),
-            accounts: dominarisystems::accounts::RegisterBlueprint {
-                payer,
-                system_program,
-                system_config: system_signer,
-                blueprint
-            }.to_account_metas(None),
-            data: dominarisystems::instruction::RegisterBlueprint {
-                name,
-                components,
+            data: dominarisystems::instruction::SystemInitRegion {
+                region_x,
+                region_y,
+                terrain
             }.data()
         }]
     }
 
-    pub fn init_player(&self, payer:Pubkey, instance: u64, name: String, image: String) -> Vec<Instruction> {
+    /**
+     * The program picks the Feature's id off the Instance Index,
+     * so feature_id has to be next_feature_id() from an up to date Instance Index.
+     */
+    pub fn init_feature(&self, payer:Pubkey, instance:u64, tile_id:u64 ,  blueprint: Pubkey, feature_id: u64) -> Vec<Instruction> {
         let world_program = self.world;
         let system_signer = self.get_system_signer();
         let world_config = Pubkey::find_program_address(&[
             b"world_signer".as_ref(),
-        ], &world_program).0; 
+        ], &world_program).0;
 
         let universe = ecs::id();
         
@@ -320,12 +311,9 @@ impl Dominari {
             self.get_system_signer().as_ref()
         ], &world_program).0;
 
-        let mut rng = rand::thread_rng();
-        let entity_id:u64 = rng.gen();
-
-        let player_entity = Pubkey::find_program_address(&[
+        let feature_entity = Pubkey::find_program_address(&[
             b"Entity".as_ref(),
-            entity_id.to_be_bytes().as_ref(),
+            feature_id.to_be_bytes().as_ref(),
             world_instance.as_ref()
         ], &ecs::id()).0;
 
@@ -333,39 +321,106 @@ impl Dominari {
             b"Instance_Index",
             world_instance.key().as_ref()
         ], &dominarisystems::id()).0;
-        
-        vec![Instruction {
+
+        vec![
+        //ComputeBudgetInstruction::set_compute_unit_limit(1_400_000u32),    
+        Instruction{
             program_id: dominarisystems::id(),
-            accounts: dominarisystems::accounts::SystemInitPlayer {
+            accounts: dominarisystems::accounts::SystemInstanceFeature {
                 payer,
                 system_program,
                 system_signer,
-
                 world_config,
                 world_program,
                 universe,
-
                 system_registration,
                 world_instance,
-
-                player_entity,
+                feature_entity,
+                blueprint,
+                tile_entity: Universe::get_keys_from_id(world_instance, vec![tile_id]).get(0).unwrap().clone(),
+                treasury: self.get_treasury(world_instance),
                 instance_index
             }.to_account_metas(Some(true)),
-            data: dominarisystems::instruction::SystemInitPlayer {
-                entity_id,
-                name, 
-                image
+            data: dominarisystems::instruction::SystemInstanceFeature {}.data()
+        }]
+    }
+
+    /**
+     * Instance Authority takes amount lamports out of the instance treasury.
+     * Wrap with World::with_cosigners when the authority has cosigners.
+     */
+    pub fn withdraw_treasury(&self, payer: Pubkey, instance: u64, amount: u64) -> Vec<Instruction> {
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            self.world.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let instance_authority = Pubkey::find_program_address(&[
+            b"Instance_Authority".as_ref(),
+            world_instance.as_ref()
+        ], &self.world).0;
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts: dominarisystems::accounts::WithdrawTreasury {
+                payer,
+                world_instance,
+                treasury: self.get_treasury(world_instance),
+                instance_authority
+            }.to_account_metas(Some(true)),
+            data: dominarisystems::instruction::WithdrawTreasury {
+                amount
             }.data()
         }]
+    }
 
+    /**
+     * Per instance treasury that tile purchases and feature costs are paid into
+     */
+    pub fn get_treasury(&self, world_instance: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[
+            b"Treasury",
+            world_instance.as_ref()
+        ], &dominarisystems::id()).0
     }
 
-    pub fn change_game_state(&self, payer: Pubkey, instance: u64, player_id: u64, game_state: dominarisystems::account::PlayPhase) -> Vec<Instruction> {
+    pub fn get_vault(&self, world_instance: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[
+            b"Vault",
+            world_instance.as_ref()
+        ], &dominarisystems::id()).0
+    }
+
+    pub fn get_player_seat(&self, world_instance: Pubkey, wallet: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[
+            b"Player_Seat",
+            world_instance.as_ref(),
+            wallet.as_ref()
+        ], &dominarisystems::id()).0
+    }
+
+    // Both PDAs are owned by the Universe and keyed on the Entity's address
+    pub fn get_entity_nft(&self, entity: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[
+            b"Entity_NFT",
+            entity.as_ref()
+        ], &ecs::id()).0
+    }
+
+    pub fn get_entity_mint(&self, entity: Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[
+            b"Entity_Mint",
+            entity.as_ref()
+        ], &ecs::id()).0
+    }
+
+    pub fn buy_tile(&self, payer: Pubkey, instance: u64, player_id: u64, tile_id: u64) -> Vec<Instruction> {
         let world_program = self.world;
         let system_signer = self.get_system_signer();
         let world_config = Pubkey::find_program_address(&[
             b"world_signer".as_ref(),
-        ], &world_program).0;
+        ], &world_program).0; 
 
         let universe = ecs::id();
         
@@ -381,37 +436,76 @@ impl Dominari {
             self.get_system_signer().as_ref()
         ], &world_program).0;
 
+        let tile = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            tile_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let player = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            player_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
         let instance_index = Pubkey::find_program_address(&[
             b"Instance_Index",
             world_instance.key().as_ref()
         ], &dominarisystems::id()).0;
 
-        let map = Universe::get_keys_from_id(world_instance, vec![self.get_gamestate(instance).index.as_ref().unwrap().map]).get(0).unwrap().clone();
-        let player = Universe::get_keys_from_id(world_instance, vec![player_id]).get(0).unwrap().clone();
-
-
         vec![Instruction {
             program_id: dominarisystems::id(),
-            accounts: dominarisystems::accounts::ChangeGameState {
+            accounts: dominarisystems::accounts::BuyTile {
                 payer,
+                system_program,
                 system_signer,
+
                 world_config,
                 world_program,
                 universe,
+
                 system_registration,
                 world_instance,
+
+                tile,
                 player,
-                map,
+                treasury: self.get_treasury(world_instance),
                 instance_index
             }.to_account_metas(Some(true)),
-            data: dominarisystems::instruction::ChangeGameState {
-                game_state
+            data: dominarisystems::instruction::BuyTile {}.data()
+        }]
+    }
+
+    pub fn get_blueprint_key(blueprint: &String) -> Pubkey {
+        Pubkey::find_program_address(&[
+            b"Blueprint",
+            blueprint.as_bytes().as_ref()
+        ], &dominarisystems::id()).0
+    }
+
+    pub async fn register_blueprint(&self,payer:Pubkey, name: String, components: BTreeMap<Pubkey, SerializedComponent>, is_mod: bool) -> Vec<Instruction> {
+        let system_signer = self.get_system_signer();
+        
+        let blueprint = Dominari::get_blueprint_key(&name);
+        println!("Registering Blueprint {} at Key {}", name, blueprint);
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts: dominarisystems::accounts::RegisterBlueprint {
+                payer,
+                system_program,
+                system_config: system_signer,
+                blueprint
+            }.to_account_metas(None),
+            data: dominarisystems::instruction::RegisterBlueprint {
+                name,
+                components,
+                is_mod,
             }.data()
         }]
     }
 
-    // Spawn Unit
-    pub fn spawn_unit(&self, payer:Pubkey, instance:u64, player_id: u64, tile_id:u64, unit_blueprint: Pubkey) -> Vec<Instruction> {
+    pub fn init_player(&self, payer:Pubkey, instance: u64, name: String, image: String) -> Vec<Instruction> {
         let world_program = self.world;
         let system_signer = self.get_system_signer();
         let world_config = Pubkey::find_program_address(&[
@@ -433,23 +527,11 @@ impl Dominari {
         ], &world_program).0;
 
         let mut rng = rand::thread_rng();
-        let unit_id:u64 = rng.gen();
-
-        let unit = Pubkey::find_program_address(&[
-            b"Entity".as_ref(),
-            unit_id.to_be_bytes().as_ref(),
-            world_instance.as_ref()
-        ], &ecs::id()).0;
-
-        let player = Pubkey::find_program_address(&[
-            b"Entity".as_ref(),
-            player_id.to_be_bytes().as_ref(),
-            world_instance.as_ref()
-        ], &ecs::id()).0;
+        let entity_id:u64 = rng.gen();
 
-        let tile = Pubkey::find_program_address(&[
+        let player_entity = Pubkey::find_program_address(&[
             b"Entity".as_ref(),
-            tile_id.to_be_bytes().as_ref(),
+            entity_id.to_be_bytes().as_ref(),
             world_instance.as_ref()
         ], &ecs::id()).0;
 
@@ -457,11 +539,10 @@ impl Dominari {
             b"Instance_Index",
             world_instance.key().as_ref()
         ], &dominarisystems::id()).0;
-
-
+        
         vec![Instruction {
             program_id: dominarisystems::id(),
-            accounts: dominarisystems::accounts::SpawnUnit {
+            accounts: dominarisystems::accounts::SystemInitPlayer {
                 payer,
                 system_program,
                 system_signer,
@@ -473,20 +554,22 @@ impl Dominari {
                 system_registration,
                 world_instance,
 
-                unit,
-                unit_blueprint,
-                player,
-                tile,
+                player_entity,
                 instance_index
             }.to_account_metas(Some(true)),
-            data: dominarisystems::instruction::SpawnUnit {
-                unit_id
+            data: dominarisystems::instruction::SystemInitPlayer {
+                entity_id,
+                name, 
+                image
             }.data()
         }]
+
     }
 
-    // Move Unit
-    pub fn move_unit(&self, payer: Pubkey, instance: u64, from_tile_id: u64, to_tile_id: u64) -> Vec<Instruction> {
+    /**
+     * Takes a seat in the instance for the payer's wallet, paying the entry fee if the game has one
+     */
+    pub fn join_game(&self, payer:Pubkey, instance: u64, name: String, image: String) -> Vec<Instruction> {
         let world_program = self.world;
         let system_signer = self.get_system_signer();
         let world_config = Pubkey::find_program_address(&[
@@ -507,23 +590,12 @@ impl Dominari {
             self.get_system_signer().as_ref()
         ], &world_program).0;
 
-        let unit_id = &self.get_gamestate(instance).get_unit_on_tile(from_tile_id).0;
-        
-        let unit = Pubkey::find_program_address(&[
-            b"Entity".as_ref(),
-            unit_id.unwrap().to_be_bytes().as_ref(),
-            world_instance.as_ref()
-        ], &ecs::id()).0;
-
-        let from = Pubkey::find_program_address(&[
-            b"Entity".as_ref(),
-            from_tile_id.to_be_bytes().as_ref(),
-            world_instance.as_ref()
-        ], &ecs::id()).0;
+        let mut rng = rand::thread_rng();
+        let entity_id:u64 = rng.gen();
 
-        let to = Pubkey::find_program_address(&[
+        let player_entity = Pubkey::find_program_address(&[
             b"Entity".as_ref(),
-            to_tile_id.to_be_bytes().as_ref(),
+            entity_id.to_be_bytes().as_ref(),
             world_instance.as_ref()
         ], &ecs::id()).0;
 
@@ -532,9 +604,12 @@ impl Dominari {
             world_instance.key().as_ref()
         ], &dominarisystems::id()).0;
 
+        let seat = self.get_player_seat(world_instance, payer);
+        let vault = self.get_vault(world_instance);
+        
         vec![Instruction {
             program_id: dominarisystems::id(),
-            accounts: dominarisystems::accounts::MoveUnit {
+            accounts: dominarisystems::accounts::JoinGame {
                 payer,
                 system_program,
                 system_signer,
@@ -546,17 +621,23 @@ impl Dominari {
                 system_registration,
                 world_instance,
 
-                from,
-                to,
-                unit,
+                player_entity,
+                seat,
+                vault,
                 instance_index
             }.to_account_metas(Some(true)),
-            data: dominarisystems::instruction::MoveUnit {}.data()
+            data: dominarisystems::instruction::JoinGame {
+                entity_id,
+                name, 
+                image
+            }.data()
         }]
     }
 
-    // Attack Unit
-    pub fn attack_tile(&self, payer: Pubkey, instance: u64, attacker_id: u64, defender_id: u64, defending_tile_id: u64) -> Vec<Instruction> {
+    /**
+     * Gives up the payer's seat before the game starts, refunding their entry fee
+     */
+    pub fn leave_game(&self, payer:Pubkey, instance: u64, player_id: u64) -> Vec<Instruction> {
         let world_program = self.world;
         let system_signer = self.get_system_signer();
         let world_config = Pubkey::find_program_address(&[
@@ -577,34 +658,20 @@ impl Dominari {
             self.get_system_signer().as_ref()
         ], &world_program).0;
 
-        let attacker = Pubkey::find_program_address(&[
-            b"Entity".as_ref(),
-            attacker_id.to_be_bytes().as_ref(),
-            world_instance.as_ref()
-        ], &ecs::id()).0;
-
-        let defender = Pubkey::find_program_address(&[
-            b"Entity".as_ref(),
-            defender_id.to_be_bytes().as_ref(),
-            world_instance.as_ref()
-        ], &ecs::id()).0;
-
-        let defending_tile = Pubkey::find_program_address(&[
-            b"Entity".as_ref(),
-            defending_tile_id.to_be_bytes().as_ref(),
-            world_instance.as_ref()
-        ], &ecs::id()).0;
+        let player_entity = Universe::get_keys_from_id(world_instance, vec![player_id]).get(0).unwrap().clone();
 
         let instance_index = Pubkey::find_program_address(&[
             b"Instance_Index",
             world_instance.key().as_ref()
         ], &dominarisystems::id()).0;
 
+        let seat = self.get_player_seat(world_instance, payer);
+        let vault = self.get_vault(world_instance);
+
         vec![Instruction {
             program_id: dominarisystems::id(),
-            accounts: dominarisystems::accounts::AttackTile {
+            accounts: dominarisystems::accounts::LeaveGame {
                 payer,
-                system_program,
                 system_signer,
 
                 world_config,
@@ -614,21 +681,984 @@ impl Dominari {
                 system_registration,
                 world_instance,
 
-                attacker,
-                defender,
-                defending_tile,
+                player_entity,
+                seat,
+                vault,
                 instance_index
             }.to_account_metas(Some(true)),
-            data: dominarisystems::instruction::AttackTile {}.data()
+            data: dominarisystems::instruction::LeaveGame {}.data()
         }]
     }
 
-    pub async fn build_gamestate(&mut self, instance:u64) -> &GameState {
-        self.state.insert(instance, GameState::new(self.client.clone(), self.world, instance));
-        self.get_mut_gamestate(instance).load_state().await;
-        self.get_gamestate(instance)
-    }
-
+    /**
+     * Claims the payer's share of the prize pool once the game is Finished
+     */
+    pub fn claim_prize(&self, payer:Pubkey, instance: u64) -> Vec<Instruction> {
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            self.world.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let instance_index = Pubkey::find_program_address(&[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ], &dominarisystems::id()).0;
+
+        let seat = self.get_player_seat(world_instance, payer);
+        let vault = self.get_vault(world_instance);
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts: dominarisystems::accounts::ClaimPrize {
+                payer,
+                world_instance,
+                seat,
+                vault,
+                instance_index
+            }.to_account_metas(Some(true)),
+            data: dominarisystems::instruction::ClaimPrize {}.data()
+        }]
+    }
+
+    pub fn change_game_state(&self, payer: Pubkey, instance: u64, player_id: u64, game_state: dominarisystems::account::PlayPhase) -> Vec<Instruction> {
+        let world_program = self.world;
+        let system_signer = self.get_system_signer();
+        let world_config = Pubkey::find_program_address(&[
+            b"world_signer".as_ref(),
+        ], &world_program).0;
+
+        let universe = ecs::id();
+        
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            world_program.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let system_registration = Pubkey::find_program_address(&[
+            b"System_Registration",
+            world_instance.to_bytes().as_ref(),
+            self.get_system_signer().as_ref()
+        ], &world_program).0;
+
+        let instance_index = Pubkey::find_program_address(&[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ], &dominarisystems::id()).0;
+
+        let map = Universe::get_keys_from_id(world_instance, vec![self.get_gamestate(instance).index.as_ref().unwrap().map]).get(0).unwrap().clone();
+        let player = Universe::get_keys_from_id(world_instance, vec![player_id]).get(0).unwrap().clone();
+
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts: dominarisystems::accounts::ChangeGameState {
+                payer,
+                system_signer,
+                world_config,
+                world_program,
+                universe,
+                system_registration,
+                world_instance,
+                player,
+                map,
+                instance_index
+            }.to_account_metas(Some(true)),
+            data: dominarisystems::instruction::ChangeGameState {
+                game_state
+            }.data()
+        }]
+    }
+
+    /**
+     * Instance authority moves the game between phases: Lobby -> Build -> Play <-> Paused -> Finished
+     * If the instance is governed by a signer set, add the other signers with World::with_cosigners
+     */
+    pub fn set_play_phase(&self, payer: Pubkey, instance: u64, game_state: dominarisystems::account::PlayPhase) -> Vec<Instruction> {
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            self.world.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let instance_index = Pubkey::find_program_address(&[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ], &dominarisystems::id()).0;
+
+        let instance_authority = Pubkey::find_program_address(&[
+            b"Instance_Authority".as_ref(),
+            world_instance.as_ref()
+        ], &self.world).0;
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts: dominarisystems::accounts::SetPlayPhase {
+                payer,
+                world_instance,
+                instance_index,
+                instance_authority
+            }.to_account_metas(Some(true)),
+            data: dominarisystems::instruction::SetPlayPhase {
+                game_state
+            }.data()
+        }]
+    }
+
+    /**
+     * Ends player_id's turn, or skips the active player if their turn has timed out
+     */
+    pub fn end_turn(&self, payer: Pubkey, instance: u64, player_id: u64) -> Vec<Instruction> {
+        let system_signer = self.get_system_signer();
+
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            self.world.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let instance_index = Pubkey::find_program_address(&[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ], &dominarisystems::id()).0;
+
+        let player = Universe::get_keys_from_id(world_instance, vec![player_id]).get(0).unwrap().clone();
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts: dominarisystems::accounts::EndTurn {
+                payer,
+                system_signer,
+                world_instance,
+                player,
+                instance_index
+            }.to_account_metas(Some(true)),
+            data: dominarisystems::instruction::EndTurn {}.data()
+        }]
+    }
+
+    // Spawn Unit
+    /**
+     * On chunked maps tile_id is the Region holding the tile and location picks the tile. None otherwise.
+     */
+    pub fn spawn_unit(&self, payer:Pubkey, instance:u64, player_id: u64, tile_id:u64, unit_blueprint: Pubkey, location: Option<ComponentLocation>) -> Vec<Instruction> {
+        let world_program = self.world;
+        let system_signer = self.get_system_signer();
+        let world_config = Pubkey::find_program_address(&[
+            b"world_signer".as_ref(),
+        ], &world_program).0; 
+
+        let universe = ecs::id();
+        
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            world_program.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let system_registration = Pubkey::find_program_address(&[
+            b"System_Registration",
+            world_instance.to_bytes().as_ref(),
+            self.get_system_signer().as_ref()
+        ], &world_program).0;
+
+        let mut rng = rand::thread_rng();
+        let unit_id:u64 = rng.gen();
+
+        let unit = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            unit_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let player = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            player_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let tile = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            tile_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let instance_index = Pubkey::find_program_address(&[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ], &dominarisystems::id()).0;
+
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts: dominarisystems::accounts::SpawnUnit {
+                payer,
+                system_program,
+                system_signer,
+
+                world_config,
+                world_program,
+                universe,
+
+                system_registration,
+                world_instance,
+
+                unit,
+                unit_blueprint,
+                player,
+                tile,
+                instance_index
+            }.to_account_metas(Some(true)),
+            data: dominarisystems::instruction::SpawnUnit {
+                unit_id,
+                location
+            }.data()
+        }]
+    }
+
+    // Move Unit
+    /**
+     * path is the tiles between from and to, in order (see pathfinding::find_move_path). Empty for a single step.
+     * expected_versions (see GameState::get_expected_versions) makes the move fail if the unit or tiles changed since. Empty to skip the check.
+     */
+    pub fn move_unit(&self, payer: Pubkey, instance: u64, from_tile_id: u64, to_tile_id: u64, path: Vec<u64>, expected_versions: Vec<EntityVersion>) -> Vec<Instruction> {
+        let world_program = self.world;
+        let system_signer = self.get_system_signer();
+        let world_config = Pubkey::find_program_address(&[
+            b"world_signer".as_ref(),
+        ], &world_program).0; 
+
+        let universe = ecs::id();
+        
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            world_program.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let system_registration = Pubkey::find_program_address(&[
+            b"System_Registration",
+            world_instance.to_bytes().as_ref(),
+            self.get_system_signer().as_ref()
+        ], &world_program).0;
+
+        let unit_id = &self.get_gamestate(instance).get_unit_on_tile(from_tile_id).0;
+        
+        let unit = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            unit_id.unwrap().to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let from = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            from_tile_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let to = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            to_tile_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let instance_index = Pubkey::find_program_address(&[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ], &dominarisystems::id()).0;
+
+        let mut accounts = dominarisystems::accounts::MoveUnit {
+            payer,
+            system_program,
+            system_signer,
+
+            world_config,
+            world_program,
+            universe,
+
+            system_registration,
+            world_instance,
+
+            from,
+            to,
+            unit,
+            instance_index
+        }.to_account_metas(Some(true));
+
+        for tile in Universe::get_keys_from_id(world_instance, path) {
+            accounts.push(AccountMeta::new_readonly(tile, false));
+        }
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts,
+            data: dominarisystems::instruction::MoveUnit {
+                expected_versions
+            }.data()
+        }]
+    }
+
+    /**
+     * Moves a unit on a chunked map. path is every tile after the unit's current one, ending at the destination.
+     * expected_versions can pin the unit and any of the Regions passed through.
+     */
+    pub fn move_unit_chunked(&self, payer: Pubkey, instance: u64, unit_id: u64, path: Vec<ComponentLocation>, expected_versions: Vec<EntityVersion>) -> Vec<Instruction> {
+        let world_program = self.world;
+        let system_signer = self.get_system_signer();
+        let world_config = Pubkey::find_program_address(&[
+            b"world_signer".as_ref(),
+        ], &world_program).0; 
+
+        let universe = ecs::id();
+        
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            world_program.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let system_registration = Pubkey::find_program_address(&[
+            b"System_Registration",
+            world_instance.to_bytes().as_ref(),
+            self.get_system_signer().as_ref()
+        ], &world_program).0;
+
+        let unit = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            unit_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let instance_index = Pubkey::find_program_address(&[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ], &dominarisystems::id()).0;
+
+        let mut accounts = dominarisystems::accounts::MoveUnitChunked {
+            payer,
+            system_program,
+            system_signer,
+
+            world_config,
+            world_program,
+            universe,
+
+            system_registration,
+            world_instance,
+
+            unit,
+            instance_index
+        }.to_account_metas(Some(true));
+
+        // Each Region the unit starts in or passes through, once
+        let from = self.get_gamestate(instance).get::<ComponentLocation>(unit_id).unwrap();
+        let mut region_ids: Vec<u64> = vec![];
+        for location in std::iter::once(&from).chain(path.iter()) {
+            let region_id = get_region_id_for(location);
+            if !region_ids.contains(&region_id) {
+                region_ids.push(region_id);
+            }
+        }
+        for region in Universe::get_keys_from_id(world_instance, region_ids) {
+            accounts.push(AccountMeta::new(region, false));
+        }
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts,
+            data: dominarisystems::instruction::MoveUnitChunked {
+                path,
+                expected_versions
+            }.data()
+        }]
+    }
+
+    pub fn play_mod(&self, payer: Pubkey, instance: u64, player_id: u64, unit_id: u64, mod_blueprint: Pubkey) -> Vec<Instruction> {
+        let world_program = self.world;
+        let system_signer = self.get_system_signer();
+        let world_config = Pubkey::find_program_address(&[
+            b"world_signer".as_ref(),
+        ], &world_program).0; 
+
+        let universe = ecs::id();
+        
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            world_program.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let system_registration = Pubkey::find_program_address(&[
+            b"System_Registration",
+            world_instance.to_bytes().as_ref(),
+            self.get_system_signer().as_ref()
+        ], &world_program).0;
+
+        let player = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            player_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let unit = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            unit_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let instance_index = Pubkey::find_program_address(&[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ], &dominarisystems::id()).0;
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts: dominarisystems::accounts::PlayMod {
+                payer,
+                system_program,
+                system_signer,
+
+                world_config,
+                world_program,
+                universe,
+
+                system_registration,
+                world_instance,
+
+                player,
+                unit,
+                mod_blueprint,
+                instance_index
+            }.to_account_metas(Some(true)),
+            data: dominarisystems::instruction::PlayMod {}.data()
+        }]
+    }
+
+    /**
+     * Returns a random seed and the commitment to submit with commit_randomness()
+     * Keep the seed around, it's revealed in attack_tile() against the same defender
+     */
+    pub fn new_randomness_seed(&self, instance: u64, defender_id: u64) -> ([u8; 32], [u8; 32]) {
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            self.world.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let defender = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            defender_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let mut rng = rand::thread_rng();
+        let seed: [u8; 32] = rng.gen();
+        (seed, dominarisystems::randomness::get_commitment(&seed, &defender))
+    }
+
+    pub fn commit_randomness(&self, payer: Pubkey, instance: u64, attacker_id: u64, commitment: [u8; 32]) -> Vec<Instruction> {
+        let world_program = self.world;
+        let system_signer = self.get_system_signer();
+        
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            world_program.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let attacker = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            attacker_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let randomness_commit = Pubkey::find_program_address(&[
+            b"Randomness_Commit",
+            attacker.as_ref()
+        ], &dominarisystems::id()).0;
+
+        let instance_index = Pubkey::find_program_address(&[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ], &dominarisystems::id()).0;
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts: dominarisystems::accounts::CommitRandomness {
+                payer,
+                system_program,
+                system_signer,
+                world_instance,
+                attacker,
+                randomness_commit,
+                instance_index
+            }.to_account_metas(None),
+            data: dominarisystems::instruction::CommitRandomness {
+                commitment
+            }.data()
+        }]
+    }
+
+    /**
+     * Closes a commitment that expired before it was revealed so the unit can commit again.
+     * The unit is marked used, so it has to recover first.
+     */
+    pub fn clear_expired_commitment(&self, payer: Pubkey, instance: u64, attacker_id: u64) -> Vec<Instruction> {
+        let world_program = self.world;
+        let system_signer = self.get_system_signer();
+        let world_config = Pubkey::find_program_address(&[
+            b"world_signer".as_ref(),
+        ], &world_program).0;
+        let universe = ecs::id();
+
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            self.world.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let attacker = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            attacker_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let randomness_commit = Pubkey::find_program_address(&[
+            b"Randomness_Commit",
+            attacker.as_ref()
+        ], &dominarisystems::id()).0;
+
+        let system_registration = Pubkey::find_program_address(&[
+            b"System_Registration",
+            world_instance.to_bytes().as_ref(),
+            system_signer.as_ref()
+        ], &world_program).0;
+
+        let instance_index = Pubkey::find_program_address(&[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ], &dominarisystems::id()).0;
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts: dominarisystems::accounts::ClearExpiredCommitment {
+                payer,
+                system_signer,
+                world_config,
+                world_program,
+                universe,
+                system_registration,
+                world_instance,
+                attacker,
+                randomness_commit,
+                slot_hashes: anchor_lang::solana_program::sysvar::slot_hashes::ID,
+                instance_index
+            }.to_account_metas(None),
+            data: dominarisystems::instruction::ClearExpiredCommitment {}.data()
+        }]
+    }
+
+    // Attack Unit
+    // seed is only needed for games in CommitReveal randomness mode
+    /**
+     * attacking_player_id is the player entity credited on a kill.
+     * Attackers without a player (admin features) can pass their own id.
+     * expected_versions can pin any of the attacker, defender, defending tile and attacking player.
+     * On chunked maps defending_tile_id is the Region holding the defender's tile.
+     */
+    pub fn attack_tile(&self, payer: Pubkey, instance: u64, attacker_id: u64, defender_id: u64, defending_tile_id: u64, attacking_player_id: u64, seed: Option<[u8; 32]>, expected_versions: Vec<EntityVersion>) -> Vec<Instruction> {
+        let world_program = self.world;
+        let system_signer = self.get_system_signer();
+        let world_config = Pubkey::find_program_address(&[
+            b"world_signer".as_ref(),
+        ], &world_program).0; 
+
+        let universe = ecs::id();
+        
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            world_program.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let system_registration = Pubkey::find_program_address(&[
+            b"System_Registration",
+            world_instance.to_bytes().as_ref(),
+            self.get_system_signer().as_ref()
+        ], &world_program).0;
+
+        let attacker = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            attacker_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let defender = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            defender_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let defending_tile = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            defending_tile_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let attacking_player = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            attacking_player_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let instance_index = Pubkey::find_program_address(&[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ], &dominarisystems::id()).0;
+
+        let randomness_commit = Pubkey::find_program_address(&[
+            b"Randomness_Commit",
+            attacker.as_ref()
+        ], &dominarisystems::id()).0;
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts: dominarisystems::accounts::AttackTile {
+                payer,
+                system_program,
+                system_signer,
+
+                world_config,
+                world_program,
+                universe,
+
+                system_registration,
+                world_instance,
+
+                attacker,
+                defender,
+                defending_tile,
+                attacking_player,
+                instance_index,
+                randomness_commit,
+                slot_hashes: anchor_lang::solana_program::sysvar::slot_hashes::ID,
+            }.to_account_metas(Some(true)),
+            data: dominarisystems::instruction::AttackTile {
+                seed,
+                expected_versions
+            }.data()
+        }]
+    }
+
+    /**
+     * Uses the feature on the tile with the unit currently occupying it.
+     * feature_owner is the key in the feature's Owner component and receives the cost of use.
+     * On chunked maps tile_id is the Region holding the tile.
+     */
+    pub fn use_feature(&self, payer: Pubkey, instance: u64, tile_id: u64, unit_id: u64, feature_id: u64, feature_owner: Pubkey) -> Vec<Instruction> {
+        let world_program = self.world;
+        let system_signer = self.get_system_signer();
+        let world_config = Pubkey::find_program_address(&[
+            b"world_signer".as_ref(),
+        ], &world_program).0; 
+
+        let universe = ecs::id();
+        
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            world_program.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let system_registration = Pubkey::find_program_address(&[
+            b"System_Registration",
+            world_instance.to_bytes().as_ref(),
+            self.get_system_signer().as_ref()
+        ], &world_program).0;
+
+        let tile = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            tile_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let feature = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            feature_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let unit = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            unit_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let instance_index = Pubkey::find_program_address(&[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ], &dominarisystems::id()).0;
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts: dominarisystems::accounts::UseFeature {
+                payer,
+                system_program,
+                system_signer,
+
+                world_config,
+                world_program,
+                universe,
+
+                system_registration,
+                world_instance,
+
+                tile,
+                feature,
+                unit,
+                feature_owner,
+                instance_index
+            }.to_account_metas(Some(true)),
+            data: dominarisystems::instruction::UseFeature {}.data()
+        }]
+    }
+
+    pub fn upgrade_feature(&self, payer: Pubkey, instance: u64, feature_id: u64) -> Vec<Instruction> {
+        let world_program = self.world;
+        let system_signer = self.get_system_signer();
+        let world_config = Pubkey::find_program_address(&[
+            b"world_signer".as_ref(),
+        ], &world_program).0; 
+
+        let universe = ecs::id();
+        
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            world_program.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let system_registration = Pubkey::find_program_address(&[
+            b"System_Registration",
+            world_instance.to_bytes().as_ref(),
+            self.get_system_signer().as_ref()
+        ], &world_program).0;
+
+        let feature = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            feature_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let instance_index = Pubkey::find_program_address(&[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ], &dominarisystems::id()).0;
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts: dominarisystems::accounts::UpgradeFeature {
+                payer,
+                system_program,
+                system_signer,
+
+                world_config,
+                world_program,
+                universe,
+
+                system_registration,
+                world_instance,
+
+                feature,
+                instance_index
+            }.to_account_metas(Some(true)),
+            data: dominarisystems::instruction::UpgradeFeature {}.data()
+        }]
+    }
+
+    /**
+     * entities are (Entity ID, original payer) pairs, see GameState::get_reclaimable()
+     */
+    pub fn reclaim_entities(&self, payer: Pubkey, instance: u64, entities: Vec<(u64, Pubkey)>) -> Vec<Instruction> {
+        let world_program = self.world;
+        let system_signer = self.get_system_signer();
+        let world_config = Pubkey::find_program_address(&[
+            b"world_signer".as_ref(),
+        ], &world_program).0; 
+
+        let universe = ecs::id();
+        
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            world_program.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let system_registration = Pubkey::find_program_address(&[
+            b"System_Registration",
+            world_instance.to_bytes().as_ref(),
+            self.get_system_signer().as_ref()
+        ], &world_program).0;
+
+        let instance_index = Pubkey::find_program_address(&[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ], &dominarisystems::id()).0;
+
+        let mut accounts = dominarisystems::accounts::ReclaimEntities {
+            payer,
+            system_signer,
+
+            world_config,
+            world_program,
+            universe,
+
+            system_registration,
+            world_instance,
+
+            instance_index
+        }.to_account_metas(Some(true));
+
+        for (entity_id, entity_payer) in entities {
+            let entity = Pubkey::find_program_address(&[
+                b"Entity".as_ref(),
+                entity_id.to_be_bytes().as_ref(),
+                world_instance.as_ref()
+            ], &ecs::id()).0;
+            accounts.push(AccountMeta::new(entity, false));
+            accounts.push(AccountMeta::new(entity_payer, false));
+        }
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts,
+            data: dominarisystems::instruction::ReclaimEntities {}.data()
+        }]
+    }
+
+    /**
+     * Mints the token for a unit or feature owned by payer into payer's associated token account.
+     */
+    pub fn tokenize_entity(&self, payer: Pubkey, instance: u64, entity_id: u64) -> Vec<Instruction> {
+        let world_program = self.world;
+        let system_signer = self.get_system_signer();
+        let world_config = Pubkey::find_program_address(&[
+            b"world_signer".as_ref(),
+        ], &world_program).0; 
+
+        let universe = ecs::id();
+        
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            world_program.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let system_registration = Pubkey::find_program_address(&[
+            b"System_Registration",
+            world_instance.to_bytes().as_ref(),
+            self.get_system_signer().as_ref()
+        ], &world_program).0;
+
+        let entity = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            entity_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let mint = self.get_entity_mint(entity);
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts: dominarisystems::accounts::TokenizeEntity {
+                payer,
+                system_program,
+                token_program: anchor_spl::token::ID,
+                associated_token_program: anchor_spl::associated_token::ID,
+                rent: anchor_lang::solana_program::sysvar::rent::ID,
+                system_signer,
+                world_config,
+                world_program,
+                universe,
+                system_registration,
+                world_instance,
+                entity,
+                entity_nft: self.get_entity_nft(entity),
+                mint,
+                owner_ata: get_associated_token_address(&payer, &mint),
+            }.to_account_metas(Some(true)),
+            data: dominarisystems::instruction::TokenizeEntity {}.data()
+        }]
+    }
+
+    /**
+     * Takes over a tokenized entity for player_id. payer has to hold the entity's token in their associated token account.
+     */
+    pub fn claim_entity(&self, payer: Pubkey, instance: u64, entity_id: u64, player_id: u64) -> Vec<Instruction> {
+        let world_program = self.world;
+        let system_signer = self.get_system_signer();
+        let world_config = Pubkey::find_program_address(&[
+            b"world_signer".as_ref(),
+        ], &world_program).0; 
+
+        let universe = ecs::id();
+        
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            world_program.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &ecs::id()).0;
+
+        let system_registration = Pubkey::find_program_address(&[
+            b"System_Registration",
+            world_instance.to_bytes().as_ref(),
+            self.get_system_signer().as_ref()
+        ], &world_program).0;
+
+        let entity = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            entity_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let player = Pubkey::find_program_address(&[
+            b"Entity".as_ref(),
+            player_id.to_be_bytes().as_ref(),
+            world_instance.as_ref()
+        ], &ecs::id()).0;
+
+        let instance_index = Pubkey::find_program_address(&[
+            b"Instance_Index",
+            world_instance.key().as_ref()
+        ], &dominarisystems::id()).0;
+
+        vec![Instruction {
+            program_id: dominarisystems::id(),
+            accounts: dominarisystems::accounts::ClaimEntity {
+                payer,
+                system_signer,
+                world_config,
+                world_program,
+                universe,
+                system_registration,
+                world_instance,
+                entity,
+                player,
+                entity_nft: self.get_entity_nft(entity),
+                holder_ata: get_associated_token_address(&payer, &self.get_entity_mint(entity)),
+                instance_index
+            }.to_account_metas(Some(true)),
+            data: dominarisystems::instruction::ClaimEntity {}.data()
+        }]
+    }
+
+    pub async fn build_gamestate(&mut self, instance:u64) -> &GameState {
+        self.state.insert(instance, GameState::new(self.client.clone(), self.world, instance));
+        self.get_mut_gamestate(instance).load_state().await;
+        self.get_gamestate(instance)
+    }
+
     pub fn get_mut_gamestate(&mut self, instance:u64) -> &mut GameState {
         self.state.get_mut(&instance).unwrap()
     }
@@ -671,30 +1701,9 @@ impl ComponentSchema {
         self.schemas.get_by_right(pubkey).unwrap()
     }
 
-    // TODO: Change the names to URLs where each of these components can be found
+    // URLs come from the relevant_components! list in dominarisystems::state
     pub fn get_all_schema_urls() -> Vec<String> {
-        vec![
-            "metadata".to_string(),
-            "mapmeta".to_string(),
-            "location".to_string(),
-            "feature".to_string(),
-            "owner".to_string(),
-            "value".to_string(),
-            "occupant".to_string(),
-            "player_stats".to_string(),
-            "last_used".to_string(),
-            "feature_rank".to_string(),
-            "range".to_string(),
-            "drop_table".to_string(),
-            "uses".to_string(),
-            "healing_power".to_string(),
-            "health".to_string(),
-            "damage".to_string(),
-            "troop_class".to_string(),
-            "active".to_string(),
-            "cost".to_string(),
-            "offchain_metadata".to_string()
-        ]
+        RelevantComponentKeys::get_schema_urls().iter().map(|url| url.to_string()).collect()
     }
 
     pub fn get_all_component_keys(&self) -> Vec<Pubkey> {
@@ -703,34 +1712,15 @@ impl ComponentSchema {
     }
 
     pub fn get_relevant_component_keys(&self) -> dominarisystems::state::RelevantComponentKeys {
-        RelevantComponentKeys {
-            metadata: *self.get_component_pubkey(&"metadata".to_string()),
-            mapmeta: *self.get_component_pubkey(&"mapmeta".to_string()),
-            location: *self.get_component_pubkey(&"location".to_string()),
-            feature: *self.get_component_pubkey(&"feature".to_string()),
-            owner: *self.get_component_pubkey(&"owner".to_string()),
-            value: *self.get_component_pubkey(&"value".to_string()),
-            occupant: *self.get_component_pubkey(&"occupant".to_string()),
-            player_stats: *self.get_component_pubkey(&"player_stats".to_string()),
-            last_used: *self.get_component_pubkey(&"last_used".to_string()),
-            feature_rank: *self.get_component_pubkey(&"feature_rank".to_string()),
-            range: *self.get_component_pubkey(&"range".to_string()),
-            drop_table: *self.get_component_pubkey(&"drop_table".to_string()),
-            uses: *self.get_component_pubkey(&"uses".to_string()),
-            healing_power: *self.get_component_pubkey(&"healing_power".to_string()),
-            health: *self.get_component_pubkey(&"health".to_string()),
-            damage: *self.get_component_pubkey(&"damage".to_string()),
-            troop_class: *self.get_component_pubkey(&"troop_class".to_string()),
-            active: *self.get_component_pubkey(&"active".to_string()),
-            cost: *self.get_component_pubkey(&"cost".to_string()),
-            offchain_metadata: *self.get_component_pubkey(&"offchain_metadata".to_string()),
-        }
+        RelevantComponentKeys::from_schema_urls(|url| *self.get_component_pubkey(&url.to_string()))
     }
     
 }
 
 #[derive(Deserialize, Debug)]
 pub struct BlueprintConfig {
+    #[serde(default)]
+    pub is_mod: bool,
     pub metadata: Option<dominarisystems::component::ComponentMetadata>,
     pub mapmeta: Option<dominarisystems::component::ComponentMapMeta>,
     pub location: Option<dominarisystems::component::ComponentLocation>,
@@ -751,6 +1741,7 @@ pub struct BlueprintConfig {
     pub active: Option<dominarisystems::component::ComponentActive>,
     pub cost: Option<dominarisystems::component::ComponentCost>,
     pub offchain_metadata: Option<dominarisystems::component::ComponentOffchainMetadata>,
+    pub vision: Option<dominarisystems::component::ComponentVision>,
 }
 
 #[derive(Clone, Debug)]
@@ -788,4 +1779,8 @@ impl Blueprint {
 pub use dominarisystems::account::PlayPhase;
 pub use dominarisystems::component::*;
 pub use dominarisystems::state::*;
-pub use dominarisystems::event::*;
\ No newline at end of file
+pub use dominarisystems::event::*;
+pub use dominarisystems::geometry::*;
+pub use dominarisystems::region::*;
+pub use dominarisystems::constant::REGION_SIZE;
+pub use ecs::state::EntityVersion;
\ No newline at end of file
@@ -1,10 +1,11 @@
 
 use std::collections::HashMap;
-use anchor_lang::{prelude::Pubkey, Key, AnchorDeserialize};
-use dominarisystems::{account::InstanceIndex, component::*};
-use ecs::account::Entity;
+use std::ops::{Deref, DerefMut};
+use anchor_lang::{prelude::Pubkey, AnchorSerialize};
+use dominarisystems::{account::{InstanceIndex, PlayPhase}, component::*, region::*};
+use ecs::{account::Entity, state::{SerializedComponent, EntityVersion}};
 use solana_client_wasm::WasmClient;
-use crate::{ util::*, dominari::{ComponentSchema, Blueprint}, universe::Universe};
+use crate::{ util::*, dominari::{ComponentSchema, Blueprint}, universe::Universe, query::EntityQuery, visibility::Visibility};
 
 #[derive(Clone)]
 pub struct GameState {
@@ -83,6 +84,26 @@ impl GameState {
             ).await.get(0).unwrap().1.to_owned()
         );        
         
+        // Chunked maps keep their tiles in Regions, which aren't in the index, so they're looked up by id
+        let map_id = self.index.as_ref().unwrap().map;
+        let mapmeta_key = self.schemas.get_component_pubkey(&ComponentMapMeta::get_schema_url().to_string());
+        let mapmeta: ComponentMapMeta = deserialize_component(entities.get(&map_id).unwrap().components.get(mapmeta_key).unwrap()).unwrap();
+        if mapmeta.chunked {
+            let region_ids: Vec<u64> = get_map_regions(&mapmeta).iter().map(|(x, y)| get_region_id(*x, *y)).collect();
+            let region_keys = Universe::get_keys_from_id(world_instance, region_ids.clone());
+            // RPC caps how many accounts can be fetched at once
+            for (chunk_idx, keys) in region_keys.chunks(100).enumerate() {
+                let accounts = self.client.get_multiple_accounts(&keys.to_vec()).await.unwrap();
+                for (i, account) in accounts.iter().enumerate() {
+                    // Regions that haven't been created yet are skipped
+                    if let Some(account) = account {
+                        let region: Entity = deserialize_account(&account.data).await.unwrap();
+                        entities.insert(region_ids[chunk_idx * 100 + i], region);
+                    }
+                }
+            }
+        }
+
         let tile_entities:Vec<(Pubkey, Entity)> = fetch_accounts::<Entity>(&self.client, &Universe::get_keys_from_id(world_instance, self.index.as_ref().unwrap().tiles.clone())).await;
         for (i, e) in tile_entities.iter().enumerate() {
             entities.insert(*self.index.as_ref().unwrap().tiles.get(i).unwrap(), e.1.to_owned());
@@ -106,14 +127,14 @@ impl GameState {
         self.entities = Some(entities);
     }
   
-    pub fn get_tile(&self, x:u8, y:u8) -> Result<(u64, Entity), &'static str> {
+    pub fn get_tile(&self, x:u16, y:u16) -> Result<(u64, Entity), &'static str> {
         if self.index.is_none() {
             return Err("Game state must be loaded first!");
         }
 
         for id in &self.index.as_ref().unwrap().tiles {
-            let tile = self.entities.as_ref().unwrap().get(&id).unwrap();
-            let location = self.get_entity_location(id).unwrap();
+            let tile = self.get_entity(*id)?;
+            let location = self.get::<ComponentLocation>(*id)?;
             if location.x == x && location.y == y {
                 return Ok((id.clone(), tile.clone()))
             }
@@ -121,145 +142,254 @@ impl GameState {
         Err("Tile Not Found!") 
     }
 
+    /**
+     * Chunked map equivalent of get_tile: returns the id of the Region holding the tile and the tile itself.
+     */
+    pub fn get_region_tile(&self, x:u16, y:u16) -> Result<(u64, RegionTile), &'static str> {
+        let location = ComponentLocation { x, y };
+        let region_id = get_region_id_for(&location);
+        let region = self.get::<ComponentRegion>(region_id)?;
+        match region.get_tile(&location) {
+            Some(tile) => Ok((region_id, tile.clone())),
+            None => Err("Tile Not Found!")
+        }
+    }
+
+    /**
+     * Feature and unit ids on the tile at (x, y), whether the map stores tiles as entities or in Regions.
+     */
+    pub fn get_tile_contents(&self, x:u16, y:u16) -> Result<(Option<u64>, Option<u64>), &'static str> {
+        let index = self.index.as_ref().ok_or("Game state must be loaded first!")?;
+        if self.get::<ComponentMapMeta>(index.map)?.chunked {
+            let (_, tile) = self.get_region_tile(x, y)?;
+            return Ok((tile.feature_id, tile.occupant_id))
+        }
+        let (tile_id, _) = self.get_tile(x, y)?;
+        Ok((self.get_feature_on_tile(tile_id).0, self.get_unit_on_tile(tile_id).0))
+    }
+
     pub fn get_unit_on_tile(&self, tile_id:u64) -> (Option<u64>, Option<Entity>) {
-        let occupant = self.get_entity_occupant(&tile_id).unwrap();
-        if occupant.occupant_id.is_none() {
+        let occupant = self.get::<ComponentOccupant>(tile_id);
+        if occupant.is_err() || occupant.as_ref().unwrap().occupant_id.is_none() {
             return (None, None)
         } else {
-            let entity = (self.entities.as_ref().unwrap().get(&occupant.occupant_id.unwrap())).unwrap().clone();
-            return (occupant.occupant_id, Some(entity))
+            let occupant_id = occupant.unwrap().occupant_id;
+            let entity = self.get_entity(occupant_id.unwrap()).ok().cloned();
+            return (occupant_id, entity)
         }
     }
 
     pub fn get_feature_on_tile(&self, tile_id:u64) -> (Option<u64>, Option<Entity>) {
-        let feature = self.get_entity_feature(&tile_id).unwrap();
-        if feature.feature_id.is_none() {
+        let feature = self.get::<ComponentFeature>(tile_id);
+        if feature.is_err() || feature.as_ref().unwrap().feature_id.is_none() {
             return (None, None)
         } else {
-            let entity = (self.entities.as_ref().unwrap().get(&feature.feature_id.unwrap())).unwrap().clone();
-            return (feature.feature_id, Some(entity))
+            let feature_id = feature.unwrap().feature_id;
+            let entity = self.get_entity(feature_id.unwrap()).ok().cloned();
+            return (feature_id, entity)
         }
     }
 
-    /** COMPONENT GETTERS */
-    pub fn get_entity_metadata(&self, entity_id: &u64) -> Option<ComponentMetadata> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().metadata.key());
-        if sc.is_none() { return None };
-        Some(ComponentMetadata::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
-    }
-    pub fn get_entity_mapmeta(&self, entity_id: &u64) -> Option<ComponentMapMeta> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().mapmeta.key());
-        if sc.is_none() { return None };
-        Some(ComponentMapMeta::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
-    }
-    pub fn get_entity_location(&self, entity_id: &u64) -> Option<ComponentLocation> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().location.key());
-        if sc.is_none() { return None };
-        Some(ComponentLocation::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
-    }
-    pub fn get_entity_feature(&self, entity_id: &u64) -> Option<ComponentFeature> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().feature.key());
-        if sc.is_none() { return None };
-        Some(ComponentFeature::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+    /**
+     * Runs the query against entities already loaded into the game state.
+     * Use EntityQuery::fetch() to query the chain for entities that aren't in any InstanceIndex.
+     */
+    pub fn query(&self, query: &EntityQuery) -> Vec<u64> {
+        let mut ids = vec![];
+        if self.entities.is_none() {
+            return ids;
+        }
+        for (id, entity) in self.entities.as_ref().unwrap().iter() {
+            if query.matches(entity) {
+                ids.push(*id);
+            }
+        }
+        ids
     }
-    pub fn get_entity_owner(&self, entity_id: &u64) -> Option<ComponentOwner> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().owner.key());
-        if sc.is_none() { return None };
-        Some(ComponentOwner::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+
+    pub fn new_query(&self) -> EntityQuery {
+        EntityQuery::new(self.world, self.instance)
     }
-    pub fn get_entity_value(&self, entity_id: &u64) -> Option<ComponentValue> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().value.key());
-        if sc.is_none() { return None };
-        Some(ComponentValue::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+
+    /**
+     * Entities reclaim_entities() will accept: inactive ones, or everything once the game is Finished.
+     * Returns (Entity ID, original payer) pairs.
+     */
+    pub fn get_reclaimable(&self) -> Vec<(u64, Pubkey)> {
+        if self.index.is_none() || self.entities.is_none() {
+            return vec![];
+        }
+        let finished = self.index.as_ref().unwrap().play_phase == PlayPhase::Finished;
+        self.entities.as_ref().unwrap().iter().filter(|(id, _)| {
+            finished || self.get::<ComponentActive>(**id).map(|a| !a.active).unwrap_or(false)
+        }).map(|(id, entity)| (*id, entity.payer)).collect()
     }
-    pub fn get_entity_occupant(&self, entity_id: &u64) -> Option<ComponentOccupant> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().occupant.key());
-        if sc.is_none() { return None };
-        Some(ComponentOccupant::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+
+    /// Drops entities that have been reclaimed on chain from the local state
+    pub fn remove_entities(&mut self, ids: &Vec<u64>) {
+        if let Some(entities) = self.entities.as_mut() {
+            entities.retain(|id, _| !ids.contains(id));
+        }
     }
-    pub fn get_entity_player_stats(&self, entity_id: &u64) -> Option<ComponentPlayerStats> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().player_stats.key());
-        if sc.is_none() { return None };
-        Some(ComponentPlayerStats::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+
+    /**
+     * Copy of the game state as player_id would see it under fog of war.
+     * Units and features outside the player's vision are dropped, and tiles (including Region tiles) stop pointing at them.
+     * Tiles, players and the map stay visible.
+     */
+    pub fn view_for(&self, player_id: u64) -> GameState {
+        let mut view = self.clone();
+        if self.index.is_none() || self.entities.is_none() {
+            return view;
+        }
+
+        let visibility = Visibility::compute(self, player_id);
+        let index = self.index.as_ref().unwrap();
+        let hidden: Vec<u64> = index.units.iter().chain(index.features.iter())
+            .filter(|id| !visibility.can_see_entity(self, **id))
+            .cloned()
+            .collect();
+
+        for tile_id in index.tiles.iter() {
+            if let Ok(mut occupant) = view.get_mut::<ComponentOccupant>(*tile_id) {
+                if occupant.occupant_id.map(|id| hidden.contains(&id)).unwrap_or(false) {
+                    occupant.occupant_id = None;
+                }
+            }
+            if let Ok(mut feature) = view.get_mut::<ComponentFeature>(*tile_id) {
+                if feature.feature_id.map(|id| hidden.contains(&id)).unwrap_or(false) {
+                    feature.feature_id = None;
+                }
+            }
+        }
+
+        let region_ids: Vec<u64> = self.iter::<ComponentRegion>().map(|(id, _)| id).collect();
+        for region_id in region_ids {
+            let mut region = view.get_mut::<ComponentRegion>(region_id).unwrap();
+            for tile in region.tiles.iter_mut().flatten() {
+                if tile.occupant_id.map(|id| hidden.contains(&id)).unwrap_or(false) {
+                    tile.occupant_id = None;
+                }
+                if tile.feature_id.map(|id| hidden.contains(&id)).unwrap_or(false) {
+                    tile.feature_id = None;
+                }
+            }
+        }
+
+        let view_index = view.index.as_mut().unwrap();
+        view_index.units.retain(|id| !hidden.contains(id));
+        view_index.features.retain(|id| !hidden.contains(id));
+        view.remove_entities(&hidden);
+        view
     }
-    pub fn get_entity_last_used(&self, entity_id: &u64) -> Option<ComponentLastUsed> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().last_used.key());
-        if sc.is_none() { return None };
-        Some(ComponentLastUsed::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+
+    pub fn get_visibility(&self, player_id: u64) -> Visibility {
+        Visibility::compute(self, player_id)
     }
-    pub fn get_entity_feature_rank(&self, entity_id: &u64) -> Option<ComponentFeatureRank> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().feature_rank.key());
-        if sc.is_none() { return None };
-        Some(ComponentFeatureRank::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+
+    /** COMPONENT ACCESSORS */
+    pub fn get_entity(&self, entity_id: u64) -> Result<&Entity, &'static str> {
+        if self.entities.is_none() {
+            return Err("Game state must be loaded first!");
+        }
+        self.entities.as_ref().unwrap().get(&entity_id).ok_or("Entity Not Found!")
     }
-    pub fn get_entity_range(&self, entity_id: &u64) -> Option<ComponentRange> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().range.key());
-        if sc.is_none() { return None };
-        Some(ComponentRange::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+
+    /**
+     * Version the entity was at when it was last loaded.
+     */
+    pub fn get_version(&self, entity_id: u64) -> Result<u64, &'static str> {
+        Ok(self.get_entity(entity_id)?.version)
     }
-    pub fn get_entity_drop_table(&self, entity_id: &u64) -> Option<ComponentDropTable> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().drop_table.key());
-        if sc.is_none() { return None };
-        Some(ComponentDropTable::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+
+    /**
+     * Pins the entities at their loaded versions, pass to an action so it fails if any of them changed since.
+     */
+    pub fn get_expected_versions(&self, entity_ids: &Vec<u64>) -> Result<Vec<EntityVersion>, &'static str> {
+        let world_instance = Universe::get_world_instance(self.world, self.instance);
+        let keys = Universe::get_keys_from_id(world_instance, entity_ids.clone());
+        let mut expected_versions = vec![];
+        for (id, entity) in entity_ids.iter().zip(keys) {
+            expected_versions.push(EntityVersion {
+                entity,
+                version: self.get_version(*id)?
+            });
+        }
+        Ok(expected_versions)
     }
-    pub fn get_entity_uses(&self, entity_id: &u64) -> Option<ComponentUses> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().uses.key());
-        if sc.is_none() { return None };
-        Some(ComponentUses::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+
+    pub fn has<T: Component>(&self, entity_id: u64) -> bool {
+        let key = match self.schemas.key_index.as_ref() {
+            Some(key_index) => T::get_component_key(key_index),
+            None => return false
+        };
+        match self.get_entity(entity_id) {
+            Ok(entity) => entity.components.contains_key(&key),
+            Err(_) => false
+        }
     }
-    pub fn get_entity_healing_power(&self, entity_id: &u64) -> Option<ComponentHealingPower> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().healing_power.key());
-        if sc.is_none() { return None };
-        Some(ComponentHealingPower::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+
+    pub fn get<T: Component>(&self, entity_id: u64) -> Result<T, &'static str> {
+        let key = T::get_component_key(self.schemas.key_index.as_ref().ok_or("Schemas must be loaded first!")?);
+        let sc = self.get_entity(entity_id)?.components.get(&key).ok_or("Component Not Found!")?;
+        deserialize_component::<T>(sc)
     }
-    pub fn get_entity_health(&self, entity_id: &u64) -> Option<ComponentHealth> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().health.key());
-        if sc.is_none() { return None };
-        Some(ComponentHealth::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+
+    /**
+     * Returns a guard that derefs to the component and writes it back into the local entity when dropped.
+     * Only changes the local copy; the chain still needs a transaction.
+     */
+    pub fn get_mut<T: Component>(&mut self, entity_id: u64) -> Result<ComponentMut<T>, &'static str> {
+        let key = T::get_component_key(self.schemas.key_index.as_ref().ok_or("Schemas must be loaded first!")?);
+        if self.entities.is_none() {
+            return Err("Game state must be loaded first!");
+        }
+        let entity = self.entities.as_mut().unwrap().get_mut(&entity_id).ok_or("Entity Not Found!")?;
+        let sc = entity.components.get_mut(&key).ok_or("Component Not Found!")?;
+        let value = deserialize_component::<T>(sc)?;
+        Ok(ComponentMut { sc, value })
     }
-    pub fn get_entity_damage(&self, entity_id: &u64) -> Option<ComponentDamage> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().damage.key());
-        if sc.is_none() { return None };
-        Some(ComponentDamage::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+
+    /// Every loaded entity that has the component, skipping components whose schema has drifted.
+    /// Empty until the schemas are loaded.
+    pub fn iter<T: Component + 'static>(&self) -> impl Iterator<Item = (u64, T)> + '_ {
+        let key = self.schemas.key_index.as_ref().map(|key_index| T::get_component_key(key_index));
+        key.into_iter().flat_map(move |key| {
+            self.entities.iter().flat_map(|entities| entities.iter()).filter_map(move |(id, entity)| {
+                let component = deserialize_component::<T>(entity.components.get(&key)?).ok()?;
+                Some((*id, component))
+            })
+        })
     }
-    pub fn get_entity_troop_class(&self, entity_id: &u64) -> Option<ComponentTroopClass> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().troop_class.key());
-        if sc.is_none() { return None };
-        Some(ComponentTroopClass::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+}
+
+pub fn deserialize_component<T: Component>(sc: &SerializedComponent) -> Result<T, &'static str> {
+    if sc.schema_hash != T::get_schema_hash() {
+        return Err("Component schema doesn't match on chain data!");
     }
-    pub fn get_entity_active(&self, entity_id: &u64) -> Option<ComponentActive> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().active.key());
-        if sc.is_none() { return None };
-        Some(ComponentActive::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+    T::try_from_slice(&sc.data.as_slice()).map_err(|_| "Component failed to deserialize!")
+}
+
+pub struct ComponentMut<'a, T: Component> {
+    sc: &'a mut SerializedComponent,
+    value: T,
+}
+
+impl<'a, T: Component> Deref for ComponentMut<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.value
     }
-    pub fn get_entity_cost(&self, entity_id: &u64) -> Option<ComponentCost> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().cost.key());
-        if sc.is_none() { return None };
-        Some(ComponentCost::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+}
+
+impl<'a, T: Component> DerefMut for ComponentMut<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
     }
-    pub fn get_entity_offchain_metadata(&self, entity_id: &u64) -> Option<ComponentOffchainMetadata> {
-        let serialized_components = &self.entities.as_ref().unwrap().get(&entity_id).unwrap().components;
-        let sc = serialized_components.get(&self.schemas.key_index.as_ref().unwrap().offchain_metadata.key());
-        if sc.is_none() { return None };
-        Some(ComponentOffchainMetadata::try_from_slice(&sc.unwrap().data.as_slice()).unwrap())
+}
+
+impl<'a, T: Component> Drop for ComponentMut<'a, T> {
+    fn drop(&mut self) {
+        self.sc.data = self.value.try_to_vec().unwrap();
     }
-}
\ No newline at end of file
+}
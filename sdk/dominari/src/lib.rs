@@ -3,6 +3,9 @@ pub mod world;
 pub mod dominari;
 pub mod util;
 pub mod gamestate;
+pub mod query;
+pub mod visibility;
+pub mod pathfinding;
 
 // Export Solana Client so no need to reimport it
 pub use solana_client_wasm::solana_sdk;
\ No newline at end of file
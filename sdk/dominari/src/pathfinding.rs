@@ -0,0 +1,225 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use dominarisystems::component::*;
+use dominarisystems::geometry::{get_distance, is_adjacent, GridMetric};
+
+use crate::gamestate::GameState;
+
+/**
+ * Movement spent stepping onto a tile, mirroring move_unit: 1 plus the terrain's cost for the class.
+ * None if the tile can't be entered (impassable terrain or occupied).
+ */
+pub fn get_step_cost(state: &GameState, tile_id: u64, class: &TroopClass) -> Option<u64> {
+    if state.get_unit_on_tile(tile_id).0.is_some() {
+        return None;
+    }
+    match state.get::<ComponentTerrain>(tile_id) {
+        Ok(terrain) => terrain.get_move_cost(class).map(|cost| 1 + cost),
+        Err(_) => Some(1)
+    }
+}
+
+/// Same as get_step_cost for a tile in a Region on a chunked map
+pub fn get_region_step_cost(tile: &RegionTile, class: &TroopClass) -> Option<u64> {
+    if tile.occupant_id.is_some() {
+        return None;
+    }
+    match &tile.terrain {
+        Some(terrain) => ComponentTerrain::from_type(terrain.clone()).get_move_cost(class).map(|cost| 1 + cost),
+        None => Some(1)
+    }
+}
+
+/**
+ * A* between two locations. step_cost gives the cost of entering a location, or None if it can't be entered.
+ * Steps go to locations adjacent under the metric. Returns the full path (start and goal included) and its cost.
+ */
+fn search(metric: &GridMetric, start: (u16, u16), goal: (u16, u16), step_cost: impl Fn((u16, u16)) -> Option<u64>) -> Option<(Vec<(u16, u16)>, u64)> {
+    let goal_location = ComponentLocation { x: goal.0, y: goal.1 };
+    // Every step costs at least 1 and covers a distance of 1, so the metric's distance never overestimates
+    let heuristic = |x: u16, y: u16| -> u64 {
+        get_distance(metric, &ComponentLocation { x, y }, &goal_location)
+    };
+
+    let mut open: BinaryHeap<Reverse<(u64, u64, u16, u16)>> = BinaryHeap::new(); // (estimate, cost, x, y)
+    let mut came_from: HashMap<(u16, u16), (u16, u16)> = HashMap::new();
+    let mut best_cost: HashMap<(u16, u16), u64> = HashMap::new();
+    best_cost.insert(start, 0);
+    open.push(Reverse((heuristic(start.0, start.1), 0, start.0, start.1)));
+
+    while let Some(Reverse((_, cost, x, y))) = open.pop() {
+        if (x, y) == goal {
+            let mut path = vec![goal];
+            let mut current = (x, y);
+            while let Some(previous) = came_from.get(&current) {
+                path.push(*previous);
+                current = *previous;
+            }
+            path.reverse();
+            return Some((path, cost));
+        }
+        if cost > *best_cost.get(&(x, y)).unwrap_or(&u64::MAX) {
+            continue;
+        }
+
+        for dx in -1_i32..=1 {
+            for dy in -1_i32..=1 {
+                if dx == 0 && dy == 0 {
+                    continue;
+                }
+                let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+                if nx < 0 || ny < 0 || nx > u16::MAX as i32 || ny > u16::MAX as i32 {
+                    continue;
+                }
+                let next = (nx as u16, ny as u16);
+                if !is_adjacent(metric, &ComponentLocation { x, y }, &ComponentLocation { x: next.0, y: next.1 }) {
+                    continue;
+                }
+                let step = match step_cost(next) {
+                    Some(step) => step,
+                    None => continue
+                };
+                let next_cost = cost + step;
+                if next_cost < *best_cost.get(&next).unwrap_or(&u64::MAX) {
+                    best_cost.insert(next, next_cost);
+                    came_from.insert(next, (x, y));
+                    open.push(Reverse((next_cost + heuristic(next.0, next.1), next_cost, next.0, next.1)));
+                }
+            }
+        }
+    }
+    None
+}
+
+/**
+ * A* over the loaded tiles from one tile to another for a unit of the given class.
+ * Steps go to tiles adjacent under the instance's grid metric. Returns the full path (from and to included) and its cost.
+ */
+pub fn find_path(state: &GameState, from_tile: u64, to_tile: u64, class: &TroopClass) -> Result<(Vec<u64>, u64), &'static str> {
+    if state.index.is_none() {
+        return Err("Game state must be loaded first!");
+    }
+
+    let mut positions: HashMap<(u16, u16), u64> = HashMap::new();
+    for tile_id in state.index.as_ref().unwrap().tiles.iter() {
+        let location = state.get::<ComponentLocation>(*tile_id)?;
+        positions.insert((location.x, location.y), *tile_id);
+    }
+    let goal = state.get::<ComponentLocation>(to_tile)?;
+    let start = state.get::<ComponentLocation>(from_tile)?;
+
+    let metric = &state.index.as_ref().unwrap().config.grid_metric;
+    let step_cost = |location: (u16, u16)| -> Option<u64> {
+        get_step_cost(state, *positions.get(&location)?, class)
+    };
+    let (path, cost) = search(metric, (start.x, start.y), (goal.x, goal.y), step_cost).ok_or("No path found!")?;
+    Ok((path.iter().map(|location| *positions.get(location).unwrap()).collect(), cost))
+}
+
+/**
+ * Path for moving the unit on from_tile to to_tile, as the intermediate tiles move_unit expects.
+ * Fails if there's no path within the unit's movement.
+ */
+pub fn find_move_path(state: &GameState, from_tile: u64, to_tile: u64) -> Result<Vec<u64>, &'static str> {
+    if from_tile == to_tile {
+        return Err("Unit is already there!");
+    }
+    let unit_id = state.get_unit_on_tile(from_tile).0.ok_or("No unit on tile!")?;
+    let class = state.get::<ComponentTroopClass>(unit_id)?.class;
+    let movement = state.get::<ComponentRange>(unit_id)?.movement;
+
+    let (path, cost) = find_path(state, from_tile, to_tile, &class)?;
+    if cost > movement {
+        return Err("Unit lacks the movement to get there!");
+    }
+    Ok(path[1..path.len() - 1].to_vec())
+}
+
+/**
+ * Chunked map equivalent of find_move_path: the path move_unit_chunked expects for moving a unit to a location,
+ * every tile after the unit's current one up to and including the destination.
+ */
+pub fn find_region_move_path(state: &GameState, unit_id: u64, to: &ComponentLocation) -> Result<Vec<ComponentLocation>, &'static str> {
+    if state.index.is_none() {
+        return Err("Game state must be loaded first!");
+    }
+    let from = state.get::<ComponentLocation>(unit_id)?;
+    if (from.x, from.y) == (to.x, to.y) {
+        return Err("Unit is already there!");
+    }
+    let class = state.get::<ComponentTroopClass>(unit_id)?.class;
+    let movement = state.get::<ComponentRange>(unit_id)?.movement;
+
+    let metric = &state.index.as_ref().unwrap().config.grid_metric;
+    let step_cost = |location: (u16, u16)| -> Option<u64> {
+        let (_, tile) = state.get_region_tile(location.0, location.1).ok()?;
+        get_region_step_cost(&tile, &class)
+    };
+    let (path, cost) = search(metric, (from.x, from.y), (to.x, to.y), step_cost).ok_or("No path found!")?;
+    if cost > movement {
+        return Err("Unit lacks the movement to get there!");
+    }
+    Ok(path[1..].iter().map(|(x, y)| ComponentLocation { x: *x, y: *y }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use super::*;
+
+    fn open_grid(size: u16) -> impl Fn((u16, u16)) -> Option<u64> {
+        move |(x, y)| if x < size && y < size { Some(1) } else { None }
+    }
+
+    #[test]
+    fn straight_line_on_an_open_grid() {
+        let (path, cost) = search(&GridMetric::Manhattan, (0, 0), (3, 0), open_grid(5)).unwrap();
+        assert_eq!(path, vec![(0, 0), (1, 0), (2, 0), (3, 0)]);
+        assert_eq!(cost, 3);
+    }
+
+    #[test]
+    fn chebyshev_moves_diagonally() {
+        let (path, cost) = search(&GridMetric::Chebyshev, (0, 0), (2, 2), open_grid(5)).unwrap();
+        assert_eq!(path, vec![(0, 0), (1, 1), (2, 2)]);
+        assert_eq!(cost, 2);
+    }
+
+    #[test]
+    fn routes_around_walls() {
+        // Wall down x = 1 except at y = 3
+        let walls: HashSet<(u16, u16)> = [(1, 0), (1, 1), (1, 2)].into_iter().collect();
+        let step_cost = |location: (u16, u16)| if walls.contains(&location) || location.0 > 4 || location.1 > 4 { None } else { Some(1) };
+        let (path, cost) = search(&GridMetric::Manhattan, (0, 0), (2, 0), step_cost).unwrap();
+        assert!(path.iter().all(|location| !walls.contains(location)));
+        assert_eq!(cost, 8);
+    }
+
+    #[test]
+    fn prefers_cheaper_terrain_over_fewer_steps() {
+        // Forest-like tile straight ahead costs 5, going around costs 3 more steps
+        let step_cost = |location: (u16, u16)| match location {
+            (1, 0) => Some(5),
+            (x, y) if x < 3 && y < 3 => Some(1),
+            _ => None
+        };
+        let (path, cost) = search(&GridMetric::Manhattan, (0, 0), (2, 0), step_cost).unwrap();
+        assert!(!path.contains(&(1, 0)));
+        assert_eq!(cost, 4);
+    }
+
+    #[test]
+    fn no_path_when_goal_is_walled_off() {
+        let step_cost = |location: (u16, u16)| if location == (0, 0) || location == (2, 2) { Some(1) } else { None };
+        assert!(search(&GridMetric::Manhattan, (0, 0), (2, 2), step_cost).is_none());
+    }
+
+    #[test]
+    fn occupied_or_impassable_region_tiles_block() {
+        let water = RegionTile { terrain: Some(TerrainType::Water), feature_id: None, occupant_id: None };
+        assert_eq!(get_region_step_cost(&water, &TroopClass::Infantry), None);
+        assert_eq!(get_region_step_cost(&water, &TroopClass::Aircraft), Some(1));
+        let occupied = RegionTile { terrain: None, feature_id: None, occupant_id: Some(7) };
+        assert_eq!(get_region_step_cost(&occupied, &TroopClass::Aircraft), None);
+    }
+}
@@ -0,0 +1,137 @@
+use anchor_lang::{prelude::Pubkey, AccountDeserialize, Discriminator};
+use ecs::{account::Entity, state::SerializedComponent};
+use solana_client_wasm::{
+    WasmClient,
+    utils::{
+        rpc_config::RpcProgramAccountsConfig,
+        rpc_filter::{RpcFilterType, Memcmp, MemcmpEncodedBytes},
+    },
+};
+
+// Byte offsets into the ecs::account::Entity layout (after the 8 byte discriminator)
+pub const ENTITY_INSTANCE_OFFSET: usize = 8 + 8;
+pub const ENTITY_WORLD_OFFSET: usize = 8 + 8 + 8;
+
+pub type ComponentPredicate = Box<dyn Fn(&SerializedComponent) -> bool>;
+
+/**
+ * Finds entities by the set of components they carry.
+ * World and instance are filtered on chain with memcmp, everything else is filtered in memory
+ * because the components BTreeMap has no fixed offsets.
+ */
+pub struct EntityQuery {
+    pub world: Pubkey,
+    pub instance: u64,
+    pub with: Vec<Pubkey>,
+    pub without: Vec<Pubkey>,
+    pub predicates: Vec<(Pubkey, ComponentPredicate)>,
+}
+
+impl EntityQuery {
+    pub fn new(world: Pubkey, instance: u64) -> Self {
+        EntityQuery {
+            world,
+            instance,
+            with: vec![],
+            without: vec![],
+            predicates: vec![],
+        }
+    }
+
+    /// Entity must have this component
+    pub fn with(mut self, component: Pubkey) -> Self {
+        self.with.push(component);
+        self
+    }
+
+    /// Entity must NOT have this component
+    pub fn without(mut self, component: Pubkey) -> Self {
+        self.without.push(component);
+        self
+    }
+
+    /// Entity must have this component AND the predicate must return true for it
+    pub fn filter(mut self, component: Pubkey, predicate: impl Fn(&SerializedComponent) -> bool + 'static) -> Self {
+        self.predicates.push((component, Box::new(predicate)));
+        self
+    }
+
+    pub fn matches(&self, entity: &Entity) -> bool {
+        if entity.world != self.world || entity.instance != self.instance {
+            return false;
+        }
+
+        for comp in self.with.iter() {
+            if !entity.components.contains_key(comp) {
+                return false;
+            }
+        }
+
+        for comp in self.without.iter() {
+            if entity.components.contains_key(comp) {
+                return false;
+            }
+        }
+
+        for (comp, predicate) in self.predicates.iter() {
+            match entity.components.get(comp) {
+                Some(sc) => {
+                    if !predicate(sc) {
+                        return false;
+                    }
+                },
+                None => return false,
+            }
+        }
+        true
+    }
+
+    pub fn get_rpc_filters(&self) -> Vec<RpcFilterType> {
+        vec![
+            RpcFilterType::Memcmp(Memcmp {
+                offset: 0,
+                bytes: MemcmpEncodedBytes::Base58(bs58::encode(Entity::discriminator()).into_string()),
+                encoding: None,
+            }),
+            RpcFilterType::Memcmp(Memcmp {
+                offset: ENTITY_INSTANCE_OFFSET,
+                bytes: MemcmpEncodedBytes::Base58(bs58::encode(self.instance.to_le_bytes()).into_string()),
+                encoding: None,
+            }),
+            RpcFilterType::Memcmp(Memcmp {
+                offset: ENTITY_WORLD_OFFSET,
+                bytes: MemcmpEncodedBytes::Base58(self.world.to_string()),
+                encoding: None,
+            }),
+        ]
+    }
+
+    /**
+     * Fetches every entity of the world instance from the Universe program and returns the ones that match.
+     * Returns (Entity Address, Entity) pairs; the entity id is on the Entity itself.
+     * Fails if the RPC call does.
+     */
+    pub async fn fetch(&self, client: &WasmClient) -> Result<Vec<(Pubkey, Entity)>, &'static str> {
+        let accounts = client.get_program_accounts_with_config(
+            &ecs::id(),
+            RpcProgramAccountsConfig {
+                filters: Some(self.get_rpc_filters()),
+                ..Default::default()
+            }
+        ).await.map_err(|_| "Failed to fetch entities from RPC!")?;
+
+        let mut results = vec![];
+        for (pubkey, account) in accounts.iter() {
+            let mut data: &[u8] = &account.data;
+            let entity = Entity::try_deserialize(&mut data);
+            if entity.is_err() {
+                continue;
+            }
+            let entity = entity.unwrap();
+            if self.matches(&entity) {
+                results.push((*pubkey, entity));
+            }
+        }
+        Ok(results)
+    }
+}
@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+use dominarisystems::component::*;
+use dominarisystems::geometry::get_distance;
+
+use crate::gamestate::GameState;
+
+/**
+ * Tiles a player can currently see.
+ * Vision comes from every active unit and feature the player owns, out to its Vision range
+ * (entities without a Vision component only see their own tile).
+ * Distance uses the instance's grid metric, same as movement and attack range.
+ */
+#[derive(Clone, Debug)]
+pub struct Visibility {
+    pub player: u64,
+    pub visible: HashSet<(u16, u16)>,
+}
+
+impl Visibility {
+    pub fn compute(state: &GameState, player_id: u64) -> Self {
+        let mut visible = HashSet::new();
+        if state.index.is_none() || state.entities.is_none() {
+            return Visibility { player: player_id, visible };
+        }
+
+        let metric = &state.index.as_ref().unwrap().config.grid_metric;
+        // (location, vision range) for everything the player can see from
+        let mut sources: Vec<(ComponentLocation, u64)> = vec![];
+        for (id, owner) in state.iter::<ComponentOwner>() {
+            if owner.player != Some(player_id) {
+                continue;
+            }
+            if state.get::<ComponentActive>(id).map(|a| !a.active).unwrap_or(false) {
+                continue;
+            }
+            if let Ok(location) = state.get::<ComponentLocation>(id) {
+                let range = state.get::<ComponentVision>(id).map(|v| v.range).unwrap_or(0);
+                sources.push((location, range));
+            }
+        }
+
+        // Standalone tiles, plus the tiles packed into Regions on chunked maps
+        let mut locations: Vec<ComponentLocation> = state.index.as_ref().unwrap().tiles.iter()
+            .filter_map(|tile_id| state.get::<ComponentLocation>(*tile_id).ok())
+            .collect();
+        for (_, region) in state.iter::<ComponentRegion>() {
+            locations.extend(region.iter_tiles().map(|(location, _)| location));
+        }
+
+        for location in locations {
+            let seen = sources.iter().any(|(source, range)| {
+                get_distance(metric, source, &location) <= *range
+            });
+            if seen {
+                visible.insert((location.x, location.y));
+            }
+        }
+
+        Visibility { player: player_id, visible }
+    }
+
+    pub fn can_see(&self, x: u16, y: u16) -> bool {
+        self.visible.contains(&(x, y))
+    }
+
+    /**
+     * Players always see their own entities. Entities without a location (map, players) are public.
+     */
+    pub fn can_see_entity(&self, state: &GameState, entity_id: u64) -> bool {
+        if let Ok(owner) = state.get::<ComponentOwner>(entity_id) {
+            if owner.player == Some(self.player) {
+                return true;
+            }
+        }
+        match state.get::<ComponentLocation>(entity_id) {
+            Ok(location) => self.can_see(location.x, location.y),
+            Err(_) => true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+    use anchor_lang::prelude::Pubkey;
+    use dominarisystems::account::{InstanceIndex, PlayPhase};
+    use dominarisystems::geometry::GridMetric;
+    use dominarisystems::state::{GameConfig, RandomnessMode, VictoryConditions};
+    use ecs::account::Entity;
+    use ecs::state::SerializedComponent;
+    use solana_client_wasm::WasmClient;
+    use super::*;
+
+    fn entity(entity_id: u64, components: Vec<(Pubkey, SerializedComponent)>) -> Entity {
+        Entity {
+            entity_id,
+            instance: 0,
+            world: Pubkey::default(),
+            world_signer: Pubkey::default(),
+            payer: Pubkey::default(),
+            version: 0,
+            components: components.into_iter().collect::<BTreeMap<_, _>>(),
+        }
+    }
+
+    /**
+     * 5x1 strip of tiles (ids 1..=5 at x 0..=4) under Manhattan distance.
+     * Player 100 has a unit (id 10) with vision 1 at x 0, player 200 an unseen unit (id 20) at x 4.
+     */
+    fn state() -> GameState {
+        let mut state = GameState::new(WasmClient::new("http://localhost:8899"), Pubkey::new_unique(), 0);
+        let keys = state.schemas.key_index.clone().unwrap();
+        let location = |x: u16| (keys.location, ComponentLocation { x, y: 0 }.to_serialized_component());
+        let owner = |player: u64| (keys.owner, ComponentOwner { owner: None, player: Some(player) }.to_serialized_component());
+
+        let mut entities = HashMap::new();
+        for x in 0..5 {
+            entities.insert(x as u64 + 1, entity(x as u64 + 1, vec![location(x)]));
+        }
+        entities.insert(10, entity(10, vec![location(0), owner(100), (keys.vision, ComponentVision { range: 1 }.to_serialized_component())]));
+        entities.insert(20, entity(20, vec![location(4), owner(200)]));
+        state.entities = Some(entities);
+
+        state.index = Some(InstanceIndex {
+            config: GameConfig {
+                max_players: 2,
+                starting_cards: vec![],
+                randomness: RandomnessMode::SlotHashes,
+                turns: None,
+                victory: VictoryConditions {
+                    last_player_standing: true,
+                    score_threshold: None,
+                    features_to_control: None,
+                    deadline_slot: None,
+                },
+                pause_quorum: None,
+                entry_fee: None,
+                grid_metric: GridMetric::Manhattan,
+            },
+            map: 0,
+            tiles: vec![1, 2, 3, 4, 5],
+            features: vec![],
+            units: vec![10, 20],
+            players: vec![100, 200],
+            play_phase: PlayPhase::Play,
+            turn_number: 0,
+            active_player: 0,
+            turn_start: 0,
+            standings: vec![],
+            winner: None,
+            phase_votes: vec![],
+            features_built: 0,
+        });
+        state
+    }
+
+    #[test]
+    fn sees_tiles_within_vision_range() {
+        let visibility = Visibility::compute(&state(), 100);
+        assert!(visibility.can_see(0, 0));
+        assert!(visibility.can_see(1, 0));
+        assert!(!visibility.can_see(2, 0));
+        assert!(!visibility.can_see(4, 0));
+    }
+
+    #[test]
+    fn entities_outside_vision_are_hidden_except_your_own() {
+        let state = state();
+        let visibility = Visibility::compute(&state, 100);
+        assert!(visibility.can_see_entity(&state, 10));
+        assert!(!visibility.can_see_entity(&state, 20));
+        // Player 200 has no vision at all, but still sees its own unit
+        let theirs = Visibility::compute(&state, 200);
+        assert!(theirs.can_see(4, 0));
+        assert!(theirs.can_see_entity(&state, 20));
+        assert!(!theirs.can_see_entity(&state, 10));
+    }
+
+    #[test]
+    fn inactive_units_give_no_vision() {
+        let mut state = state();
+        let active_key = state.schemas.key_index.clone().unwrap().active;
+        state.entities.as_mut().unwrap().get_mut(&10).unwrap().components.insert(active_key, ComponentActive { active: false }.to_serialized_component());
+        assert!(Visibility::compute(&state, 100).visible.is_empty());
+    }
+
+    #[test]
+    fn view_for_drops_hidden_units_and_their_tile_pointers() {
+        let mut state = state();
+        let occupant_key = state.schemas.key_index.clone().unwrap().occupant;
+        state.entities.as_mut().unwrap().get_mut(&5).unwrap().components.insert(occupant_key, ComponentOccupant { occupant_id: Some(20) }.to_serialized_component());
+
+        let view = state.view_for(100);
+        assert!(view.get_entity(20).is_err());
+        assert!(!view.index.as_ref().unwrap().units.contains(&20));
+        assert_eq!(view.get::<ComponentOccupant>(5).unwrap().occupant_id, None);
+        assert!(view.get_entity(10).is_ok());
+    }
+}
@@ -1,6 +1,7 @@
 use anchor_lang::{prelude::*, InstructionData};
 use anchor_lang::system_program::ID as system_program;
 use dominariworld::account::WorldConfig;
+pub use dominariworld::state::{ComponentPermissions, Permission};
 use solana_client_wasm::{WasmClient, solana_sdk::instruction::Instruction};
 use crate::util::fetch_account;
 
@@ -131,7 +132,10 @@ impl World {
         }]
     }    
 
-    pub async fn add_components_to_system_registration(&self, components: Vec<Pubkey>, system:Pubkey, instance:u64, payer: Pubkey) -> Vec<Instruction> {
+    /**
+     * Grants system the given permissions on components. payer has to be the instance authority.
+     */
+    pub async fn grant_component_permissions(&self, components: Vec<Pubkey>, permissions: ComponentPermissions, system:Pubkey, instance:u64, payer: Pubkey) -> Vec<Instruction> {
         let world_config_acc:dominariworld::account::WorldConfig = self.get_world_config().await.1;
 
         let world_instance = Pubkey::find_program_address(&[
@@ -153,7 +157,7 @@ impl World {
 
         vec![Instruction {
             program_id: self.pubkey,
-            accounts: dominariworld::accounts::AddComponentsToSystemRegistration {
+            accounts: dominariworld::accounts::GrantComponentPermissions {
                 payer,
                 system_program,
                 world_instance,
@@ -161,12 +165,229 @@ impl World {
                 system_registration,
                 system
             }.to_account_metas(None),
-            data: dominariworld::instruction::AddComponentsToSystemRegistration {
+            data: dominariworld::instruction::GrantComponentPermissions {
                 components,
+                permissions,
             }.data()
         }]
     }
 
+    /**
+     * Takes the given permissions on components away from system. payer has to be the instance authority.
+     */
+    pub async fn revoke_component_permissions(&self, components: Vec<Pubkey>, permissions: ComponentPermissions, system:Pubkey, instance:u64, payer: Pubkey) -> Vec<Instruction> {
+        let world_config_acc:dominariworld::account::WorldConfig = self.get_world_config().await.1;
+
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            &self.pubkey.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &world_config_acc.universe).0;
+
+        let instance_authority = Pubkey::find_program_address(&[
+            b"Instance_Authority".as_ref(),
+            world_instance.to_bytes().as_ref()
+        ], &self.pubkey).0;
+
+        let system_registration = Pubkey::find_program_address(&[
+            b"System_Registration",
+            world_instance.to_bytes().as_ref(),
+            system.to_bytes().as_ref()
+        ], &self.pubkey).0;
+
+        vec![Instruction {
+            program_id: self.pubkey,
+            accounts: dominariworld::accounts::RevokeComponentPermissions {
+                payer,
+                world_instance,
+                instance_authority,
+                system_registration,
+                system
+            }.to_account_metas(None),
+            data: dominariworld::instruction::RevokeComponentPermissions {
+                components,
+                permissions,
+            }.data()
+        }]
+    }
+
+
+    /**
+     * Drops components from system's registration entirely and returns their rent to payer, the instance authority.
+     */
+    pub async fn remove_components_from_system_registration(&self, components: Vec<Pubkey>, system:Pubkey, instance:u64, payer: Pubkey) -> Vec<Instruction> {
+        let world_config_acc:dominariworld::account::WorldConfig = self.get_world_config().await.1;
+
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            &self.pubkey.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &world_config_acc.universe).0;
+
+        let instance_authority = Pubkey::find_program_address(&[
+            b"Instance_Authority".as_ref(),
+            world_instance.to_bytes().as_ref()
+        ], &self.pubkey).0;
+
+        let system_registration = Pubkey::find_program_address(&[
+            b"System_Registration",
+            world_instance.to_bytes().as_ref(),
+            system.to_bytes().as_ref()
+        ], &self.pubkey).0;
+
+        vec![Instruction {
+            program_id: self.pubkey,
+            accounts: dominariworld::accounts::RemoveComponentsFromSystemRegistration {
+                payer,
+                system_program,
+                world_instance,
+                instance_authority,
+                system_registration,
+                system
+            }.to_account_metas(None),
+            data: dominariworld::instruction::RemoveComponentsFromSystemRegistration {
+                components,
+            }.data()
+        }]
+    }
+
+    /**
+     * Closes system's registration for the instance, returning the rent to payer, the instance authority.
+     */
+    pub async fn unregister_system(&self, system:Pubkey, instance:u64, payer: Pubkey) -> Vec<Instruction> {
+        let world_config_acc:dominariworld::account::WorldConfig = self.get_world_config().await.1;
+
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            &self.pubkey.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &world_config_acc.universe).0;
+
+        let instance_authority = Pubkey::find_program_address(&[
+            b"Instance_Authority".as_ref(),
+            world_instance.to_bytes().as_ref()
+        ], &self.pubkey).0;
+
+        let system_registration = Pubkey::find_program_address(&[
+            b"System_Registration",
+            world_instance.to_bytes().as_ref(),
+            system.to_bytes().as_ref()
+        ], &self.pubkey).0;
+
+        vec![Instruction {
+            program_id: self.pubkey,
+            accounts: dominariworld::accounts::UnregisterSystem {
+                payer,
+                world_instance,
+                instance_authority,
+                system_registration,
+                system
+            }.to_account_metas(None),
+            data: dominariworld::instruction::UnregisterSystem {}.data()
+        }]
+    }
+
+    /**
+     * Proposes new_authority as the instance authority. payer has to be the current instance authority.
+     */
+    pub async fn propose_instance_authority(&self, new_authority: Pubkey, instance:u64, payer: Pubkey) -> Vec<Instruction> {
+        let world_config_acc:dominariworld::account::WorldConfig = self.get_world_config().await.1;
+
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            &self.pubkey.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &world_config_acc.universe).0;
+
+        let instance_authority = Pubkey::find_program_address(&[
+            b"Instance_Authority".as_ref(),
+            world_instance.to_bytes().as_ref()
+        ], &self.pubkey).0;
+
+        vec![Instruction {
+            program_id: self.pubkey,
+            accounts: dominariworld::accounts::UpdateInstanceAuthority {
+                payer,
+                world_instance,
+                instance_authority,
+            }.to_account_metas(None),
+            data: dominariworld::instruction::ProposeInstanceAuthority {
+                new_authority,
+            }.data()
+        }]
+    }
+
+    /**
+     * Signed by the pending authority to take over the instance.
+     */
+    pub async fn accept_instance_authority(&self, instance:u64, new_authority: Pubkey) -> Vec<Instruction> {
+        let world_config_acc:dominariworld::account::WorldConfig = self.get_world_config().await.1;
+
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            &self.pubkey.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &world_config_acc.universe).0;
+
+        let instance_authority = Pubkey::find_program_address(&[
+            b"Instance_Authority".as_ref(),
+            world_instance.to_bytes().as_ref()
+        ], &self.pubkey).0;
+
+        vec![Instruction {
+            program_id: self.pubkey,
+            accounts: dominariworld::accounts::AcceptInstanceAuthority {
+                new_authority,
+                world_instance,
+                instance_authority,
+            }.to_account_metas(None),
+            data: dominariworld::instruction::AcceptInstanceAuthority {}.data()
+        }]
+    }
+
+    /**
+     * Sets the M-of-N signer set for the instance. Empty signers with a threshold of 0 goes back to the single authority key.
+     */
+    pub async fn set_instance_signers(&self, signers: Vec<Pubkey>, threshold: u8, instance:u64, payer: Pubkey) -> Vec<Instruction> {
+        let world_config_acc:dominariworld::account::WorldConfig = self.get_world_config().await.1;
+
+        let world_instance = Pubkey::find_program_address(&[
+            b"World".as_ref(),
+            &self.pubkey.as_ref(),
+            instance.to_be_bytes().as_ref()
+        ], &world_config_acc.universe).0;
+
+        let instance_authority = Pubkey::find_program_address(&[
+            b"Instance_Authority".as_ref(),
+            world_instance.to_bytes().as_ref()
+        ], &self.pubkey).0;
+
+        vec![Instruction {
+            program_id: self.pubkey,
+            accounts: dominariworld::accounts::UpdateInstanceAuthority {
+                payer,
+                world_instance,
+                instance_authority,
+            }.to_account_metas(None),
+            data: dominariworld::instruction::SetInstanceSigners {
+                signers,
+                threshold,
+            }.data()
+        }]
+    }
+
+    /**
+     * Appends co-signers to authority gated instructions when the instance is governed by a signer set.
+     * The payer counts as one approval, so pass the other signers here.
+     */
+    pub fn with_cosigners(ixs: Vec<Instruction>, cosigners: &[Pubkey]) -> Vec<Instruction> {
+        ixs.into_iter().map(|mut ix| {
+            for cosigner in cosigners {
+                ix.accounts.push(AccountMeta::new_readonly(*cosigner, true));
+            }
+            ix
+        }).collect()
+    }
 
     /*******************************************************************Account Fetch */
     pub async fn get_world_config(&self) -> (Pubkey, dominariworld::account::WorldConfig) {